@@ -0,0 +1,321 @@
+//! Documentation for built-in functions and `@if` conditions, and the
+//! directive keywords, mirroring the parser's keywords.
+//!
+//! The function and condition tables live in `tree-sitter-jake`, where the
+//! linter shares them.
+
+pub use tree_sitter_jake::builtins::{condition, function, Builtin, CONDITIONS, FUNCTIONS};
+
+use crate::guide;
+
+/// Markdown documentation for a function or condition: the signature, then
+/// the guide's description and example, or the table's summary for builtins
+/// the guide does not document.
+pub fn documentation(builtin: &Builtin) -> String {
+    let mut doc = format!("```jake\n{}\n```\n\n", builtin.signature());
+    match guide::function(builtin.name) {
+        Some(row) => {
+            doc.push_str(row.description);
+            if let Some(example) = row.example {
+                doc.push_str("\n\nExample: ");
+                doc.push_str(example);
+            }
+        }
+        None => doc.push_str(builtin.summary),
+    }
+    doc
+}
+
+/// Where a directive may appear.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    /// At the start of a line outside recipes.
+    File,
+    /// Before a recipe header, as recipe metadata.
+    Attribute,
+    /// In a recipe body.
+    Body,
+}
+
+/// A directive keyword and where the guide describes it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Directive {
+    pub keyword: &'static str,
+    pub scope: Scope,
+    pub summary: &'static str,
+    /// The guide section documenting the directive.
+    pub section: Option<&'static str>,
+}
+
+impl Directive {
+    /// Markdown documentation: the summary, then the guide section.
+    pub fn documentation(&self) -> String {
+        match self.section.and_then(guide::section) {
+            Some(section) => format!("{}\n\n{section}", self.summary),
+            None => self.summary.to_string(),
+        }
+    }
+}
+
+const fn directive(
+    keyword: &'static str,
+    scope: Scope,
+    summary: &'static str,
+    section: Option<&'static str>,
+) -> Directive {
+    Directive {
+        keyword,
+        scope,
+        summary,
+        section,
+    }
+}
+
+use Scope::{Attribute, Body, File};
+
+/// Every directive, in the order completion offers them.
+pub const DIRECTIVES: &[Directive] = &[
+    directive(
+        "@import",
+        File,
+        "Import recipes from another Jakefile",
+        Some("Basic Import"),
+    ),
+    directive(
+        "@dotenv",
+        File,
+        "Load variables from a .env file",
+        Some("Loading .env Files"),
+    ),
+    directive(
+        "@export",
+        File,
+        "Export a variable to every command",
+        Some("Exporting Variables"),
+    ),
+    directive(
+        "@require",
+        File,
+        "Fail unless environment variables are set",
+        Some("@require"),
+    ),
+    directive(
+        "@default",
+        File,
+        "Run the next recipe when none is named",
+        Some("Setting a Default"),
+    ),
+    directive(
+        "@pre",
+        File,
+        "Run a command before every recipe",
+        Some("Global Hooks"),
+    ),
+    directive(
+        "@post",
+        File,
+        "Run a command after every recipe",
+        Some("Global Hooks"),
+    ),
+    directive(
+        "@before",
+        File,
+        "Run a command before one recipe",
+        Some("Targeted Hooks"),
+    ),
+    directive(
+        "@after",
+        File,
+        "Run a command after one recipe",
+        Some("Targeted Hooks"),
+    ),
+    directive(
+        "@on_error",
+        File,
+        "Run a command when any recipe fails",
+        Some("Error Hooks"),
+    ),
+    directive(
+        "@group",
+        Attribute,
+        "Group the recipe in `jake --list`",
+        Some("@group"),
+    ),
+    directive(
+        "@desc",
+        Attribute,
+        "Describe the recipe in `jake --list`",
+        Some("@description"),
+    ),
+    directive(
+        "@description",
+        Attribute,
+        "Describe the recipe in `jake --list`",
+        Some("@description"),
+    ),
+    directive(
+        "@alias",
+        Attribute,
+        "Other names that run the recipe",
+        Some("Recipe Aliases"),
+    ),
+    directive(
+        "@quiet",
+        Attribute,
+        "Do not echo the recipe's commands",
+        Some("@quiet"),
+    ),
+    directive(
+        "@timeout",
+        Attribute,
+        "Stop the recipe after a duration such as `30s`, `5m` or `2h`",
+        None,
+    ),
+    directive(
+        "@platform",
+        Attribute,
+        "Only run the recipe on these platforms",
+        Some("@platform"),
+    ),
+    directive(
+        "@only",
+        Attribute,
+        "Alias for `@platform`",
+        Some("@platform"),
+    ),
+    directive(
+        "@only-os",
+        Attribute,
+        "Alias for `@platform`",
+        Some("@platform"),
+    ),
+    directive(
+        "@needs",
+        Attribute,
+        "Require commands before the recipe runs",
+        Some("@needs"),
+    ),
+    directive(
+        "@if",
+        Body,
+        "Run the following lines if a condition holds",
+        Some("Basic If/Else"),
+    ),
+    directive(
+        "@elif",
+        Body,
+        "Another condition of an `@if` block",
+        Some("If/Elif/Else"),
+    ),
+    directive(
+        "@else",
+        Body,
+        "Lines to run when no condition of an `@if` held",
+        Some("Basic If/Else"),
+    ),
+    directive("@end", Body, "Close an `@if` or `@each` block", None),
+    directive(
+        "@each",
+        Body,
+        "Run the following lines once per item",
+        Some("@each"),
+    ),
+    directive(
+        "@cd",
+        Body,
+        "Run the following commands in another directory",
+        Some("@cd"),
+    ),
+    directive(
+        "@cache",
+        Body,
+        "Skip the recipe when these files are unchanged",
+        Some("@cache"),
+    ),
+    directive(
+        "@watch",
+        Body,
+        "Files that re-run the recipe in watch mode",
+        Some("@watch"),
+    ),
+    directive(
+        "@confirm",
+        Body,
+        "Ask for confirmation before continuing",
+        Some("@confirm"),
+    ),
+    directive(
+        "@ignore",
+        Body,
+        "Continue when the next command fails",
+        Some("@ignore"),
+    ),
+    directive(
+        "@shell",
+        Body,
+        "Run the commands with another shell",
+        Some("@shell"),
+    ),
+    directive(
+        "@launch",
+        Body,
+        "Open a file or URL with the default application",
+        Some("@launch"),
+    ),
+    directive(
+        "@needs",
+        Body,
+        "Require commands before continuing",
+        Some("@needs"),
+    ),
+    directive(
+        "@require",
+        Body,
+        "Fail unless environment variables are set",
+        Some("@require"),
+    ),
+    directive(
+        "@export",
+        Body,
+        "Export a variable to the following commands",
+        Some("Exporting Variables"),
+    ),
+    directive(
+        "@pre",
+        Body,
+        "Run a command before this recipe",
+        Some("Recipe Hooks"),
+    ),
+    directive(
+        "@post",
+        Body,
+        "Run a command after this recipe",
+        Some("Recipe Hooks"),
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_documented_in_guide() {
+        let undocumented: Vec<_> = FUNCTIONS
+            .iter()
+            .chain(CONDITIONS)
+            .filter(|builtin| guide::function(builtin.name).is_none())
+            .map(|builtin| builtin.name)
+            .collect();
+        assert!(undocumented.is_empty(), "undocumented: {undocumented:?}");
+
+        for directive in DIRECTIVES {
+            if let Some(section) = directive.section {
+                assert!(
+                    guide::section(section).is_some(),
+                    "{} refers to missing section {section:?}",
+                    directive.keyword
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Call hierarchy over the recipe dependency graph.
+//!
+//! Recipes are the callable items. A recipe calls its dependencies and the
+//! recipes its `@needs cmd -> task` requirements point to. `@before` and
+//! `@after` hooks run along with the recipe they target, so they are listed
+//! among both its outgoing and its incoming calls as items of their own,
+//! with the hook's target name as the call site. Names are resolved like
+//! go-to-definition, so `docker:push` in the root file and `push` inside
+//! `docker.jake` are the same item.
+//!
+//! Items carry the URI of the document the hierarchy was prepared in; the
+//! incoming and outgoing calls of an item are looked up in the workspace
+//! rooted at that document.
+
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Position, SymbolKind,
+    Url,
+};
+use serde_json::{json, Value};
+use tree_sitter::Range;
+use tree_sitter_jake::ast::{
+    descendants_of_kind, kinds, node_text, AstNode, GlobalDirective, Jakefile, RecipeKind,
+};
+use tree_sitter_jake::workspace::{ModuleId, Workspace, WorkspaceRecipe};
+
+use crate::document::Document;
+use crate::resolve::{self, Symbol, Target};
+use crate::workspace;
+
+/// The document a hierarchy was prepared in and its workspace.
+pub struct Context<'a> {
+    pub uri: &'a Url,
+    pub document: &'a Document,
+    pub workspace: &'a Workspace,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Callable<'w> {
+    Recipe(&'w WorkspaceRecipe),
+    /// A targeted `@before`/`@after` hook, in the module it is written in.
+    Hook(ModuleId, GlobalDirective<'w>),
+}
+
+/// The recipe named at `position`.
+pub fn prepare(context: &Context<'_>, position: Position) -> Option<Vec<CallHierarchyItem>> {
+    let document = context.document;
+    let Symbol::Recipe(name) = resolve::classify(document.name_at(position)?, document.text())?
+    else {
+        return None;
+    };
+    let recipe = resolve::resolve_recipe(context.workspace, context.workspace.root(), name)?;
+    Some(vec![to_item(context, Callable::Recipe(recipe))?])
+}
+
+/// The URI of the document `item` was prepared in.
+pub fn root_uri(item: &CallHierarchyItem) -> Option<Url> {
+    let root = item.data.as_ref()?.get("root")?.as_str()?;
+    Url::parse(root).ok()
+}
+
+/// The recipes that call the recipe `item` and the hooks that target it, or
+/// the recipes a hook runs with.
+pub fn incoming_calls(
+    context: &Context<'_>,
+    item: &CallHierarchyItem,
+) -> Option<Vec<CallHierarchyIncomingCall>> {
+    let callee = callable(context.workspace, item)?;
+    let workspace = context.workspace;
+    let mut incoming = Vec::new();
+    for caller in workspace.recipes() {
+        let ranges: Vec<_> = calls(workspace, caller)
+            .into_iter()
+            .filter(|(call, _)| *call == callee)
+            .flat_map(|(_, ranges)| ranges)
+            .collect();
+        if ranges.is_empty() {
+            continue;
+        }
+        incoming.push(CallHierarchyIncomingCall {
+            from: to_item(context, Callable::Recipe(caller))?,
+            from_ranges: lsp_ranges(context, caller.module, &ranges)?,
+        });
+    }
+    if let Callable::Recipe(recipe) = callee {
+        for (module, directive, target) in hooks(workspace, recipe) {
+            incoming.push(CallHierarchyIncomingCall {
+                from: to_item(context, Callable::Hook(module, directive))?,
+                from_ranges: lsp_ranges(context, module, &[target])?,
+            });
+        }
+    }
+    Some(incoming)
+}
+
+/// What the recipe `item` calls. Hooks call nothing.
+pub fn outgoing_calls(
+    context: &Context<'_>,
+    item: &CallHierarchyItem,
+) -> Option<Vec<CallHierarchyOutgoingCall>> {
+    let Callable::Recipe(caller) = callable(context.workspace, item)? else {
+        return Some(Vec::new());
+    };
+    calls(context.workspace, caller)
+        .into_iter()
+        .map(|(callee, ranges)| {
+            Some(CallHierarchyOutgoingCall {
+                to: to_item(context, callee)?,
+                from_ranges: lsp_ranges(context, caller.module, &ranges)?,
+            })
+        })
+        .collect()
+}
+
+/// Everything `caller` runs, in source order, with the ranges in its module
+/// that make it run: dependency and `@needs` names, and for hooks the name
+/// of the recipe itself.
+fn calls<'w>(
+    workspace: &'w Workspace,
+    caller: &WorkspaceRecipe,
+) -> Vec<(Callable<'w>, Vec<Range>)> {
+    let mut calls: Vec<(Callable<'w>, Vec<Range>)> = Vec::new();
+    let mut add = |callee, range| match calls.iter_mut().find(|(call, _)| *call == callee) {
+        Some((_, ranges)) => ranges.push(range),
+        None => calls.push((callee, vec![range])),
+    };
+
+    let module = workspace.module(caller.module);
+    if let Some(recipe) = resolve::recipe_node(workspace, caller) {
+        let mut names = descendants_of_kind(recipe.syntax(), kinds::DEPENDENCY_NAME);
+        names.extend(descendants_of_kind(recipe.syntax(), kinds::IDENTIFIER));
+        names.sort_by_key(|node| node.start_byte());
+        for node in names {
+            // Identifiers call a recipe only after `@needs cmd ->`; the
+            // others name the recipe itself, commands or parameters.
+            let is_call = node.kind() == kinds::DEPENDENCY_NAME
+                || node.prev_sibling().is_some_and(|prev| prev.kind() == "->");
+            if !is_call {
+                continue;
+            }
+            let name = node_text(node, &module.source);
+            if let Some(callee) = resolve::resolve_recipe(workspace, caller.module, name) {
+                add(Callable::Recipe(callee), node.range());
+            }
+        }
+    }
+
+    let name_range = workspace.entry(caller).name_range;
+    for (module, directive, _) in hooks(workspace, caller) {
+        add(Callable::Hook(module, directive), name_range);
+    }
+    calls
+}
+
+/// The `@before`/`@after` hooks targeting `recipe`, with the range of each
+/// target name.
+fn hooks<'w>(
+    workspace: &'w Workspace,
+    recipe: &WorkspaceRecipe,
+) -> Vec<(ModuleId, GlobalDirective<'w>, Range)> {
+    let mut hooks = Vec::new();
+    for (id, module) in workspace.modules() {
+        let Some(jakefile) = Jakefile::cast(module.tree.root_node()) else {
+            continue;
+        };
+        for directive in jakefile.global_directives() {
+            let Some(target) = directive.hook().and_then(|hook| hook.target()) else {
+                continue;
+            };
+            let resolved = resolve::resolve_recipe(workspace, id, target.text(&module.source));
+            if resolved == Some(recipe) {
+                hooks.push((id, directive, target.range()));
+            }
+        }
+    }
+    hooks
+}
+
+fn to_item(context: &Context<'_>, callable: Callable<'_>) -> Option<CallHierarchyItem> {
+    let workspace = context.workspace;
+    let root = context.uri.as_str();
+    match callable {
+        Callable::Recipe(recipe) => {
+            let entry = workspace.entry(recipe);
+            let location = location(context, recipe.module, entry.range)?;
+            Some(CallHierarchyItem {
+                name: recipe.name.clone(),
+                kind: match entry.kind {
+                    RecipeKind::File => SymbolKind::FILE,
+                    _ => SymbolKind::FUNCTION,
+                },
+                tags: None,
+                detail: entry.description.clone(),
+                uri: location.uri,
+                range: location.range,
+                selection_range: location_range(context, recipe.module, entry.name_range)?,
+                data: Some(json!({ "root": root, "recipe": recipe.name })),
+            })
+        }
+        Callable::Hook(module_id, directive) => {
+            let module = workspace.module(module_id);
+            let hook = directive.hook()?;
+            let target = hook.target()?;
+            let location = location(context, module_id, directive.range())?;
+            let command = hook
+                .command_range()
+                .map(|range| module.source[range].trim().to_string());
+            Some(CallHierarchyItem {
+                name: format!("{} {}", directive.keyword()?, target.text(&module.source)),
+                kind: SymbolKind::EVENT,
+                tags: None,
+                detail: command,
+                uri: location.uri,
+                range: location.range,
+                selection_range: location_range(context, module_id, target.range())?,
+                data: Some(json!({
+                    "root": root,
+                    "hook": [module_id.0, directive.syntax().start_byte()],
+                })),
+            })
+        }
+    }
+}
+
+/// The recipe or hook an item from [`prepare`] or a call stands for.
+fn callable<'w>(workspace: &'w Workspace, item: &CallHierarchyItem) -> Option<Callable<'w>> {
+    let data = item.data.as_ref()?;
+    if let Some(name) = data.get("recipe").and_then(Value::as_str) {
+        return workspace.recipe(name).map(Callable::Recipe);
+    }
+    let hook = data.get("hook")?.as_array()?;
+    let (module, start) = match hook.as_slice() {
+        [module, start] => (module.as_u64()? as usize, start.as_u64()? as usize),
+        _ => return None,
+    };
+    let (id, module) = workspace.modules().find(|(id, _)| id.0 == module)?;
+    let jakefile = Jakefile::cast(module.tree.root_node())?;
+    let directive = jakefile
+        .global_directives()
+        .find(|directive| directive.syntax().start_byte() == start)?;
+    Some(Callable::Hook(id, directive))
+}
+
+fn location(context: &Context<'_>, module: ModuleId, range: Range) -> Option<lsp_types::Location> {
+    workspace::location(
+        context.uri,
+        context.document,
+        context.workspace,
+        Target { module, range },
+    )
+}
+
+fn location_range(
+    context: &Context<'_>,
+    module: ModuleId,
+    range: Range,
+) -> Option<lsp_types::Range> {
+    location(context, module, range).map(|location| location.range)
+}
+
+fn lsp_ranges(
+    context: &Context<'_>,
+    module: ModuleId,
+    ranges: &[Range],
+) -> Option<Vec<lsp_types::Range>> {
+    ranges
+        .iter()
+        .map(|&range| location_range(context, module, range))
+        .collect()
+}
@@ -0,0 +1,217 @@
+//! `textDocument/codeAction`: quick fixes for lint results, and the
+//! refactors in [`refactor`].
+//!
+//! Every lint fix is offered as the preferred quick fix for its diagnostic.
+//! An unknown dependency may also be a recipe that has not been written yet,
+//! and an undefined `{{name}}` a variable that has not been declared, so
+//! those get a second fix that adds the missing definition.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use jake_lint::{Edit, Fix, Linter};
+use lsp_types::{CodeAction, CodeActionKind, Diagnostic, TextEdit, Url, WorkspaceEdit};
+use tree_sitter_jake::ast::{AstNode, Jakefile};
+use tree_sitter_jake::workspace::Workspace;
+
+use crate::diagnostics;
+use crate::document::Document;
+use crate::refactor;
+
+/// The kinds of action the server offers.
+pub fn kinds() -> Vec<CodeActionKind> {
+    vec![
+        CodeActionKind::QUICKFIX,
+        CodeActionKind::REFACTOR_EXTRACT,
+        CodeActionKind::REFACTOR_INLINE,
+        CodeActionKind::REFACTOR_REWRITE,
+        refactor::refactor_move(),
+    ]
+}
+
+/// Quick fixes and refactors for `range` of `document`, of the kinds in
+/// `only`. `workspace` is rooted at the document.
+pub fn code_actions(
+    uri: &Url,
+    document: &Document,
+    workspace: &Workspace,
+    linter: &Linter,
+    range: Range<usize>,
+    only: Option<&[CodeActionKind]>,
+) -> Vec<CodeAction> {
+    let mut actions = Vec::new();
+    if wants(only, &CodeActionKind::QUICKFIX) {
+        actions = quick_fixes(uri, document, workspace, linter, range.clone());
+    }
+    actions.extend(refactor::refactors(
+        uri,
+        document,
+        workspace,
+        range,
+        |kind| wants(only, kind),
+    ));
+    actions
+}
+
+/// Quick fixes for the lint results of `document` that touch `range`.
+fn quick_fixes(
+    uri: &Url,
+    document: &Document,
+    workspace: &Workspace,
+    linter: &Linter,
+    range: Range<usize>,
+) -> Vec<CodeAction> {
+    let mut actions = Vec::new();
+    for lint in linter.lint_module(workspace, workspace.root()) {
+        if lint.range.end < range.start || lint.range.start > range.end {
+            continue;
+        }
+        let diagnostic = diagnostics::lint_diagnostic(document, &lint);
+        if let Some(fix) = &lint.fix {
+            actions.push(quick_fix(uri, document, fix, &diagnostic, true));
+        }
+        let name = &document.text()[lint.range.clone()];
+        let definition = match lint.code {
+            "JK001" => create_recipe(document.text(), name),
+            "JK002" => Some(add_variable(document, workspace, name)),
+            _ => None,
+        };
+        if let Some(fix) = definition {
+            actions.push(quick_fix(uri, document, &fix, &diagnostic, false));
+        }
+    }
+    actions
+}
+
+/// Append an empty task named `name`. Namespaced names belong to an import
+/// and are left alone.
+fn create_recipe(source: &str, name: &str) -> Option<Fix> {
+    if name.contains([':', '.', '/']) {
+        return None;
+    }
+    let separator = append_separator(source);
+    Some(Fix::new(
+        format!("Create recipe '{name}'"),
+        vec![Edit::insert(
+            source.len(),
+            format!("{separator}task {name}:\n"),
+        )],
+    ))
+}
+
+/// What to insert before appending a paragraph to `source`, so that it is
+/// separated by one blank line.
+pub(crate) fn append_separator(source: &str) -> &'static str {
+    if source.is_empty() || source.ends_with("\n\n") {
+        ""
+    } else if source.ends_with('\n') {
+        "\n"
+    } else {
+        "\n\n"
+    }
+}
+
+/// Declare `name` with an empty value after the last variable or, when
+/// there are none, after the imports.
+fn add_variable(document: &Document, workspace: &Workspace, name: &str) -> Fix {
+    let source = document.text();
+    let title = format!("Add variable '{name}'");
+    let declaration = format!("{name} = \"\"\n");
+    let index = &workspace.module(workspace.root()).index;
+    if let Some(last) = index.variables().last() {
+        let (offset, newline) = line_end(source, last.range.end_byte);
+        let edit = Edit::insert(offset, format!("{newline}{declaration}"));
+        return Fix::new(title, vec![edit]);
+    }
+
+    // Otherwise after the imports and the shebang, in a paragraph of its own.
+    let shebang =
+        Jakefile::cast(document.tree().root_node()).and_then(|jakefile| jakefile.shebang());
+    let preamble = index
+        .imports()
+        .iter()
+        .map(|import| import.range.end_byte)
+        .chain(shebang.map(|shebang| shebang.end_byte()))
+        .max();
+    let edit = match preamble {
+        Some(end) => {
+            let (offset, newline) = line_end(source, end);
+            Edit::insert(offset, format!("{newline}\n{declaration}"))
+        }
+        None => Edit::insert(0, format!("{declaration}\n")),
+    };
+    Fix::new(title, vec![edit])
+}
+
+/// The offset just past the line that the range ending at `offset` ends on,
+/// and the newline to insert first when that line lacks its own.
+fn line_end(source: &str, offset: usize) -> (usize, &'static str) {
+    if offset > 0 && source[..offset].ends_with('\n') {
+        return (offset, "");
+    }
+    match source[offset..].find('\n') {
+        Some(newline) => (offset + newline + 1, ""),
+        None => (source.len(), "\n"),
+    }
+}
+
+/// Whether a client that asked for `only` accepts actions of `kind`. Kinds
+/// are hierarchical, so asking for `refactor` includes `refactor.inline`.
+fn wants(only: Option<&[CodeActionKind]>, kind: &CodeActionKind) -> bool {
+    let Some(only) = only else {
+        return true;
+    };
+    let kind = kind.as_str();
+    only.iter().any(|requested| {
+        let requested = requested.as_str();
+        requested.is_empty()
+            || kind
+                .strip_prefix(requested)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+fn quick_fix(
+    uri: &Url,
+    document: &Document,
+    fix: &Fix,
+    diagnostic: &Diagnostic,
+    is_preferred: bool,
+) -> CodeAction {
+    let edits = fix
+        .edits
+        .iter()
+        .map(|edit| TextEdit::new(document.range(edit.range.clone()), edit.replacement.clone()))
+        .collect();
+    CodeAction {
+        title: fix.title.clone(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEdit::new(HashMap::from([(uri.clone(), edits)]))),
+        is_preferred: Some(is_preferred),
+        ..CodeAction::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_end() {
+        let source = "a = \"1\"\nb = \"2\"";
+        assert_eq!(line_end(source, 3), (8, ""));
+        assert_eq!(line_end(source, 8), (8, ""));
+        assert_eq!(line_end(source, 12), (source.len(), "\n"));
+    }
+
+    #[test]
+    fn test_wants() {
+        let quickfix = CodeActionKind::QUICKFIX;
+        assert!(wants(None, &quickfix));
+        assert!(wants(Some(&[CodeActionKind::EMPTY]), &quickfix));
+        assert!(wants(Some(&[CodeActionKind::QUICKFIX]), &quickfix));
+        assert!(!wants(Some(&[CodeActionKind::REFACTOR]), &quickfix));
+        assert!(!wants(Some(&[CodeActionKind::new("quick")]), &quickfix));
+    }
+}
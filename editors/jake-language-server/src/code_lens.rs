@@ -0,0 +1,66 @@
+//! `textDocument/codeLens`: run, dry-run, watch and run-with-arguments
+//! lenses above every recipe header, executing the commands in [`run`].
+//!
+//! "Run with args…" passes one `name=value` argument per parameter, with
+//! the default filled in, for the client to let the user edit before it
+//! executes the command.
+
+use lsp_types::{CodeLens, Command, Url};
+use tree_sitter_jake::ast::{AstNode, Jakefile, Parameter, ValueKind};
+
+use crate::document::Document;
+use crate::run;
+
+pub fn code_lenses(uri: &Url, document: &Document) -> Vec<CodeLens> {
+    let source = document.text();
+    let Some(jakefile) = Jakefile::cast(document.tree().root_node()) else {
+        return Vec::new();
+    };
+    let mut lenses = Vec::new();
+    for recipe in jakefile.recipes() {
+        let (Some(header), Some(name)) = (recipe.header(), recipe.name(source)) else {
+            continue;
+        };
+        let range = document.range(header.byte_range());
+        let arguments = vec![uri.as_str().into(), name.into()];
+        let suggested: Vec<String> = recipe
+            .parameters()
+            .into_iter()
+            .filter(|parameter| !parameter.is_variadic())
+            .filter_map(|parameter| suggested_argument(parameter, source))
+            .collect();
+        let commands = [
+            ("▶ Run", run::RUN, arguments.clone()),
+            ("Dry run", run::DRY_RUN, arguments.clone()),
+            ("Watch", run::WATCH, arguments.clone()),
+            (
+                "Run with args…",
+                run::RUN_WITH_ARGS,
+                [arguments, vec![suggested.into()]].concat(),
+            ),
+        ];
+        lenses.extend(commands.map(|(title, command, arguments)| CodeLens {
+            range,
+            command: Some(Command {
+                title: title.to_string(),
+                command: command.to_string(),
+                arguments: Some(arguments),
+            }),
+            data: None,
+        }));
+    }
+    lenses
+}
+
+/// `name=default`, or `name=` for a parameter without a default.
+fn suggested_argument(parameter: Parameter<'_>, source: &str) -> Option<String> {
+    let name = parameter.name()?.text(source);
+    let default = match parameter.default_value() {
+        Some(value) => match value.kind() {
+            Some(ValueKind::String(string)) => string.value(source).into_owned(),
+            _ => value.text(source).to_string(),
+        },
+        None => String::new(),
+    };
+    Some(format!("{name}={default}"))
+}
@@ -0,0 +1,464 @@
+//! `textDocument/completion`.
+//!
+//! The context is read from the text of the current line rather than the
+//! tree, because the line being typed rarely parses: `task deploy: [do` and
+//! `    @` are both syntax errors until they are finished. The tree and the
+//! workspace then supply the names to offer.
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Documentation, InsertTextFormat,
+    MarkupContent, MarkupKind, Position, TextEdit,
+};
+use tree_sitter_jake::ast::{AstNode, Jakefile, Recipe};
+use tree_sitter_jake::workspace::Workspace;
+
+use crate::builtins::{self, Builtin, Scope};
+use crate::document::Document;
+use crate::guide;
+
+/// What the cursor is completing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Context {
+    /// A name in a `[...]` dependency list.
+    Dependency,
+    /// A recipe name after `@before`, `@after` or `@needs cmd ->`.
+    RecipeName,
+    /// An expression inside `{{ }}`.
+    Interpolation,
+    /// A condition function after `@if` or `@elif`.
+    Condition,
+    /// A directive keyword after `@`.
+    Directive { in_body: bool },
+}
+
+pub fn completions(
+    document: &Document,
+    workspace: &Workspace,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let text = document.text();
+    let offset = document.offset(position);
+    let line_start = text[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+    let line = &text[line_start..offset];
+    let Some(context) = context(line) else {
+        return Vec::new();
+    };
+
+    let prefix_start = offset - prefix_len(line, context);
+    let completer = Completer {
+        replace: document.range(prefix_start..offset),
+        items: Vec::new(),
+    };
+    let recipe = line
+        .starts_with(char::is_whitespace)
+        .then(|| enclosing_recipe(document, position.line))
+        .flatten();
+    match context {
+        Context::Dependency | Context::RecipeName => {
+            let current = recipe_on_line(document, position.line);
+            recipes(completer, workspace, current.and_then(|r| r.name(text)))
+        }
+        Context::Interpolation => interpolation(completer, document, workspace, recipe, offset),
+        Context::Condition => builtins(completer, builtins::CONDITIONS),
+        Context::Directive { in_body } => {
+            let blocks = match in_body {
+                true => open_blocks(text, line_start),
+                false => Vec::new(),
+            };
+            directives(completer, in_body, &blocks)
+        }
+    }
+}
+
+fn context(line: &str) -> Option<Context> {
+    let trimmed = line.trim_start();
+    let is_indented = trimmed.len() < line.len();
+
+    if let Some(open) = line.rfind("{{") {
+        if !line[open..].contains("}}") {
+            return Some(Context::Interpolation);
+        }
+    }
+    if let Some(keyword) = trimmed.strip_prefix('@') {
+        if keyword.chars().all(is_keyword_char) {
+            return Some(Context::Directive {
+                in_body: is_indented,
+            });
+        }
+    }
+    let keyword = trimmed.split_whitespace().next().unwrap_or_default();
+    let rest = trimmed[keyword.len()..].trim_start();
+    match keyword {
+        // Conditions take no operators, so only the name is completed.
+        "@if" | "@elif" => return (!rest.contains('(')).then_some(Context::Condition),
+        "@before" | "@after" if !is_indented => {
+            return (!rest.contains(char::is_whitespace)).then_some(Context::RecipeName);
+        }
+        "@needs" => {
+            let before_word = rest.trim_end_matches(is_name_char).trim_end();
+            return before_word.ends_with("->").then_some(Context::RecipeName);
+        }
+        _ => {}
+    }
+    if !is_indented {
+        if let Some(open) = line.rfind('[') {
+            if !line[open..].contains(']') {
+                return Some(Context::Dependency);
+            }
+        }
+    }
+    None
+}
+
+/// The length of the partial word before the cursor that completion
+/// replaces.
+fn prefix_len(line: &str, context: Context) -> usize {
+    let is_part = |c: char| match context {
+        Context::Dependency | Context::RecipeName => {
+            is_name_char(c) || matches!(c, '.' | '/' | ':')
+        }
+        Context::Interpolation => is_name_char(c) || matches!(c, '$' | '@'),
+        Context::Condition => is_name_char(c),
+        Context::Directive { .. } => is_keyword_char(c) || c == '@',
+    };
+    let word = line
+        .rsplit(|c: char| !is_part(c))
+        .next()
+        .unwrap_or_default();
+    if let Context::Directive { .. } = context {
+        // Only the `@` that starts the directive belongs to it.
+        return word.rfind('@').map_or(0, |at| word.len() - at);
+    }
+    word.len()
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+fn is_keyword_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c == '_' || c == '-'
+}
+
+/// The recipe whose body contains line `row`: the first unindented line
+/// above it must be the recipe's header.
+fn enclosing_recipe(document: &Document, row: u32) -> Option<Recipe<'_>> {
+    let text = document.text();
+    let lines: Vec<&str> = text.lines().take(row as usize).collect();
+    let header_row = lines
+        .iter()
+        .rposition(|line| !line.is_empty() && !line.starts_with(char::is_whitespace))?;
+    recipe_on_line(document, header_row as u32)
+}
+
+/// The recipe whose header is on line `row`.
+fn recipe_on_line(document: &Document, row: u32) -> Option<Recipe<'_>> {
+    let jakefile = Jakefile::cast(document.tree().root_node())?;
+    let mut recipes = jakefile.recipes();
+    recipes.find(|recipe| {
+        recipe
+            .header()
+            .is_some_and(|header| header.start_position().row == row as usize)
+    })
+}
+
+/// The `@if`/`@each` blocks open at the line starting at `line_start`,
+/// innermost last, counted from the recipe header the way the executor
+/// pairs them.
+fn open_blocks(text: &str, line_start: usize) -> Vec<&'static str> {
+    let mut blocks = Vec::new();
+    for line in text[..line_start].lines().rev() {
+        if !line.is_empty() && !line.starts_with(char::is_whitespace) {
+            break;
+        }
+        let opener = match line.split_whitespace().next() {
+            Some("@end") => {
+                blocks.push("@end");
+                continue;
+            }
+            Some("@if") => "@if",
+            Some("@each") => "@each",
+            _ => continue,
+        };
+        // Reading upwards, an `@end` below closes this block.
+        if blocks.last() == Some(&"@end") {
+            blocks.pop();
+        } else {
+            blocks.push(opener);
+        }
+    }
+    blocks.retain(|block| *block != "@end");
+    blocks.reverse();
+    blocks
+}
+
+struct Completer {
+    /// The partial word the items replace.
+    replace: lsp_types::Range,
+    items: Vec<CompletionItem>,
+}
+
+impl Completer {
+    fn push(
+        &mut self,
+        label: String,
+        kind: CompletionItemKind,
+        detail: Option<String>,
+        documentation: Option<String>,
+    ) -> &mut CompletionItem {
+        let text_edit = TextEdit::new(self.replace, label.clone());
+        self.items.push(CompletionItem {
+            label,
+            kind: Some(kind),
+            detail,
+            documentation: documentation.map(|value| {
+                Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value,
+                })
+            }),
+            text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+            ..CompletionItem::default()
+        });
+        self.items.last_mut().expect("item was just pushed")
+    }
+}
+
+/// Every recipe and alias in the workspace except `current` and the aliases
+/// of private recipes, with namespaces written as `ns:`.
+fn recipes(
+    mut completer: Completer,
+    workspace: &Workspace,
+    current: Option<&str>,
+) -> Vec<CompletionItem> {
+    for recipe in workspace.recipes() {
+        if Some(recipe.name.as_str()) == current {
+            continue;
+        }
+        let entry = workspace.entry(recipe);
+        let mut documentation = entry.description.clone();
+        if let Some(source_file) = &recipe.origin.source_file {
+            let file = source_file
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy();
+            let defined_in = format!("Defined in `{file}`");
+            documentation = Some(match documentation {
+                Some(description) => format!("{description}\n\n{defined_in}"),
+                None => defined_in,
+            });
+        }
+        let label = recipe.name.replace('.', ":");
+        let kind = entry.kind.as_str().to_string();
+        completer.push(
+            label.clone(),
+            CompletionItemKind::FUNCTION,
+            Some(kind),
+            documentation,
+        );
+        if entry.is_private() {
+            continue;
+        }
+        for alias in &entry.aliases {
+            completer.push(
+                alias.name.clone(),
+                CompletionItemKind::FUNCTION,
+                Some(format!("alias of {label}")),
+                entry.description.clone(),
+            );
+        }
+    }
+    completer.items
+}
+
+fn interpolation(
+    mut completer: Completer,
+    document: &Document,
+    workspace: &Workspace,
+    recipe: Option<Recipe<'_>>,
+    offset: usize,
+) -> Vec<CompletionItem> {
+    let source = document.text();
+    if let Some(recipe) = recipe {
+        let name = recipe.name(source).unwrap_or_default();
+        for parameter in recipe.parameters() {
+            let Some(id) = parameter.name() else {
+                continue;
+            };
+            completer.push(
+                id.text(source).to_string(),
+                CompletionItemKind::VARIABLE,
+                Some(format!("parameter of {name}")),
+                Some(format!(
+                    "```jake\n{}{}\n```",
+                    parameter.kleene().unwrap_or_default(),
+                    parameter.text(source)
+                )),
+            );
+        }
+        if is_in_each(document, offset) {
+            completer.push(
+                "item".to_string(),
+                CompletionItemKind::VARIABLE,
+                Some("@each item".to_string()),
+                guide::section("@each").map(str::to_string),
+            );
+        }
+    }
+
+    for variable in workspace.variables() {
+        let is_first = workspace
+            .variable(&variable.name)
+            .is_some_and(|first| first == variable);
+        if !is_first
+            || completer
+                .items
+                .iter()
+                .any(|item| item.label == variable.name)
+        {
+            continue;
+        }
+        let entry = workspace.variable_entry(variable);
+        completer.push(
+            variable.name.clone(),
+            CompletionItemKind::VARIABLE,
+            Some(entry.value.clone()),
+            None,
+        );
+    }
+
+    let positional = guide::section("Positional Arguments").map(str::to_string);
+    completer.push(
+        "$1".to_string(),
+        CompletionItemKind::CONSTANT,
+        Some("first positional argument".to_string()),
+        positional,
+    );
+    completer.push(
+        "$@".to_string(),
+        CompletionItemKind::CONSTANT,
+        Some("all positional arguments".to_string()),
+        guide::section("All Arguments ({{$@}})").map(str::to_string),
+    );
+
+    builtins(completer, builtins::FUNCTIONS)
+}
+
+/// Whether `offset` is inside an `@each` block, going by the lines above it.
+fn is_in_each(document: &Document, offset: usize) -> bool {
+    let text = document.text();
+    let line_start = text[..offset].rfind('\n').map_or(0, |newline| newline + 1);
+    open_blocks(text, line_start).contains(&"@each")
+}
+
+/// Functions or conditions, inserted as snippets with their parameters as
+/// placeholders.
+fn builtins(mut completer: Completer, builtins: &[Builtin]) -> Vec<CompletionItem> {
+    for builtin in builtins {
+        let placeholders: Vec<_> = builtin
+            .parameters
+            .iter()
+            .enumerate()
+            .map(|(i, parameter)| format!("${{{}:{parameter}}}", i + 1))
+            .collect();
+        let snippet = format!("{}({})", builtin.name, placeholders.join(", "));
+        let item = completer.push(
+            builtin.name.to_string(),
+            CompletionItemKind::FUNCTION,
+            Some(builtin.signature()),
+            Some(builtins::documentation(builtin)),
+        );
+        if let Some(CompletionTextEdit::Edit(edit)) = &mut item.text_edit {
+            edit.new_text = snippet;
+        }
+        item.insert_text_format = Some(InsertTextFormat::SNIPPET);
+    }
+    completer.items
+}
+
+/// Directive keywords valid at this level. In a body, `@elif` and `@else`
+/// need an open `@if`, and `@end` any open block.
+fn directives(
+    mut completer: Completer,
+    in_body: bool,
+    open_blocks: &[&str],
+) -> Vec<CompletionItem> {
+    for directive in builtins::DIRECTIVES {
+        let is_valid = match directive.scope {
+            Scope::File | Scope::Attribute => !in_body,
+            Scope::Body => {
+                in_body
+                    && match directive.keyword {
+                        "@elif" | "@else" => open_blocks.last() == Some(&"@if"),
+                        "@end" => !open_blocks.is_empty(),
+                        _ => true,
+                    }
+            }
+        };
+        if is_valid {
+            completer.push(
+                directive.keyword.to_string(),
+                CompletionItemKind::KEYWORD,
+                Some(directive.summary.to_string()),
+                Some(directive.documentation()),
+            );
+        }
+    }
+    completer.items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context() {
+        assert_eq!(context("task deploy: [bu"), Some(Context::Dependency));
+        assert_eq!(
+            context("task deploy: [build, docker:"),
+            Some(Context::Dependency)
+        );
+        assert_eq!(context("task deploy: [build]"), None);
+        assert_eq!(context("    echo {{ver"), Some(Context::Interpolation));
+        assert_eq!(context("    echo {{ver}} "), None);
+        assert_eq!(
+            context("    @i"),
+            Some(Context::Directive { in_body: true })
+        );
+        assert_eq!(context("@"), Some(Context::Directive { in_body: false }));
+        assert_eq!(context("    @if "), Some(Context::Condition));
+        assert_eq!(context("    @elif is_"), Some(Context::Condition));
+        assert_eq!(context("    @if env("), None);
+        assert_eq!(context("@before "), Some(Context::RecipeName));
+        assert_eq!(context("@before build echo"), None);
+        assert_eq!(context("    @needs docker -> "), Some(Context::RecipeName));
+        assert_eq!(
+            context("    @needs docker -> ins"),
+            Some(Context::RecipeName)
+        );
+        assert_eq!(context("    @needs docker "), None);
+        assert_eq!(context("    echo done"), None);
+    }
+
+    #[test]
+    fn test_prefix_len() {
+        assert_eq!(
+            prefix_len("task deploy: [docker:bu", Context::Dependency),
+            9
+        );
+        assert_eq!(prefix_len("    echo {{$", Context::Interpolation), 1);
+        assert_eq!(prefix_len("    echo {{$@", Context::Interpolation), 2);
+        assert_eq!(
+            prefix_len("    @el", Context::Directive { in_body: true }),
+            3
+        );
+    }
+
+    #[test]
+    fn test_open_blocks() {
+        let text = "task build:\n    @if env(CI)\n        @each a b\n            echo {{item}}\n        @end\n";
+        assert_eq!(open_blocks(text, text.len()), ["@if"]);
+        let text = "task build:\n    @each a b\n        @if env(CI)\n";
+        assert_eq!(open_blocks(text, text.len()), ["@each", "@if"]);
+    }
+}
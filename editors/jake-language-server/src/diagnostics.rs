@@ -0,0 +1,154 @@
+//! Syntax errors, import errors, missing paths and lint results for an open
+//! document.
+//!
+//! Lint results are kept between runs in a [`DiagnosticsCache`], so after an
+//! edit only the recipes that changed are linted again. When an imported file
+//! changes, the recipes of the document keep their results unless a name
+//! they can see was added, removed or renamed.
+
+use std::path::{Path, PathBuf};
+
+use jake_lint::{LintCache, Linter, Severity};
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use tree_sitter::Node;
+use tree_sitter_jake::workspace::Workspace;
+
+use crate::document::Document;
+use crate::document_links::{self, Target};
+
+/// What the last diagnostics run for a document leaves to the next.
+#[derive(Debug, Default)]
+pub struct DiagnosticsCache {
+    /// The document version the results are for.
+    version: Option<i32>,
+    lint: LintCache,
+    /// Files of the workspace the document was last linted in.
+    files: Vec<PathBuf>,
+}
+
+impl DiagnosticsCache {
+    /// Whether the results depend on the file at `path`.
+    pub fn depends_on(&self, path: &Path) -> bool {
+        self.files.iter().any(|file| file == path)
+    }
+}
+
+/// Everything to report for `document`, in source order. `workspace` is
+/// rooted at the document.
+pub fn diagnostics(
+    document: &Document,
+    workspace: &Workspace,
+    linter: &Linter,
+    cache: &mut DiagnosticsCache,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    collect_syntax_errors(document, document.tree().root_node(), &mut diagnostics);
+
+    for error in workspace.errors() {
+        if error.importer == workspace.root() {
+            diagnostics.push(diagnostic(
+                document.node_range(error.range),
+                DiagnosticSeverity::ERROR,
+                None,
+                error.to_string(),
+            ));
+        }
+    }
+    for reference in document_links::path_references(document, workspace) {
+        let message = match reference.target {
+            Target::Missing => format!("'{}' does not exist", reference.path),
+            Target::Glob(files) if files.is_empty() => {
+                format!("'{}' matches no files", reference.path)
+            }
+            Target::Path(_) | Target::Glob(_) => continue,
+        };
+        diagnostics.push(diagnostic(
+            document.node_range(reference.node.range()),
+            DiagnosticSeverity::WARNING,
+            None,
+            message,
+        ));
+    }
+    let changed = cache
+        .version
+        .and_then(|version| document.changes_since(version));
+    if changed.is_none() {
+        cache.lint = LintCache::default();
+    }
+    let lints = linter.lint_changes(
+        workspace,
+        workspace.root(),
+        changed.unwrap_or_default(),
+        &mut cache.lint,
+    );
+    for lint in lints {
+        diagnostics.push(lint_diagnostic(document, &lint));
+    }
+    cache.version = Some(document.version());
+    cache.files = workspace
+        .modules()
+        .map(|(_, module)| module.path.clone())
+        .collect();
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.range.start);
+    diagnostics
+}
+
+fn collect_syntax_errors(document: &Document, node: Node<'_>, diagnostics: &mut Vec<Diagnostic>) {
+    if !node.has_error() {
+        return;
+    }
+    if node.is_error() || node.is_missing() {
+        let message = if node.is_missing() {
+            format!("Syntax error: missing {}", node.kind())
+        } else {
+            "Syntax error".to_string()
+        };
+        diagnostics.push(diagnostic(
+            document.node_range(node.range()),
+            DiagnosticSeverity::ERROR,
+            None,
+            message,
+        ));
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_syntax_errors(document, child, diagnostics);
+    }
+}
+
+/// The LSP form of a lint result in `document`.
+pub fn lint_diagnostic(document: &Document, lint: &jake_lint::Diagnostic) -> Diagnostic {
+    diagnostic(
+        document.range(lint.range.clone()),
+        severity(lint.severity),
+        Some(lint.code),
+        lint.message.clone(),
+    )
+}
+
+fn diagnostic(
+    range: lsp_types::Range,
+    severity: DiagnosticSeverity,
+    code: Option<&str>,
+    message: String,
+) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: code.map(|code| NumberOrString::String(code.to_string())),
+        source: Some("jake".to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+fn severity(severity: Severity) -> DiagnosticSeverity {
+    match severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Info => DiagnosticSeverity::INFORMATION,
+        Severity::Hint => DiagnosticSeverity::HINT,
+    }
+}
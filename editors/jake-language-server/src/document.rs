@@ -0,0 +1,324 @@
+//! An open text document, its syntax tree and its index.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use lsp_types::{Position, TextDocumentContentChangeEvent, Url};
+use tree_sitter::{InputEdit, Node, Parser, Point, Tree};
+use tree_sitter_jake::index::JakefileIndex;
+use tree_sitter_jake::workspace::normalize_path;
+
+use crate::document_links::Target;
+use crate::line_index::LineIndex;
+use crate::resolve;
+
+/// The editor's copy of a Jakefile.
+///
+/// Edits are applied to the previous tree with [`Tree::edit`] and the text is
+/// reparsed incrementally, so only the changed region is parsed again. The
+/// index is updated the same way: only recipes in the changed region are
+/// indexed again.
+pub struct Document {
+    /// Canonical path for `file:` URIs, used to serve the buffer to imports.
+    path: Option<PathBuf>,
+    text: String,
+    version: i32,
+    tree: Tree,
+    index: JakefileIndex,
+    line_index: LineIndex,
+    /// The version before the last change.
+    previous_version: i32,
+    /// Byte ranges of `text` that the last change edited or whose syntax it
+    /// changed, sorted and disjoint.
+    changed: Vec<Range<usize>>,
+    /// What the paths written in directives resolved to on disk, for this
+    /// version.
+    resolved_paths: RefCell<HashMap<String, Target>>,
+}
+
+impl Document {
+    pub fn new(parser: &mut Parser, uri: &Url, text: String, version: i32) -> Self {
+        let tree = parse(parser, &text, None);
+        let index = JakefileIndex::build(&tree, &text);
+        let path = uri
+            .to_file_path()
+            .ok()
+            .map(|path| std::fs::canonicalize(&path).unwrap_or_else(|_| normalize_path(&path)));
+        Self {
+            path,
+            line_index: LineIndex::new(&text),
+            changed: Vec::new(),
+            resolved_paths: RefCell::default(),
+            text,
+            version,
+            previous_version: version,
+            tree,
+            index,
+        }
+    }
+
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    pub fn index(&self) -> &JakefileIndex {
+        &self.index
+    }
+
+    /// What `path`, as written in a directive, resolves to. `lookup` reads
+    /// the disk at most once per path and version.
+    pub fn resolved_path(&self, path: &str, lookup: impl FnOnce() -> Target) -> Target {
+        self.resolved_paths
+            .borrow_mut()
+            .entry(path.to_string())
+            .or_insert_with(lookup)
+            .clone()
+    }
+
+    /// Byte ranges of the text that differ from `version`, if that is the
+    /// current or the previous version.
+    pub fn changes_since(&self, version: i32) -> Option<&[Range<usize>]> {
+        if version == self.version {
+            Some(&[])
+        } else if version == self.previous_version {
+            Some(&self.changed)
+        } else {
+            None
+        }
+    }
+
+    /// Apply `changes` in order and reparse.
+    pub fn apply_changes(
+        &mut self,
+        parser: &mut Parser,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        version: i32,
+    ) {
+        let mut reuse_tree = true;
+        let mut edited = Vec::new();
+        for change in changes {
+            match change.range {
+                Some(range) => edited.push(self.edit(range, &change.text)),
+                None => {
+                    self.text = change.text;
+                    self.line_index = LineIndex::new(&self.text);
+                    reuse_tree = false;
+                }
+            }
+        }
+
+        let tree = parse(parser, &self.text, reuse_tree.then_some(&self.tree));
+        self.changed = if reuse_tree {
+            let mut changed: Vec<Range<usize>> = edited_ranges(&edited);
+            changed.extend(
+                self.tree
+                    .changed_ranges(&tree)
+                    .map(|range| range.start_byte..range.end_byte),
+            );
+            merge(changed)
+        } else {
+            std::iter::once(0..self.text.len()).collect()
+        };
+        self.index = if reuse_tree {
+            self.index.update(&tree, &self.text, &self.changed)
+        } else {
+            JakefileIndex::build(&tree, &self.text)
+        };
+        self.tree = tree;
+        self.previous_version = self.version;
+        self.version = version;
+        self.resolved_paths.get_mut().clear();
+    }
+
+    /// Replace `range` with `text` and record the edit in the old tree.
+    fn edit(&mut self, range: lsp_types::Range, text: &str) -> InputEdit {
+        let start_byte = self.offset(range.start);
+        let old_end_byte = self.offset(range.end).max(start_byte);
+        let start_position = self.line_index.point(start_byte);
+        let old_end_position = self.line_index.point(old_end_byte);
+
+        self.text.replace_range(start_byte..old_end_byte, text);
+        self.line_index = LineIndex::new(&self.text);
+
+        let edit = InputEdit {
+            start_byte,
+            old_end_byte,
+            new_end_byte: start_byte + text.len(),
+            start_position,
+            old_end_position,
+            new_end_position: end_point(start_position, text),
+        };
+        self.tree.edit(&edit);
+        edit
+    }
+
+    /// The byte offset of an LSP position.
+    pub fn offset(&self, position: Position) -> usize {
+        self.line_index.offset(&self.text, position)
+    }
+
+    /// The LSP range of a byte range.
+    pub fn range(&self, range: Range<usize>) -> lsp_types::Range {
+        self.line_index.range(&self.text, range)
+    }
+
+    /// The LSP range of a tree-sitter range.
+    pub fn node_range(&self, range: tree_sitter::Range) -> lsp_types::Range {
+        self.range(range.start_byte..range.end_byte)
+    }
+
+    /// The name token under the cursor: an identifier or dependency name
+    /// containing `position`, or ending right before it.
+    pub fn name_at(&self, position: Position) -> Option<Node<'_>> {
+        resolve::name_at(self.tree.root_node(), self.offset(position))
+    }
+}
+
+fn parse(parser: &mut Parser, text: &str, old_tree: Option<&Tree>) -> Tree {
+    parser
+        .parse(text, old_tree)
+        .expect("parser has a language and no timeout")
+}
+
+/// The text inserted by each of `edits`, applied in order, as byte ranges
+/// of the final text.
+fn edited_ranges(edits: &[InputEdit]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for edit in edits {
+        // Move what earlier edits inserted past this one.
+        let offset = |byte: usize| {
+            if byte <= edit.start_byte {
+                byte
+            } else if byte >= edit.old_end_byte {
+                byte - edit.old_end_byte + edit.new_end_byte
+            } else {
+                edit.start_byte
+            }
+        };
+        for range in &mut ranges {
+            *range = offset(range.start)..offset(range.end);
+        }
+        ranges.push(edit.start_byte..edit.new_end_byte);
+    }
+    ranges
+}
+
+/// Sort `ranges` and merge those that overlap or touch.
+fn merge(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Where inserting `text` at `start` ends.
+fn end_point(start: Point, text: &str) -> Point {
+    match text.rfind('\n') {
+        Some(last) => Point::new(
+            start.row + text.matches('\n').count(),
+            text.len() - last - 1,
+        ),
+        None => Point::new(start.row, start.column + text.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Range;
+
+    use super::*;
+
+    fn change(range: Range, text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(range),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_incremental_edits_match_full_parse() {
+        let mut parser = Parser::new();
+        parser.set_language(&tree_sitter_jake::language()).unwrap();
+        let uri = Url::parse("untitled:Jakefile").unwrap();
+        let mut document =
+            Document::new(&mut parser, &uri, "task build:\n    make\n".to_string(), 1);
+
+        document.apply_changes(
+            &mut parser,
+            vec![
+                // Rename `build` to `compile`.
+                change(
+                    Range::new(Position::new(0, 5), Position::new(0, 10)),
+                    "compile",
+                ),
+                // Append a second recipe that depends on it.
+                change(
+                    Range::new(Position::new(2, 0), Position::new(2, 0)),
+                    "\ntask test: [compile]\n    make test\n",
+                ),
+            ],
+            2,
+        );
+
+        let expected = "task compile:\n    make\n\ntask test: [compile]\n    make test\n";
+        assert_eq!(document.text(), expected);
+        assert_eq!(document.version(), 2);
+
+        let fresh = Document::new(&mut parser, &uri, expected.to_string(), 3);
+        assert_eq!(
+            document.tree().root_node().to_sexp(),
+            fresh.tree().root_node().to_sexp()
+        );
+    }
+
+    #[test]
+    fn test_changes_since() {
+        let mut parser = Parser::new();
+        parser.set_language(&tree_sitter_jake::language()).unwrap();
+        let uri = Url::parse("untitled:Jakefile").unwrap();
+        let text = "task build:\n    make\n\ntask test:\n    make test\n";
+        let mut document = Document::new(&mut parser, &uri, text.to_string(), 1);
+
+        document.apply_changes(
+            &mut parser,
+            vec![change(
+                Range::new(Position::new(1, 8), Position::new(1, 8)),
+                " -j4",
+            )],
+            2,
+        );
+
+        assert_eq!(document.changes_since(2), Some(&[][..]));
+        assert_eq!(document.changes_since(0), None);
+        let edited = "task build:\n    make".len();
+        let changed = document.changes_since(1).unwrap();
+        assert!(changed
+            .iter()
+            .any(|range| range.start <= edited && edited + " -j4".len() <= range.end));
+        let test = document.text().find("task test").unwrap();
+        assert!(changed.iter().all(|range| range.end <= test));
+
+        let fresh = JakefileIndex::build(document.tree(), document.text());
+        assert_eq!(document.index().recipes(), fresh.recipes());
+    }
+}
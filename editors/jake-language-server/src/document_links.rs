@@ -0,0 +1,176 @@
+//! `textDocument/documentLink` for the paths of `@import`, `@dotenv`, `@cd`,
+//! `@cache` and `@watch`, resolved against the directory of the Jakefile
+//! the way `import.zig` resolves imports.
+//!
+//! A glob pattern links the file it matches when it matches exactly one;
+//! otherwise hovering it lists the matches. Paths that resolve to nothing
+//! are reported as warnings with the other diagnostics. Each version of a
+//! document resolves a path once, however many requests ask for it.
+
+use std::path::{Path, PathBuf};
+
+use lsp_types::{DocumentLink, Url};
+use tree_sitter::Node;
+use tree_sitter_jake::ast::{
+    fields, kinds, node_text, AstNode, BodyDirectiveKind, GlobalDirectiveKind, Jakefile,
+    StringLiteral,
+};
+use tree_sitter_jake::workspace::Workspace;
+
+use crate::document::Document;
+use crate::glob;
+
+/// A path written in a directive and what it resolves to.
+pub struct PathReference<'tree> {
+    pub node: Node<'tree>,
+    /// The path as written, without quotes.
+    pub path: String,
+    pub target: Target,
+}
+
+#[derive(Clone, Debug)]
+pub enum Target {
+    /// An existing file or directory.
+    Path(PathBuf),
+    /// The files a glob pattern matches, possibly none.
+    Glob(Vec<PathBuf>),
+    /// A path that does not exist.
+    Missing,
+}
+
+/// Every path in `document`, in source order. Unresolved imports are left
+/// out, as the workspace already reports them.
+pub fn path_references<'a>(
+    document: &'a Document,
+    workspace: &Workspace,
+) -> Vec<PathReference<'a>> {
+    let source = document.text();
+    let Some(jakefile) = Jakefile::cast(document.tree().root_node()) else {
+        return Vec::new();
+    };
+    let mut references = Vec::new();
+    for import in jakefile.imports() {
+        let Some(path) = import.path() else {
+            continue;
+        };
+        let node = path.syntax();
+        let target = workspace.imports().iter().find_map(|edge| {
+            (edge.from == workspace.root() && edge.path_range == node.range())
+                .then_some(edge.to)
+                .flatten()
+        });
+        if let Some(target) = target {
+            references.push(PathReference {
+                node,
+                path: path.value(source).into_owned(),
+                target: Target::Path(workspace.module(target).path.clone()),
+            });
+        }
+    }
+
+    // Other paths are relative to the Jakefile, so a buffer without one has
+    // nothing to resolve them against.
+    let Some(dir) = document.path().and_then(|path| path.parent()) else {
+        return references;
+    };
+    let mut nodes = Vec::new();
+    for directive in jakefile.global_directives() {
+        if directive.kind() == Some(GlobalDirectiveKind::Dotenv) {
+            nodes.extend(
+                directive
+                    .inner()
+                    .and_then(|inner| inner.child_by_field_name(fields::PATH)),
+            );
+        }
+    }
+    for recipe in jakefile.recipes() {
+        for directive in recipe.body().iter().flat_map(|body| body.directives()) {
+            if matches!(
+                directive.kind(),
+                Some(BodyDirectiveKind::Cd | BodyDirectiveKind::Cache | BodyDirectiveKind::Watch)
+            ) {
+                nodes.extend(directive.paths());
+            }
+        }
+    }
+    for node in nodes {
+        let path = match StringLiteral::cast(node) {
+            Some(string) => string.value(source).into_owned(),
+            None if node.kind() == kinds::INTERPOLATION => continue,
+            None => node_text(node, source).to_string(),
+        };
+        let target = document.resolved_path(&path, || resolve(dir, &path));
+        references.push(PathReference { node, path, target });
+    }
+    references.sort_by_key(|reference| reference.node.start_byte());
+    references
+}
+
+fn resolve(dir: &Path, path: &str) -> Target {
+    if glob::is_glob(path) {
+        return Target::Glob(glob::expand(dir, path));
+    }
+    let path = dir.join(path);
+    if path.exists() {
+        Target::Path(path)
+    } else {
+        Target::Missing
+    }
+}
+
+pub fn document_links(document: &Document, workspace: &Workspace) -> Vec<DocumentLink> {
+    path_references(document, workspace)
+        .into_iter()
+        .filter_map(|reference| {
+            let target = match &reference.target {
+                Target::Path(path) => path,
+                Target::Glob(files) if files.len() == 1 => &files[0],
+                Target::Glob(_) | Target::Missing => return None,
+            };
+            Some(DocumentLink {
+                range: document.node_range(reference.node.range()),
+                target: Some(Url::from_file_path(target).ok()?),
+                tooltip: None,
+                data: None,
+            })
+        })
+        .collect()
+}
+
+/// The glob pattern at `offset` and a list of the files it matches.
+pub fn glob_hover<'a>(
+    document: &'a Document,
+    workspace: &Workspace,
+    offset: usize,
+) -> Option<(Node<'a>, String)> {
+    let reference = path_references(document, workspace)
+        .into_iter()
+        .find(|reference| reference.node.byte_range().contains(&offset))?;
+    let Target::Glob(files) = reference.target else {
+        return None;
+    };
+    let dir = document.path()?.parent()?;
+    let markdown = match files.len() {
+        0 => format!("`{}` matches no files", reference.path),
+        count => {
+            let count = match count {
+                1 => "1 file".to_string(),
+                glob::MAX_MATCHES => format!("{count} or more files"),
+                _ => format!("{count} files"),
+            };
+            let list: Vec<_> = files
+                .iter()
+                .map(|file| {
+                    let relative = file.strip_prefix(dir).unwrap_or(file);
+                    format!("- `{}`", relative.display())
+                })
+                .collect();
+            format!(
+                "`{}` matches {count}:\n\n{}",
+                reference.path,
+                list.join("\n")
+            )
+        }
+    };
+    Some((reference.node, markdown))
+}
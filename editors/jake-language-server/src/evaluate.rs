@@ -0,0 +1,363 @@
+//! Static evaluation of expressions, for showing what an interpolation
+//! expands to without running anything.
+//!
+//! Strings, numbers, variables, `+`, `/`, `if` with a decidable condition and
+//! the built-ins that only transform their argument evaluate to text. Values
+//! that depend on the machine the recipe runs on are kept opaque: names that
+//! `@require` or `@dotenv` provide, `$VAR` and built-ins reading `$HOME` or
+//! the working directory are [`Value::Env`], and backticks are
+//! [`Value::Shell`].
+
+use tree_sitter::Node;
+use tree_sitter_jake::ast::{
+    fields, kinds, node_text, Assignment, AstNode, Expression, ExpressionKind, FunctionCall,
+    GlobalDirectiveKind, IfExpression, Value as ValueNode, ValueKind,
+};
+use tree_sitter_jake::workspace::{ModuleId, Workspace};
+
+use crate::resolve::{self, Symbol};
+
+/// What an expression evaluates to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Text(String),
+    /// Read from the environment when the recipe runs.
+    Env,
+    /// The output of a shell command.
+    Shell,
+}
+
+impl Value {
+    /// Combine two operands with `op`. Opaque operands make the result
+    /// opaque, and a shell command outweighs the environment.
+    fn combine(self, other: Value, op: impl FnOnce(String, String) -> String) -> Value {
+        match (self, other) {
+            (Value::Text(lhs), Value::Text(rhs)) => Value::Text(op(lhs, rhs)),
+            (Value::Shell, _) | (_, Value::Shell) => Value::Shell,
+            _ => Value::Env,
+        }
+    }
+}
+
+/// Evaluates expressions of the modules of a workspace.
+pub struct Evaluator<'a> {
+    workspace: &'a Workspace,
+    /// The start of each variable and parameter being evaluated, to stop at
+    /// cycles.
+    stack: Vec<(ModuleId, usize)>,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(workspace: &'a Workspace) -> Self {
+        Self {
+            workspace,
+            stack: Vec::new(),
+        }
+    }
+
+    /// The value of `expression` in `module`, or `None` when it cannot be
+    /// known without running Jake.
+    pub fn expression(&mut self, module: ModuleId, expression: Expression<'_>) -> Option<Value> {
+        let value = match expression.kind()? {
+            ExpressionKind::Value(value) => self.value(module, value)?,
+            ExpressionKind::Concat(lhs, rhs) => {
+                let lhs = self.expression(module, lhs)?;
+                let rhs = self.expression(module, rhs)?;
+                lhs.combine(rhs, |lhs, rhs| lhs + &rhs)
+            }
+            ExpressionKind::Join(lhs, rhs) => {
+                let lhs = self.expression(module, lhs)?;
+                let rhs = self.expression(module, rhs)?;
+                lhs.combine(rhs, |lhs, rhs| format!("{lhs}/{rhs}"))
+            }
+            ExpressionKind::If(if_expression) => self.if_expression(module, if_expression)?,
+        };
+        match value {
+            Value::Text(text) if expression.is_absolute() => Some(Value::Text(format!("/{text}"))),
+            value => Some(value),
+        }
+    }
+
+    pub fn value(&mut self, module: ModuleId, value: ValueNode<'_>) -> Option<Value> {
+        let source = &self.workspace.module(module).source;
+        match value.kind()? {
+            ValueKind::String(string) => Some(Value::Text(string.value(source).into_owned())),
+            ValueKind::Number(node) | ValueKind::Path(node) => {
+                Some(Value::Text(node_text(node, source).to_string()))
+            }
+            ValueKind::Identifier(identifier) => self.name(module, identifier.syntax()),
+            ValueKind::FunctionCall(call) => self.call(module, call),
+            ValueKind::ExternalCommand(_) => Some(Value::Shell),
+            // `$1` and `$@` are the command line, not the environment.
+            ValueKind::ShellVariable(variable) => {
+                let text = node_text(variable, source);
+                (!text[1..].starts_with(|c: char| c.is_ascii_digit() || c == '@'))
+                    .then_some(Value::Env)
+            }
+            ValueKind::Parenthesized(expression) => self.expression(module, expression),
+        }
+    }
+
+    /// The value of the variable or parameter `node`. A parameter evaluates
+    /// to its default, which the command line may override.
+    fn name(&mut self, module: ModuleId, node: Node<'_>) -> Option<Value> {
+        let source = &self.workspace.module(module).source;
+        match resolve::classify(node, source)? {
+            Symbol::Variable(name) => self.variable(name),
+            Symbol::Parameter(_, parameter) => {
+                let default = parameter.default_value()?;
+                self.guarded((module, parameter.byte_range().start), |evaluator| {
+                    evaluator.value(module, default)
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// `evaluate`, unless the definition starting at `key` is already being
+    /// evaluated.
+    fn guarded(
+        &mut self,
+        key: (ModuleId, usize),
+        evaluate: impl FnOnce(&mut Self) -> Option<Value>,
+    ) -> Option<Value> {
+        if self.stack.contains(&key) {
+            return None;
+        }
+        self.stack.push(key);
+        let value = evaluate(self);
+        self.stack.pop();
+        value
+    }
+
+    /// The value of the variable `name`, evaluated in the module that
+    /// assigns it.
+    pub fn variable(&mut self, name: &str) -> Option<Value> {
+        let workspace = self.workspace;
+        let Some(variable) = workspace.variable(name) else {
+            return self.is_from_environment(name).then_some(Value::Env);
+        };
+        let module = workspace.module(variable.module);
+        let start = workspace.variable_entry(variable).range.start_byte;
+        let expression = module
+            .tree
+            .root_node()
+            .descendant_for_byte_range(start, start)
+            .and_then(|node| {
+                std::iter::successors(Some(node), Node::parent).find_map(Assignment::cast)
+            })?
+            .value()?;
+        self.guarded((variable.module, start), |evaluator| {
+            evaluator.expression(variable.module, expression)
+        })
+    }
+
+    /// Whether `node` names a variable that nothing defines.
+    fn is_undefined(&self, module: ModuleId, node: Node<'_>) -> bool {
+        let source = &self.workspace.module(module).source;
+        matches!(resolve::classify(node, source), Some(Symbol::Variable(name))
+            if self.workspace.variable(name).is_none() && !self.is_from_environment(name))
+    }
+
+    /// Whether `name` is required from, or may be loaded into, the
+    /// environment.
+    fn is_from_environment(&self, name: &str) -> bool {
+        self.workspace.modules().any(|(_, module)| {
+            let index = &module.index;
+            index
+                .directives(GlobalDirectiveKind::Dotenv)
+                .next()
+                .is_some()
+                || index
+                    .directives(GlobalDirectiveKind::Require)
+                    .any(|require| require.args.iter().any(|arg| arg == name))
+        })
+    }
+
+    fn call(&mut self, module: ModuleId, call: FunctionCall<'_>) -> Option<Value> {
+        let source = &self.workspace.module(module).source;
+        let name = call.name()?.text(source);
+        if IMPURE.contains(&name) {
+            return Some(Value::Env);
+        }
+        let [argument] = call.arguments()[..] else {
+            return None;
+        };
+        // Like `resolveArg` in `functions.zig`, a bare name that is not a
+        // variable is taken literally.
+        let argument = match bare_name(argument) {
+            Some(node) if self.is_undefined(module, node) => {
+                Value::Text(node_text(node, source).to_string())
+            }
+            _ => self.expression(module, argument)?,
+        };
+        match argument {
+            Value::Text(text) => Some(Value::Text(apply(name, &text)?)),
+            opaque => Some(opaque),
+        }
+    }
+
+    /// The value of the first branch whose condition holds. Conditions that
+    /// depend on the environment leave the result unknown.
+    fn if_expression(
+        &mut self,
+        module: ModuleId,
+        if_expression: IfExpression<'_>,
+    ) -> Option<Value> {
+        if self.condition(module, if_expression.condition()?)? {
+            return self.expression(module, if_expression.consequence()?);
+        }
+        for alternative in if_expression.alternatives() {
+            let body = alternative
+                .child_by_field_name(fields::BODY)
+                .and_then(Expression::cast)?;
+            if alternative.kind() == kinds::ELSE_CLAUSE {
+                return self.expression(module, body);
+            }
+            let condition = alternative
+                .named_children(&mut alternative.walk())
+                .find(|node| node.kind() == kinds::CONDITION)?;
+            if self.condition(module, condition)? {
+                return self.expression(module, body);
+            }
+        }
+        None
+    }
+
+    /// Whether `lhs == rhs` or `lhs != rhs` holds, when both sides are known.
+    fn condition(&mut self, module: ModuleId, condition: Node<'_>) -> Option<bool> {
+        let mut cursor = condition.walk();
+        let children: Vec<Node<'_>> = condition.children(&mut cursor).collect();
+        let [lhs, op, rhs] = children[..] else {
+            return None;
+        };
+        let lhs = self.expression(module, Expression::cast(lhs)?)?;
+        let rhs = self.expression(module, Expression::cast(rhs)?)?;
+        let (Value::Text(lhs), Value::Text(rhs)) = (lhs, rhs) else {
+            return None;
+        };
+        match op.kind() {
+            "==" => Some(lhs == rhs),
+            "!=" => Some(lhs != rhs),
+            _ => None,
+        }
+    }
+}
+
+/// Built-ins whose result depends on `$HOME`, `$SHELL`, the working
+/// directory or the platform.
+const IMPURE: &[&str] = &[
+    "absolute_path",
+    "home",
+    "local_bin",
+    "shell_config",
+    "launch",
+];
+
+/// The identifier that makes up all of `expression`.
+fn bare_name(expression: Expression<'_>) -> Option<Node<'_>> {
+    match expression.kind()? {
+        ExpressionKind::Value(value) => match value.kind()? {
+            ValueKind::Identifier(identifier) => Some(identifier.syntax()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Apply the pure built-in `name` to `arg`, like `functions.zig` does.
+fn apply(name: &str, arg: &str) -> Option<String> {
+    let result = match name {
+        "uppercase" => arg.to_ascii_uppercase(),
+        "lowercase" => arg.to_ascii_lowercase(),
+        "trim" => arg.trim_matches([' ', '\t', '\n', '\r']).to_string(),
+        "dirname" => match dirname(arg) {
+            Some(dir) => dir.to_string(),
+            None if arg.starts_with('/') => "/".to_string(),
+            None => ".".to_string(),
+        },
+        "basename" => basename(arg).to_string(),
+        "extension" => {
+            let base = basename(arg);
+            match base.rfind('.') {
+                Some(dot) if dot > 0 => base[dot..].to_string(),
+                _ => String::new(),
+            }
+        }
+        "without_extension" => {
+            let base = basename(arg);
+            let stem = match base.rfind('.') {
+                Some(dot) if dot > 0 => &base[..dot],
+                _ => base,
+            };
+            with_dirname(arg, stem)
+        }
+        "without_extensions" => {
+            let base = basename(arg);
+            // A leading dot names a dotfile rather than starting an extension.
+            let stem = match base.get(1..).and_then(|rest| rest.find('.')) {
+                Some(dot) => &base[..dot + 1],
+                None => base,
+            };
+            with_dirname(arg, stem)
+        }
+        _ => return None,
+    };
+    Some(result)
+}
+
+fn with_dirname(path: &str, name: &str) -> String {
+    match dirname(path) {
+        Some(dir) => format!("{dir}/{name}"),
+        None => name.to_string(),
+    }
+}
+
+/// `std.fs.path.dirname` for POSIX paths.
+fn dirname(path: &str) -> Option<&str> {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return None;
+    }
+    match trimmed.rfind('/') {
+        Some(0) => Some("/"),
+        Some(slash) => Some(&trimmed[..slash]),
+        None => None,
+    }
+}
+
+/// `std.fs.path.basename` for POSIX paths.
+fn basename(path: &str) -> &str {
+    let trimmed = path.trim_end_matches('/');
+    match trimmed.rfind('/') {
+        Some(slash) => &trimmed[slash + 1..],
+        None => trimmed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let cases = [
+            ("uppercase", "hello", "HELLO"),
+            ("trim", "  x \n", "x"),
+            ("dirname", "/path/to/file.txt", "/path/to"),
+            ("dirname", "/file", "/"),
+            ("dirname", "file", "."),
+            ("basename", "/path/to/file.txt", "file.txt"),
+            ("basename", "dir/", "dir"),
+            ("extension", "src/main.rs", ".rs"),
+            ("extension", ".bashrc", ""),
+            ("without_extension", "dist/app.tar.gz", "dist/app.tar"),
+            ("without_extensions", "dist/app.tar.gz", "dist/app"),
+            ("without_extensions", ".hidden.txt", ".hidden"),
+        ];
+        for (name, arg, expected) in cases {
+            assert_eq!(apply(name, arg).as_deref(), Some(expected), "{name}({arg})");
+        }
+        assert_eq!(apply("home", ""), None);
+    }
+}
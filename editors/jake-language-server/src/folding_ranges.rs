@@ -0,0 +1,114 @@
+//! `textDocument/foldingRange`: recipes, `@if`/`@each` blocks, runs of
+//! comments and runs of recipes in the same `@group`.
+//!
+//! A recipe folds from its header, so its attributes stay visible, while a
+//! group folds from the attributes of its first recipe. Each branch of a
+//! block folds on its own, leaving `@elif`, `@else` and `@end` in view.
+
+use std::ops::Range;
+
+use lsp_types::{FoldingRange, FoldingRangeKind};
+use tree_sitter_jake::ast::{descendants_of_kind, kinds, AstNode, Jakefile};
+use tree_sitter_jake::blocks::BlockTree;
+
+use crate::document::Document;
+
+pub fn folding_ranges(document: &Document) -> Vec<FoldingRange> {
+    let source = document.text();
+    let Some(jakefile) = Jakefile::cast(document.tree().root_node()) else {
+        return Vec::new();
+    };
+    let mut folds = Vec::new();
+    let mut fold = |range: Range<usize>, kind: FoldingRangeKind| {
+        let range = document.range(trim_end(source, range));
+        if range.end.line > range.start.line {
+            folds.push(FoldingRange {
+                start_line: range.start.line,
+                start_character: None,
+                end_line: range.end.line,
+                end_character: None,
+                kind: Some(kind),
+                collapsed_text: None,
+            });
+        }
+    };
+
+    for recipe in jakefile.recipes() {
+        let start = recipe.header().map_or(recipe.byte_range().start, |header| {
+            header.byte_range().start
+        });
+        fold(start..recipe.byte_range().end, FoldingRangeKind::Region);
+
+        let Some(body) = recipe.body() else {
+            continue;
+        };
+        for block in BlockTree::build(body).blocks() {
+            for branch in &block.branches {
+                let range = branch.range();
+                fold(range.start_byte..range.end_byte, FoldingRangeKind::Region);
+            }
+        }
+    }
+
+    for range in group_ranges(document) {
+        fold(range, FoldingRangeKind::Region);
+    }
+    for range in comment_runs(document) {
+        fold(range, FoldingRangeKind::Comment);
+    }
+
+    folds.sort_by_key(|fold| (fold.start_line, std::cmp::Reverse(fold.end_line)));
+    folds.dedup_by_key(|fold| fold.start_line);
+    folds
+}
+
+/// Byte ranges of each run of consecutive recipes in the same `@group`,
+/// from the attributes of the first recipe to the end of the last.
+pub fn group_ranges(document: &Document) -> Vec<Range<usize>> {
+    let index = document.index();
+    let mut runs: Vec<(&str, Range<usize>)> = Vec::new();
+    let mut previous: Option<&str> = None;
+    for recipe in index.recipes() {
+        let group = recipe.group.as_deref();
+        let range = recipe.range.start_byte..recipe.range.end_byte;
+        match (group, runs.last_mut()) {
+            (Some(group), Some((name, run))) if previous == Some(group) && *name == group => {
+                run.end = range.end;
+            }
+            (Some(group), _) => runs.push((group, range)),
+            (None, _) => {}
+        }
+        previous = group;
+    }
+    runs.into_iter().map(|(_, range)| range).collect()
+}
+
+/// Byte ranges of each run of comments on consecutive lines of their own.
+fn comment_runs(document: &Document) -> Vec<Range<usize>> {
+    let source = document.text();
+    let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+    for comment in descendants_of_kind(document.tree().root_node(), kinds::COMMENT) {
+        let line_start = source[..comment.start_byte()]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        if !source[line_start..comment.start_byte()].trim().is_empty() {
+            continue;
+        }
+        let row = comment.start_position().row;
+        match runs.last_mut() {
+            Some((last_row, run)) if *last_row + 1 == row => {
+                *last_row = row;
+                run.end = comment.end_byte();
+            }
+            _ => runs.push((row, comment.byte_range())),
+        }
+    }
+    runs.into_iter().map(|(_, range)| range).collect()
+}
+
+/// `range` without trailing whitespace, so a fold ends on its last line of
+/// text rather than on the line after a trailing newline.
+pub fn trim_end(source: &str, range: Range<usize>) -> Range<usize> {
+    let text = source[range.clone()].trim_end();
+    range.start..range.start + text.len()
+}
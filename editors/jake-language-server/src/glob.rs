@@ -0,0 +1,168 @@
+//! Glob patterns as `glob.zig` matches them, for the `@cache` and `@watch`
+//! patterns shown by document links and hovers.
+//!
+//! `*` and `?` never cross `/`, `**` does, and `[...]` classes support
+//! ranges and `!`/`^` negation.
+
+use std::path::{Path, PathBuf};
+
+/// Most files [`expand`] returns; patterns like `**/*` are only listed.
+pub const MAX_MATCHES: usize = 100;
+
+/// Whether `pattern` has glob characters, as opposed to naming one path.
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Whether `path`, relative and `/`-separated, matches `pattern`.
+pub fn matches(pattern: &str, path: &str) -> bool {
+    match_from(pattern.as_bytes(), path.as_bytes())
+}
+
+fn match_from(pattern: &[u8], path: &[u8]) -> bool {
+    let (mut p, mut s) = (0, 0);
+    while p < pattern.len() {
+        match pattern[p] {
+            b'*' if pattern.get(p + 1) == Some(&b'*') => {
+                p += 2;
+                if pattern.get(p) == Some(&b'/') {
+                    p += 1;
+                }
+                return p == pattern.len()
+                    || (s..=path.len()).any(|i| match_from(&pattern[p..], &path[i..]));
+            }
+            b'*' => {
+                p += 1;
+                let segment_end = path[s..]
+                    .iter()
+                    .position(|&c| c == b'/')
+                    .map_or(path.len(), |i| s + i);
+                if p == pattern.len() {
+                    return segment_end == path.len();
+                }
+                return (s..=segment_end).any(|i| match_from(&pattern[p..], &path[i..]));
+            }
+            b'?' => {
+                if path.get(s).is_none_or(|&c| c == b'/') {
+                    return false;
+                }
+                p += 1;
+                s += 1;
+            }
+            b'[' => {
+                let Some(&c) = path.get(s).filter(|&&c| c != b'/') else {
+                    return false;
+                };
+                let (matched, end) = match_class(pattern, p, c);
+                if !matched {
+                    return false;
+                }
+                p = end;
+                s += 1;
+            }
+            literal => {
+                if path.get(s) != Some(&literal) {
+                    return false;
+                }
+                p += 1;
+                s += 1;
+            }
+        }
+    }
+    s == path.len()
+}
+
+/// Whether the class starting at `pattern[start]` (the `[`) matches `c`, and
+/// where the class ends.
+fn match_class(pattern: &[u8], start: usize, c: u8) -> (bool, usize) {
+    let mut i = start + 1;
+    let negated = matches!(pattern.get(i), Some(b'!' | b'^'));
+    if negated {
+        i += 1;
+    }
+    let mut matched = false;
+    if pattern.get(i) == Some(&b']') {
+        matched = c == b']';
+        i += 1;
+    }
+    while i < pattern.len() && pattern[i] != b']' {
+        if i + 2 < pattern.len() && pattern[i + 1] == b'-' && pattern[i + 2] != b']' {
+            matched |= (pattern[i]..=pattern[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            matched |= pattern[i] == c;
+            i += 1;
+        }
+    }
+    if i < pattern.len() {
+        i += 1;
+    }
+    (matched != negated, i)
+}
+
+/// The files under `dir` that `pattern` matches, sorted, at most
+/// [`MAX_MATCHES`]. Like `glob.zig`, the walk starts at the directory before
+/// the first glob character.
+pub fn expand(dir: &Path, pattern: &str) -> Vec<PathBuf> {
+    let first_glob = pattern.find(['*', '?', '[']).unwrap_or(pattern.len());
+    let (base, rest) = match pattern[..first_glob].rfind('/') {
+        Some(slash) => (&pattern[..slash], &pattern[slash + 1..]),
+        None => ("", pattern),
+    };
+    let base = dir.join(base);
+    let mut files = Vec::new();
+    walk(&base, "", rest, &mut files);
+    files.sort();
+    files
+}
+
+fn walk(dir: &Path, relative: &str, pattern: &str, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.filter_map(Result::ok).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        if files.len() == MAX_MATCHES {
+            return;
+        }
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        let path = match relative {
+            "" => name.to_string(),
+            _ => format!("{relative}/{name}"),
+        };
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            // Without `**`, a match is only as deep as the pattern.
+            if pattern.contains("**") || path.matches('/').count() < pattern.matches('/').count() {
+                walk(&entry.path(), &path, pattern, files);
+            }
+        } else if matches(pattern, &path) {
+            files.push(entry.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("*.rs", "main.rs"));
+        assert!(!matches("*.rs", "src/main.rs"));
+        assert!(matches("**/*.rs", "src/bin/main.rs"));
+        assert!(matches("src/**", "src/bin/main.rs"));
+        assert!(matches("file?.txt", "file1.txt"));
+        assert!(!matches("file?.txt", "file10.txt"));
+        assert!(matches("[a-c]*.md", "b.md"));
+        assert!(!matches("[!a-c]*.md", "b.md"));
+        assert!(is_glob("src/*.rs"));
+        assert!(!is_glob("src/main.rs"));
+    }
+}
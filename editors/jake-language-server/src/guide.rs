@@ -0,0 +1,134 @@
+//! Reference documentation taken from `GUIDE.md`.
+//!
+//! The guide is compiled into the server, so completion and hover always
+//! describe the same Jake the guide in this checkout does.
+
+/// The user guide at the root of the repository.
+const GUIDE: &str = include_str!("../../../GUIDE.md");
+
+/// The text under the heading `title`, up to the next heading of any level.
+///
+/// Headings of the form `### @each - Loop Iteration` are also found by the
+/// part before ` - `, so directives can be looked up by keyword.
+pub fn section(title: &str) -> Option<&'static str> {
+    let mut lines = Lines::new(GUIDE);
+    let start = lines.find_map(|(line, end)| {
+        let heading = heading(line)?;
+        let matches = heading == title
+            || heading
+                .strip_prefix(title)
+                .is_some_and(|rest| rest.starts_with(" - "));
+        matches.then_some(end)
+    })?;
+    let end = lines
+        .find(|(line, _)| heading(line).is_some())
+        .map_or(GUIDE.len(), |(line, _)| offset_of(line));
+    let text = GUIDE[start..end].trim();
+    (!text.is_empty()).then_some(text)
+}
+
+/// A row of a reference table whose first cell is `` `name(...)` ``.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TableRow {
+    /// The first cell without backticks, e.g. `uppercase(s)`.
+    pub signature: &'static str,
+    pub description: &'static str,
+    pub example: Option<&'static str>,
+}
+
+/// The function or condition `name` from the guide's reference tables.
+pub fn function(name: &str) -> Option<TableRow> {
+    GUIDE.lines().find_map(|line| {
+        let mut cells = line.strip_prefix('|')?.split('|').map(str::trim);
+        let signature = cells.next()?.strip_prefix('`')?.strip_suffix('`')?;
+        if signature.split('(').next() != Some(name) || !signature.ends_with(')') {
+            return None;
+        }
+        let description = cells.next().filter(|cell| !cell.is_empty())?;
+        let example = cells.next().filter(|cell| !cell.is_empty());
+        Some(TableRow {
+            signature,
+            description,
+            example,
+        })
+    })
+}
+
+/// The title of a Markdown heading line.
+fn heading(line: &str) -> Option<&str> {
+    let title = line.trim_start_matches('#');
+    (title.len() < line.len() && title.starts_with(' ')).then(|| title.trim())
+}
+
+fn offset_of(line: &str) -> usize {
+    line.as_ptr() as usize - GUIDE.as_ptr() as usize
+}
+
+/// Lines outside fenced code blocks, where `#` starts a comment rather than
+/// a heading, with the offset just past each line.
+struct Lines {
+    lines: std::str::Lines<'static>,
+    in_code: bool,
+}
+
+impl Lines {
+    fn new(text: &'static str) -> Self {
+        Self {
+            lines: text.lines(),
+            in_code: false,
+        }
+    }
+}
+
+impl Iterator for Lines {
+    type Item = (&'static str, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.lines.next()?;
+            if line.trim_start().starts_with("```") {
+                self.in_code = !self.in_code;
+                continue;
+            }
+            if !self.in_code {
+                return Some((line, offset_of(line) + line.len()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections() {
+        let each = section("@each").unwrap();
+        assert!(each.starts_with("Iterate over items:"));
+        assert!(!each.contains("Glob Pattern Expansion"));
+
+        // `# Process all TypeScript files` inside a code block is not a
+        // heading.
+        let globs = section("Glob Pattern Expansion").unwrap();
+        assert!(globs.contains("# Process specific file types"));
+
+        assert_eq!(section("@nonexistent"), None);
+    }
+
+    #[test]
+    fn test_functions() {
+        let row = function("without_extension").unwrap();
+        assert_eq!(row.signature, "without_extension(p)");
+        assert_eq!(row.description, "Remove last extension");
+        assert_eq!(
+            row.example,
+            Some("`{{without_extension(file.tar.gz)}}` → `file.tar`")
+        );
+
+        let row = function("is_watching").unwrap();
+        assert_eq!(row.signature, "is_watching()");
+        assert_eq!(row.example, None);
+
+        assert_eq!(function("no_such_function"), None);
+    }
+}
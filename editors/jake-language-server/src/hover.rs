@@ -0,0 +1,194 @@
+//! `textDocument/hover` for names, directives and built-in functions.
+//!
+//! Names are resolved like go-to-definition, so a dependency on an imported
+//! recipe shows the recipe from the file that defines it.
+
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+use tree_sitter::Node;
+use tree_sitter_jake::ast::{kinds, node_text, AstNode, Recipe, StringLiteral};
+use tree_sitter_jake::workspace::{ModuleId, Workspace};
+
+use crate::builtins::{self, Scope};
+use crate::document::Document;
+use crate::document_links;
+use crate::resolve::{self, Symbol};
+use crate::signature_help::{self, parameter_text};
+
+pub fn hover(document: &Document, workspace: &Workspace, position: Position) -> Option<Hover> {
+    let offset = document.offset(position);
+    let source = document.text();
+    let root = document.tree().root_node();
+    let (node, value) = if let Some(hover) = document_links::glob_hover(document, workspace, offset)
+    {
+        hover
+    } else if let Some(node) = document.name_at(position) {
+        (node, name_markdown(workspace, node, source)?)
+    } else if let Some(node) = resolve::condition_name_at(root, offset) {
+        let condition = builtins::condition(node_text(node, source))?;
+        (node, builtins::documentation(condition))
+    } else {
+        let node = directive_at(document, offset)?;
+        (node, directive_markdown(node, source)?)
+    };
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(document.node_range(node.range())),
+    })
+}
+
+fn name_markdown(workspace: &Workspace, node: Node<'_>, source: &str) -> Option<String> {
+    let markdown = match resolve::classify(node, source)? {
+        Symbol::Recipe(name) => {
+            let recipe = resolve::resolve_recipe(workspace, workspace.root(), name)?;
+            let module = workspace.module(recipe.module);
+            let defined_in =
+                (recipe.module != workspace.root()).then(|| defined_in(workspace, recipe.module));
+            recipe_markdown(
+                resolve::recipe_node(workspace, recipe)?,
+                &module.source,
+                defined_in,
+            )
+        }
+        Symbol::Variable(name) => {
+            let variable = workspace.variable(name)?;
+            let module = workspace.module(variable.module);
+            let range = workspace.variable_entry(variable).range;
+            let assignment = &module.source[range.start_byte..range.end_byte];
+            let mut markdown = format!("```jake\n{}\n```", assignment.trim_end());
+            if variable.module != workspace.root() {
+                markdown.push_str("\n\n");
+                markdown.push_str(&defined_in(workspace, variable.module));
+            }
+            markdown
+        }
+        Symbol::Parameter(recipe, parameter) => format!(
+            "```jake\n{}\n```\n\nParameter of `{}`",
+            parameter_text(parameter, source),
+            recipe.name(source).unwrap_or_default()
+        ),
+        Symbol::EachItem(each) => format!(
+            "```jake\n{}\n```\n\nThe current item of this `@each` loop",
+            each.text(source).trim_end()
+        ),
+        Symbol::Function(name) => {
+            let builtin = match node.parent()?.kind() {
+                kinds::CONDITION_FUNCTION => builtins::condition(name),
+                _ => builtins::function(name),
+            }?;
+            builtins::documentation(builtin)
+        }
+    };
+    Some(markdown)
+}
+
+/// The `@keyword` token containing `offset`, or ending right before it.
+fn directive_at(document: &Document, offset: usize) -> Option<Node<'_>> {
+    let root = document.tree().root_node();
+    [Some(offset), offset.checked_sub(1)]
+        .into_iter()
+        .flatten()
+        .filter_map(|offset| root.descendant_for_byte_range(offset, offset))
+        .find(|node| node.child_count() == 0 && node.kind().starts_with('@'))
+}
+
+fn directive_markdown(node: Node<'_>, source: &str) -> Option<String> {
+    let keyword = node_text(node, source);
+    let in_body = std::iter::successors(node.parent(), Node::parent)
+        .any(|ancestor| ancestor.kind() == kinds::RECIPE_BODY);
+    let mut directives = builtins::DIRECTIVES
+        .iter()
+        .filter(|directive| directive.keyword == keyword);
+    let directive = directives
+        .clone()
+        .find(|directive| (directive.scope == Scope::Body) == in_body)
+        .or_else(|| directives.next())?;
+    Some(format!(
+        "```jake\n{keyword}\n```\n\n{}",
+        directive.documentation()
+    ))
+}
+
+/// The signature, description, doc comment and metadata of a recipe, in the
+/// order `jake --show` prints them. `defined_in` names the file of an
+/// imported recipe.
+fn recipe_markdown(recipe: Recipe<'_>, source: &str, defined_in: Option<String>) -> String {
+    let mut markdown = format!(
+        "```jake\n{}\n```",
+        signature_help::recipe_signature(recipe, source).label
+    );
+    let description = recipe
+        .description()
+        .map(|description| description.value(source));
+    if let Some(description) = &description {
+        markdown.push_str("\n\n");
+        markdown.push_str(description);
+    }
+    if let Some(doc) = doc_comment(recipe, source) {
+        if description.as_deref() != Some(doc.as_str()) {
+            markdown.push_str("\n\n");
+            markdown.push_str(&doc);
+        }
+    }
+
+    let mut metadata = vec![format!("Type: {}", recipe.kind().as_str())];
+    if let Some(group) = recipe.group() {
+        let group = match StringLiteral::cast(group) {
+            Some(string) => string.value(source).into_owned(),
+            None => node_text(group, source).to_string(),
+        };
+        metadata.push(format!("Group: `{group}`"));
+    }
+    let aliases = recipe.aliases();
+    if !aliases.is_empty() {
+        metadata.push(format!("Aliases: {}", code_list(&aliases, source)));
+    }
+    let platforms = recipe.platforms();
+    if !platforms.is_empty() {
+        metadata.push(format!("Platforms: {}", code_list(&platforms, source)));
+    }
+    metadata.extend(defined_in);
+    markdown.push_str("\n\n");
+    markdown.push_str(&metadata.join("  \n"));
+    markdown
+}
+
+/// The comment lines directly above a recipe and its attributes, without a
+/// blank line in between. `jake --list` treats the same lines as the
+/// recipe's doc comment.
+fn doc_comment(recipe: Recipe<'_>, source: &str) -> Option<String> {
+    let above = &source[..recipe.syntax().start_byte()];
+    let mut lines: Vec<&str> = above
+        .lines()
+        .rev()
+        .map_while(|line| line.strip_prefix('#'))
+        .map(str::trim)
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+    lines.reverse();
+    Some(lines.join("\n"))
+}
+
+fn code_list<'a>(nodes: &[impl AstNode<'a>], source: &str) -> String {
+    nodes
+        .iter()
+        .map(|node| format!("`{}`", node.text(source)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `Defined in` with the path of an imported module, relative to the root
+/// file's directory where possible.
+fn defined_in(workspace: &Workspace, module: ModuleId) -> String {
+    let path = &workspace.module(module).path;
+    let root = &workspace.module(workspace.root()).path;
+    let relative = root
+        .parent()
+        .and_then(|dir| path.strip_prefix(dir).ok())
+        .unwrap_or(path);
+    format!("Defined in `{}`", relative.display())
+}
@@ -0,0 +1,97 @@
+//! `textDocument/inlayHint`: the value each `{{ }}` expands to, after the
+//! interpolation, when [`evaluate`](crate::evaluate) can tell statically.
+//!
+//! A parameter shows its default, since the command line may not set it.
+//! Interpolations of a plain string or number already show their value and
+//! get no hint.
+
+use std::ops::Range;
+
+use lsp_types::{InlayHint, InlayHintLabel, InlayHintTooltip};
+use tree_sitter_jake::ast::{
+    descendants_of_kind, kinds, AstNode, ExpressionKind, Interpolation, ValueKind,
+};
+use tree_sitter_jake::workspace::Workspace;
+
+use crate::document::Document;
+use crate::evaluate::{Evaluator, Value};
+use crate::resolve::{self, Symbol};
+
+/// Longest value shown in full; longer ones are cut with an ellipsis.
+const MAX_LABEL_CHARS: usize = 40;
+
+/// Hints for the interpolations of `document` that overlap `range`.
+/// `workspace` is rooted at the document.
+pub fn inlay_hints(
+    document: &Document,
+    workspace: &Workspace,
+    range: Range<usize>,
+) -> Vec<InlayHint> {
+    let source = document.text();
+    let mut evaluator = Evaluator::new(workspace);
+    descendants_of_kind(document.tree().root_node(), kinds::INTERPOLATION)
+        .into_iter()
+        .filter(|node| node.start_byte() <= range.end && range.start <= node.end_byte())
+        .filter_map(Interpolation::cast)
+        .filter_map(|interpolation| {
+            let expression = interpolation.expression()?;
+            let mut tooltip = None;
+            if let Some(ExpressionKind::Value(value)) = expression.kind() {
+                match value.kind()? {
+                    ValueKind::String(_) | ValueKind::Number(_) | ValueKind::Path(_) => {
+                        return None
+                    }
+                    ValueKind::Identifier(identifier) => {
+                        if let Some(Symbol::Parameter(_, _)) =
+                            resolve::classify(identifier.syntax(), source)
+                        {
+                            let name = identifier.text(source);
+                            tooltip = Some(format!("Default of parameter '{name}'"));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let value = evaluator.expression(workspace.root(), expression)?;
+            let end = interpolation.byte_range().end;
+            Some(InlayHint {
+                position: document.range(end..end).end,
+                label: InlayHintLabel::String(format!("= {}", label(&value))),
+                kind: None,
+                text_edits: None,
+                tooltip: tooltip.map(InlayHintTooltip::String),
+                padding_left: Some(true),
+                padding_right: None,
+                data: None,
+            })
+        })
+        .collect()
+}
+
+/// A quoted, escaped and possibly shortened `value`.
+fn label(value: &Value) -> String {
+    let text = match value {
+        Value::Text(text) => text,
+        Value::Env => return "<env>".to_string(),
+        Value::Shell => return "<shell>".to_string(),
+    };
+    let escaped: String = text.escape_debug().collect();
+    if escaped.chars().count() <= MAX_LABEL_CHARS {
+        return format!("\"{escaped}\"");
+    }
+    let shortened: String = escaped.chars().take(MAX_LABEL_CHARS - 1).collect();
+    format!("\"{shortened}…\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label() {
+        assert_eq!(label(&Value::Text("a\tb".into())), "\"a\\tb\"");
+        assert_eq!(label(&Value::Env), "<env>");
+        let long = label(&Value::Text("x".repeat(50)));
+        assert_eq!(long, format!("\"{}…\"", "x".repeat(39)));
+    }
+}
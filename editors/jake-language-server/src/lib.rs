@@ -0,0 +1,784 @@
+//! Language server for Jakefiles.
+//!
+//! [`run`] speaks LSP over any [`Connection`]: the binary connects it to
+//! stdio, and tests drive it in-process through [`Connection::memory`].
+//! Documents are parsed with the tree-sitter grammar from `tree-sitter-jake`
+//! and reparsed incrementally on every change. Diagnostics combine syntax
+//! errors, unresolved imports, missing paths and the `jake-lint` rules, with imports read
+//! from open buffers before falling back to disk.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use jake_lint::{Linter, Severity};
+use lsp_server::{
+    Connection, ErrorCode, ExtractError, Message, Notification, Request, RequestId, Response,
+    ResponseError,
+};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, PublishDiagnostics,
+    WorkDoneProgressCancel,
+};
+use lsp_types::request::{
+    CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+    CodeActionRequest, CodeLensRequest, Completion, DocumentLinkRequest, DocumentSymbolRequest,
+    ExecuteCommand, FoldingRangeRequest, GotoDefinition, HoverRequest, InlayHintRequest,
+    PrepareRenameRequest, References, Rename, Request as _, SelectionRangeRequest,
+    SemanticTokensFullRequest, SemanticTokensRangeRequest, SignatureHelpRequest,
+    WorkDoneProgressCreate, WorkspaceSymbolRequest,
+};
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    CallHierarchyServerCapability, CodeActionOptions, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, CodeActionResponse, CodeLens, CodeLensOptions, CodeLensParams,
+    CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DocumentLink, DocumentLinkOptions,
+    DocumentLinkParams, DocumentSymbolParams, DocumentSymbolResponse, ExecuteCommandOptions,
+    ExecuteCommandParams, FoldingRange, FoldingRangeParams, FoldingRangeProviderCapability,
+    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams, HoverProviderCapability,
+    InitializeParams, InitializeResult, InlayHint, InlayHintParams, Location, NumberOrString,
+    OneOf, PrepareRenameResponse, ProgressToken, PublishDiagnosticsParams, ReferenceParams,
+    RenameOptions, RenameParams, SelectionRange, SelectionRangeParams,
+    SelectionRangeProviderCapability, SemanticTokens, SemanticTokensFullOptions,
+    SemanticTokensOptions, SemanticTokensParams, SemanticTokensRangeParams,
+    SemanticTokensRangeResult, SemanticTokensResult, ServerCapabilities, ServerInfo, SignatureHelp,
+    SignatureHelpOptions, SignatureHelpParams, TextDocumentPositionParams,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url, WorkDoneProgressCancelParams,
+    WorkDoneProgressCreateParams, WorkspaceEdit, WorkspaceSymbolParams, WorkspaceSymbolResponse,
+};
+use tree_sitter::Parser;
+use tree_sitter_jake::workspace::ModuleCache;
+
+mod builtins;
+mod call_hierarchy;
+mod code_actions;
+mod code_lens;
+mod completion;
+mod diagnostics;
+mod document;
+mod document_links;
+mod evaluate;
+mod folding_ranges;
+mod glob;
+mod guide;
+mod hover;
+mod inlay_hints;
+mod line_index;
+mod refactor;
+mod references;
+mod resolve;
+mod run;
+mod selection_ranges;
+mod semantic_tokens;
+mod signature_help;
+mod symbols;
+mod workspace;
+
+pub use line_index::LineIndex;
+
+use diagnostics::DiagnosticsCache;
+use document::Document;
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// What the server supports, sent in the `initialize` response.
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(["@", "[", "{", ",", ":", "$"].map(String::from).to_vec()),
+            ..CompletionOptions::default()
+        }),
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(["(", ","].map(String::from).to_vec()),
+            ..SignatureHelpOptions::default()
+        }),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        workspace_symbol_provider: Some(OneOf::Left(true)),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+        rename_provider: Some(OneOf::Right(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: Default::default(),
+        })),
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(code_actions::kinds()),
+            ..CodeActionOptions::default()
+        })),
+        code_lens_provider: Some(CodeLensOptions {
+            resolve_provider: Some(false),
+        }),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: run::commands(),
+            work_done_progress_options: Default::default(),
+        }),
+        document_link_provider: Some(DocumentLinkOptions {
+            resolve_provider: Some(false),
+            work_done_progress_options: Default::default(),
+        }),
+        inlay_hint_provider: Some(OneOf::Left(true)),
+        semantic_tokens_provider: Some(
+            SemanticTokensOptions {
+                legend: semantic_tokens::legend(),
+                range: Some(true),
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                ..SemanticTokensOptions::default()
+            }
+            .into(),
+        ),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// Perform the `initialize` handshake and serve requests until `shutdown`
+/// and `exit`.
+pub fn run(connection: Connection) -> Result<(), Error> {
+    let (id, params) = connection.initialize_start()?;
+    let params: InitializeParams = serde_json::from_value(params)?;
+    let result = InitializeResult {
+        capabilities: capabilities(),
+        server_info: Some(ServerInfo {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }),
+    };
+    connection.initialize_finish(id, serde_json::to_value(result)?)?;
+
+    Server::new(&connection, &params).main_loop()
+}
+
+/// The workspace folders of the client, or its root for clients that
+/// predate folders.
+#[allow(deprecated)]
+fn workspace_roots(params: &InitializeParams) -> Vec<PathBuf> {
+    let uris = match &params.workspace_folders {
+        Some(folders) => folders.iter().map(|folder| &folder.uri).collect(),
+        None => params.root_uri.iter().collect::<Vec<_>>(),
+    };
+    uris.into_iter()
+        .filter_map(|uri| uri.to_file_path().ok())
+        .collect()
+}
+
+/// The `jake` binary to run recipes with: `jakePath` from the
+/// initialization options, or `jake` from `PATH`.
+fn jake_path(params: &InitializeParams) -> PathBuf {
+    params
+        .initialization_options
+        .as_ref()
+        .and_then(|options| options.get("jakePath")?.as_str())
+        .map_or_else(|| PathBuf::from("jake"), PathBuf::from)
+}
+
+/// A linter with the severities from the `lint` initialization option, an
+/// object from rule codes or names to `error`, `warning`, `info`, `hint` or
+/// `off`, as accepted by `jake-lint --severity`. Unknown rules and levels
+/// are ignored.
+fn linter(params: &InitializeParams) -> Linter {
+    let mut linter = Linter::new();
+    let rules = params
+        .initialization_options
+        .as_ref()
+        .and_then(|options| options.get("lint")?.as_object());
+    for (rule, level) in rules.into_iter().flatten() {
+        if let Some(severity) = level.as_str().and_then(Severity::parse) {
+            linter.set_severity(rule, severity);
+        }
+    }
+    linter
+}
+
+struct Server<'a> {
+    connection: &'a Connection,
+    parser: Parser,
+    linter: Linter,
+    documents: HashMap<Url, Document>,
+    /// Parsed open documents and the files they import.
+    modules: ModuleCache,
+    diagnostics: HashMap<Url, DiagnosticsCache>,
+    /// Workspace folders searched for Jakefiles by `workspace/symbol`.
+    roots: Vec<PathBuf>,
+    jake: PathBuf,
+    /// Whether the client accepts progress tokens created by the server.
+    work_done_progress: bool,
+    runs: run::Runs,
+    /// Progress tokens the server asked the client to create, by request.
+    created_tokens: HashMap<RequestId, ProgressToken>,
+    next_run: u32,
+}
+
+impl<'a> Server<'a> {
+    fn new(connection: &'a Connection, params: &InitializeParams) -> Self {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_jake::language())
+            .expect("Error loading Jake language");
+        Self {
+            connection,
+            parser,
+            linter: linter(params),
+            documents: HashMap::new(),
+            modules: ModuleCache::new(),
+            diagnostics: HashMap::new(),
+            roots: workspace_roots(params),
+            jake: jake_path(params),
+            work_done_progress: params
+                .capabilities
+                .window
+                .as_ref()
+                .and_then(|window| window.work_done_progress)
+                .unwrap_or(false),
+            runs: run::Runs::default(),
+            created_tokens: HashMap::new(),
+            next_run: 0,
+        }
+    }
+
+    fn main_loop(&mut self) -> Result<(), Error> {
+        let connection = self.connection;
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        self.runs.cancel_all();
+                        return Ok(());
+                    }
+                    self.on_request(request)?;
+                }
+                Message::Notification(notification) => self.on_notification(notification)?,
+                Message::Response(response) => self.on_response(response),
+            }
+        }
+        Ok(())
+    }
+
+    fn on_request(&mut self, request: Request) -> Result<(), Error> {
+        RequestDispatcher {
+            server: self,
+            request: Some(request),
+        }
+        .on::<HoverRequest>(Server::hover)?
+        .on::<Completion>(Server::completion)?
+        .on::<SignatureHelpRequest>(Server::signature_help)?
+        .on::<GotoDefinition>(Server::definition)?
+        .on::<References>(Server::references)?
+        .on::<CallHierarchyPrepare>(Server::prepare_call_hierarchy)?
+        .on::<CallHierarchyIncomingCalls>(Server::incoming_calls)?
+        .on::<CallHierarchyOutgoingCalls>(Server::outgoing_calls)?
+        .on_fallible::<PrepareRenameRequest>(Server::prepare_rename)?
+        .on_fallible::<Rename>(Server::rename)?
+        .on::<CodeActionRequest>(Server::code_actions)?
+        .on::<DocumentSymbolRequest>(Server::document_symbols)?
+        .on::<WorkspaceSymbolRequest>(Server::workspace_symbols)?
+        .on::<FoldingRangeRequest>(Server::folding_ranges)?
+        .on::<SelectionRangeRequest>(Server::selection_ranges)?
+        .on::<SemanticTokensFullRequest>(Server::semantic_tokens)?
+        .on::<SemanticTokensRangeRequest>(Server::semantic_tokens_range)?
+        .on::<InlayHintRequest>(Server::inlay_hints)?
+        .on::<CodeLensRequest>(Server::code_lenses)?
+        .on::<DocumentLinkRequest>(Server::document_links)?
+        .on_fallible::<ExecuteCommand>(Server::execute_command)?
+        .finish()
+    }
+
+    fn on_notification(&mut self, notification: Notification) -> Result<(), Error> {
+        NotificationDispatcher {
+            server: self,
+            notification: Some(notification),
+        }
+        .on::<DidOpenTextDocument>(Server::did_open)?
+        .on::<DidChangeTextDocument>(Server::did_change)?
+        .on::<DidCloseTextDocument>(Server::did_close)?
+        .on::<WorkDoneProgressCancel>(Server::cancel_run)?;
+        Ok(())
+    }
+
+    fn did_open(&mut self, params: DidOpenTextDocumentParams) -> Result<(), Error> {
+        let item = params.text_document;
+        let document = Document::new(&mut self.parser, &item.uri, item.text, item.version);
+        workspace::cache(&mut self.modules, &document);
+        self.documents.insert(item.uri.clone(), document);
+        self.diagnostics.remove(&item.uri);
+        self.publish_diagnostics(&item.uri)
+    }
+
+    fn did_change(&mut self, params: DidChangeTextDocumentParams) -> Result<(), Error> {
+        let uri = params.text_document.uri;
+        let Some(document) = self.documents.get_mut(&uri) else {
+            return Ok(());
+        };
+        document.apply_changes(
+            &mut self.parser,
+            params.content_changes,
+            params.text_document.version,
+        );
+        workspace::cache(&mut self.modules, document);
+        let path = document.path().cloned();
+        self.publish_diagnostics(&uri)?;
+
+        // Documents importing this one may see different names now.
+        let Some(path) = path else {
+            return Ok(());
+        };
+        let importers: Vec<Url> = self
+            .diagnostics
+            .iter()
+            .filter(|(other, cache)| **other != uri && cache.depends_on(&path))
+            .map(|(other, _)| other.clone())
+            .collect();
+        for importer in importers {
+            self.publish_diagnostics(&importer)?;
+        }
+        Ok(())
+    }
+
+    fn did_close(&mut self, params: DidCloseTextDocumentParams) -> Result<(), Error> {
+        let uri = params.text_document.uri;
+        let Some(document) = self.documents.remove(&uri) else {
+            return Ok(());
+        };
+        // Workspaces that import the file read it from disk again.
+        if let Some(path) = document.path() {
+            self.modules.remove(path);
+        }
+        self.diagnostics.remove(&uri);
+        // Clear the diagnostics of the closed buffer.
+        self.send_notification::<PublishDiagnostics>(PublishDiagnosticsParams {
+            uri,
+            diagnostics: Vec::new(),
+            version: None,
+        })
+    }
+
+    fn publish_diagnostics(&mut self, uri: &Url) -> Result<(), Error> {
+        let Some(document) = self.documents.get(uri) else {
+            return Ok(());
+        };
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let cache = self.diagnostics.entry(uri.clone()).or_default();
+        let diagnostics = diagnostics::diagnostics(document, &workspace, &self.linter, cache);
+        self.send_notification::<PublishDiagnostics>(PublishDiagnosticsParams {
+            uri: uri.clone(),
+            diagnostics,
+            version: Some(document.version()),
+        })
+    }
+
+    fn cancel_run(&mut self, params: WorkDoneProgressCancelParams) -> Result<(), Error> {
+        self.runs.cancel(&params.token);
+        Ok(())
+    }
+
+    fn send_notification<N: lsp_types::notification::Notification>(
+        &self,
+        params: N::Params,
+    ) -> Result<(), Error> {
+        let notification = Notification::new(N::METHOD.to_string(), params);
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+
+    fn hover(&mut self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        hover::hover(document, &workspace, position.position)
+    }
+
+    fn signature_help(&mut self, params: SignatureHelpParams) -> Option<SignatureHelp> {
+        let position = params.text_document_position_params;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        signature_help::signature_help(document, &workspace, position.position)
+    }
+
+    fn completion(&mut self, params: CompletionParams) -> Option<CompletionResponse> {
+        let position = params.text_document_position;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let items = completion::completions(document, &workspace, position.position);
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn definition(&mut self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let uri = &position.text_document.uri;
+        let document = self.documents.get(uri)?;
+        let symbol = resolve::classify(document.name_at(position.position)?, document.text())?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let target = resolve::definition(&workspace, symbol)?;
+        let location = workspace::location(uri, document, &workspace, target)?;
+        Some(GotoDefinitionResponse::Scalar(location))
+    }
+
+    fn references(&mut self, params: ReferenceParams) -> Option<Vec<Location>> {
+        let position = params.text_document_position;
+        let uri = &position.text_document.uri;
+        let document = self.documents.get(uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let references = references::references(&workspace, document.offset(position.position))?;
+        let include_declaration = params.context.include_declaration;
+        let locations = references
+            .into_iter()
+            .filter(|reference| include_declaration || !reference.is_definition)
+            .filter_map(|reference| {
+                workspace::location(uri, document, &workspace, reference.target)
+            })
+            .collect();
+        Some(locations)
+    }
+
+    fn prepare_call_hierarchy(
+        &mut self,
+        params: CallHierarchyPrepareParams,
+    ) -> Option<Vec<CallHierarchyItem>> {
+        let position = params.text_document_position_params;
+        let uri = &position.text_document.uri;
+        let document = self.documents.get(uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let context = call_hierarchy::Context {
+            uri,
+            document,
+            workspace: &workspace,
+        };
+        call_hierarchy::prepare(&context, position.position)
+    }
+
+    fn incoming_calls(
+        &mut self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Option<Vec<CallHierarchyIncomingCall>> {
+        let uri = call_hierarchy::root_uri(&params.item)?;
+        let document = self.documents.get(&uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let context = call_hierarchy::Context {
+            uri: &uri,
+            document,
+            workspace: &workspace,
+        };
+        call_hierarchy::incoming_calls(&context, &params.item)
+    }
+
+    fn outgoing_calls(
+        &mut self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Option<Vec<CallHierarchyOutgoingCall>> {
+        let uri = call_hierarchy::root_uri(&params.item)?;
+        let document = self.documents.get(&uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let context = call_hierarchy::Context {
+            uri: &uri,
+            document,
+            workspace: &workspace,
+        };
+        call_hierarchy::outgoing_calls(&context, &params.item)
+    }
+
+    fn prepare_rename(
+        &mut self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>, ResponseError> {
+        let Some(document) = self.documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let range = references::prepare_rename(&workspace, document.offset(params.position))
+            .map_err(rename_error)?;
+        Ok(range.map(|range| PrepareRenameResponse::Range(document.node_range(range))))
+    }
+
+    fn rename(&mut self, params: RenameParams) -> Result<Option<WorkspaceEdit>, ResponseError> {
+        let position = params.text_document_position;
+        let uri = &position.text_document.uri;
+        let Some(document) = self.documents.get(uri) else {
+            return Ok(None);
+        };
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let offset = document.offset(position.position);
+        let references =
+            references::rename(&workspace, offset, &params.new_name).map_err(rename_error)?;
+        if references.is_empty() {
+            return Ok(None);
+        }
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for reference in references {
+            if let Some(location) = workspace::location(uri, document, &workspace, reference.target)
+            {
+                let edit = TextEdit::new(location.range, params.new_name.clone());
+                changes.entry(location.uri).or_default().push(edit);
+            }
+        }
+        Ok(Some(WorkspaceEdit::new(changes)))
+    }
+
+    fn code_actions(&mut self, params: CodeActionParams) -> Option<CodeActionResponse> {
+        let uri = &params.text_document.uri;
+        let document = self.documents.get(uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let range = document.offset(params.range.start)..document.offset(params.range.end);
+        let actions = code_actions::code_actions(
+            uri,
+            document,
+            &workspace,
+            &self.linter,
+            range,
+            params.context.only.as_deref(),
+        );
+        Some(
+            actions
+                .into_iter()
+                .map(CodeActionOrCommand::CodeAction)
+                .collect(),
+        )
+    }
+
+    fn document_symbols(&mut self, params: DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        Some(DocumentSymbolResponse::Nested(symbols::document_symbols(
+            document,
+        )))
+    }
+
+    fn workspace_symbols(
+        &mut self,
+        params: WorkspaceSymbolParams,
+    ) -> Option<WorkspaceSymbolResponse> {
+        let symbols = symbols::workspace_symbols(
+            &self.documents,
+            &mut self.modules,
+            &self.roots,
+            &params.query,
+        );
+        Some(WorkspaceSymbolResponse::Nested(symbols))
+    }
+
+    fn folding_ranges(&mut self, params: FoldingRangeParams) -> Option<Vec<FoldingRange>> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        Some(folding_ranges::folding_ranges(document))
+    }
+
+    fn selection_ranges(&mut self, params: SelectionRangeParams) -> Option<Vec<SelectionRange>> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        Some(selection_ranges::selection_ranges(
+            document,
+            &params.positions,
+        ))
+    }
+
+    fn semantic_tokens(&mut self, params: SemanticTokensParams) -> Option<SemanticTokensResult> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let data = semantic_tokens::semantic_tokens(document, &workspace, None);
+        Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: None,
+            data,
+        }))
+    }
+
+    fn semantic_tokens_range(
+        &mut self,
+        params: SemanticTokensRangeParams,
+    ) -> Option<SemanticTokensRangeResult> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let range = document.offset(params.range.start)..document.offset(params.range.end);
+        let data = semantic_tokens::semantic_tokens(document, &workspace, Some(range));
+        Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+            result_id: None,
+            data,
+        }))
+    }
+
+    fn inlay_hints(&mut self, params: InlayHintParams) -> Option<Vec<InlayHint>> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        let range = document.offset(params.range.start)..document.offset(params.range.end);
+        Some(inlay_hints::inlay_hints(document, &workspace, range))
+    }
+
+    fn code_lenses(&mut self, params: CodeLensParams) -> Option<Vec<CodeLens>> {
+        let uri = &params.text_document.uri;
+        let document = self.documents.get(uri)?;
+        Some(code_lens::code_lenses(uri, document))
+    }
+
+    fn document_links(&mut self, params: DocumentLinkParams) -> Option<Vec<DocumentLink>> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let workspace = workspace::load(&self.documents, &mut self.modules, document);
+        Some(document_links::document_links(document, &workspace))
+    }
+
+    /// Start a `jake.*` command and answer right away; its output follows as
+    /// progress or log messages. When the server creates the progress token,
+    /// the output waits until the client has answered the create request.
+    fn execute_command(
+        &mut self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>, ResponseError> {
+        let invocation =
+            run::Invocation::parse(&params.command, &params.arguments).map_err(|message| {
+                ResponseError {
+                    code: ErrorCode::InvalidParams as i32,
+                    message,
+                    data: None,
+                }
+            })?;
+        self.next_run += 1;
+        let id = format!("jake/run/{}", self.next_run);
+        let (key, output) = match params.work_done_progress_params.work_done_token {
+            Some(token) => (token.clone(), Some(run::Output::Progress(token))),
+            None if self.work_done_progress => (NumberOrString::String(id.clone()), None),
+            None => (NumberOrString::String(id.clone()), Some(run::Output::Log)),
+        };
+        let sender = self.connection.sender.clone();
+        let send = move |message| {
+            // The client is gone when the channel is closed.
+            let _ = sender.send(message);
+        };
+        self.runs
+            .spawn(&self.jake, &invocation, key.clone(), output.clone(), send)
+            .map_err(|error| {
+                request_failed(format!("Could not run {}: {error}", self.jake.display()))
+            })?;
+        if output.is_none() {
+            let request = Request::new(
+                id.clone().into(),
+                WorkDoneProgressCreate::METHOD.to_string(),
+                WorkDoneProgressCreateParams { token: key.clone() },
+            );
+            self.created_tokens.insert(id.into(), key);
+            self.connection
+                .sender
+                .send(request.into())
+                .map_err(|error| request_failed(error.to_string()))?;
+        }
+        Ok(None)
+    }
+
+    /// Start reporting a run once its progress token exists, or to the log if
+    /// the client refused to create it.
+    fn on_response(&mut self, response: Response) {
+        let Some(token) = self.created_tokens.remove(&response.id) else {
+            return;
+        };
+        let output = match response.error {
+            None => run::Output::Progress(token.clone()),
+            Some(_) => run::Output::Log,
+        };
+        self.runs.start(&token, output);
+    }
+}
+
+fn request_failed(message: String) -> ResponseError {
+    ResponseError {
+        code: ErrorCode::RequestFailed as i32,
+        message,
+        data: None,
+    }
+}
+
+fn rename_error(error: references::RenameError) -> ResponseError {
+    let code = match error {
+        references::RenameError::InvalidName(_) => ErrorCode::InvalidParams,
+        _ => ErrorCode::RequestFailed,
+    };
+    ResponseError {
+        code: code as i32,
+        message: error.to_string(),
+        data: None,
+    }
+}
+
+/// Routes a request to the first matching handler and sends its response.
+struct RequestDispatcher<'s, 'a> {
+    server: &'s mut Server<'a>,
+    request: Option<Request>,
+}
+
+impl<'a> RequestDispatcher<'_, 'a> {
+    fn on<R: lsp_types::request::Request>(
+        &mut self,
+        handler: fn(&mut Server<'a>, R::Params) -> R::Result,
+    ) -> Result<&mut Self, Error> {
+        self.on_fallible::<R>(|server, params| Ok(handler(server, params)))
+    }
+
+    /// Like [`on`](Self::on), for handlers that can answer with an error.
+    fn on_fallible<R: lsp_types::request::Request>(
+        &mut self,
+        handler: impl FnOnce(&mut Server<'a>, R::Params) -> Result<R::Result, ResponseError>,
+    ) -> Result<&mut Self, Error> {
+        let Some(request) = self.request.take() else {
+            return Ok(self);
+        };
+        let id = request.id.clone();
+        let response = match request.extract::<R::Params>(R::METHOD) {
+            Ok((id, params)) => match handler(self.server, params) {
+                Ok(result) => Response::new_ok(id, result),
+                Err(error) => Response {
+                    id,
+                    result: None,
+                    error: Some(error),
+                },
+            },
+            Err(ExtractError::MethodMismatch(request)) => {
+                self.request = Some(request);
+                return Ok(self);
+            }
+            Err(ExtractError::JsonError { method, error }) => Response::new_err(
+                id,
+                ErrorCode::InvalidParams as i32,
+                format!("invalid params for {method}: {error}"),
+            ),
+        };
+        self.server.connection.sender.send(response.into())?;
+        Ok(self)
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        if let Some(request) = self.request.take() {
+            let response = Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unhandled method: {}", request.method),
+            );
+            self.server.connection.sender.send(response.into())?;
+        }
+        Ok(())
+    }
+}
+
+/// Routes a notification to the first matching handler. Unknown
+/// notifications are ignored, as the protocol requires.
+struct NotificationDispatcher<'s, 'a> {
+    server: &'s mut Server<'a>,
+    notification: Option<Notification>,
+}
+
+impl<'a> NotificationDispatcher<'_, 'a> {
+    fn on<N: lsp_types::notification::Notification>(
+        &mut self,
+        handler: fn(&mut Server<'a>, N::Params) -> Result<(), Error>,
+    ) -> Result<&mut Self, Error> {
+        let Some(notification) = self.notification.take() else {
+            return Ok(self);
+        };
+        match notification.extract::<N::Params>(N::METHOD) {
+            Ok(params) => handler(self.server, params)?,
+            Err(ExtractError::MethodMismatch(notification)) => {
+                self.notification = Some(notification);
+            }
+            Err(ExtractError::JsonError { method, error }) => {
+                eprintln!("jake-language-server: invalid params for {method}: {error}");
+            }
+        }
+        Ok(self)
+    }
+}
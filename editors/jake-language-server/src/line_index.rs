@@ -0,0 +1,98 @@
+//! Conversion between LSP positions and byte offsets.
+//!
+//! LSP positions count UTF-16 code units within a line, while tree-sitter
+//! and the rest of the tooling work with byte offsets into UTF-8 text.
+
+use std::ops::Range;
+
+use lsp_types::Position;
+use tree_sitter::Point;
+
+/// Start offsets of every line of a text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { line_starts }
+    }
+
+    /// The byte offset of `position`, clamped to the end of its line and to
+    /// the end of the text.
+    pub fn offset(&self, text: &str, position: Position) -> usize {
+        let Some(&start) = self.line_starts.get(position.line as usize) else {
+            return text.len();
+        };
+        let line = line_text(text, start);
+        let mut units = 0;
+        for (i, c) in line.char_indices() {
+            if units >= position.character as usize {
+                return start + i;
+            }
+            units += c.len_utf16();
+        }
+        start + line.len()
+    }
+
+    pub fn position(&self, text: &str, offset: usize) -> Position {
+        let point = self.point(offset);
+        let start = self.line_starts[point.row];
+        let character: usize = text[start..offset.min(text.len())]
+            .chars()
+            .map(char::len_utf16)
+            .sum();
+        Position::new(point.row as u32, character as u32)
+    }
+
+    pub fn range(&self, text: &str, range: Range<usize>) -> lsp_types::Range {
+        lsp_types::Range::new(
+            self.position(text, range.start),
+            self.position(text, range.end),
+        )
+    }
+
+    /// The tree-sitter point of `offset`, with the column counted in bytes.
+    pub fn point(&self, offset: usize) -> Point {
+        let row = self
+            .line_starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1);
+        Point::new(row, offset - self.line_starts[row])
+    }
+}
+
+/// The line starting at `start`, without its line break.
+fn line_text(text: &str, start: usize) -> &str {
+    let line = &text[start..];
+    let line = line.split('\n').next().unwrap_or_default();
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offsets_round_trip() {
+        let text = "task build:\n    echo \"ø😀\"\r\n";
+        let index = LineIndex::new(text);
+
+        assert_eq!(index.offset(text, Position::new(1, 4)), 16);
+        assert_eq!(index.position(text, 16), Position::new(1, 4));
+
+        // The emoji is two UTF-16 code units but four bytes.
+        let after_emoji = text.find("\"\r").unwrap();
+        assert_eq!(index.position(text, after_emoji), Position::new(1, 13));
+        assert_eq!(index.offset(text, Position::new(1, 13)), after_emoji);
+
+        // Past the end of a line or of the text.
+        assert_eq!(index.offset(text, Position::new(0, 99)), 11);
+        assert_eq!(index.offset(text, Position::new(9, 0)), text.len());
+        assert_eq!(index.point(text.len()), Point::new(2, 0));
+    }
+}
@@ -0,0 +1,21 @@
+//! `jake-language-server`: the Jake language server over stdio.
+
+use std::process::ExitCode;
+
+use lsp_server::Connection;
+
+fn main() -> ExitCode {
+    let (connection, io_threads) = Connection::stdio();
+    let result = jake_language_server::run(connection);
+    if let Err(err) = io_threads.join() {
+        eprintln!("jake-language-server: {err}");
+        return ExitCode::FAILURE;
+    }
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("jake-language-server: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,578 @@
+//! Refactoring code actions.
+//!
+//! Every refactor rewrites only the tokens and lines it has to, found through
+//! the tree-sitter tree, so comments and formatting around them survive.
+//! Refactors that would change what a Jakefile does are still listed, but
+//! disabled with the reason.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use lsp_types::{CodeAction, CodeActionDisabled, CodeActionKind, TextEdit, Url, WorkspaceEdit};
+use tree_sitter::Node;
+use tree_sitter_jake::ast::{
+    kinds, Assignment, AstNode, Dependency, ExpressionKind, Interpolation, Recipe, RecipeHeader,
+    RecipeKind, ValueKind,
+};
+use tree_sitter_jake::workspace::{ModuleId, Workspace};
+
+use crate::code_actions::append_separator;
+use crate::document::Document;
+use crate::line_index::LineIndex;
+use crate::references;
+use crate::resolve::{self, Symbol};
+
+/// `refactor.move`, which the protocol does not predefine.
+pub fn refactor_move() -> CodeActionKind {
+    CodeActionKind::from("refactor.move")
+}
+
+/// The refactors available for `range` of `document`, of the kinds accepted
+/// by `wants`. `workspace` is rooted at the document.
+pub fn refactors(
+    uri: &Url,
+    document: &Document,
+    workspace: &Workspace,
+    range: Range<usize>,
+    wants: impl Fn(&CodeActionKind) -> bool,
+) -> Vec<CodeAction> {
+    let context = Context {
+        uri,
+        document,
+        workspace,
+    };
+    let root = document.tree().root_node();
+    let mut actions = Vec::new();
+
+    if wants(&CodeActionKind::REFACTOR_INLINE) {
+        actions.extend(context.inline_variable(range.start));
+    }
+    let node = root.descendant_for_byte_range(range.start, range.end);
+    let Some(recipe) = node
+        .and_then(|node| std::iter::successors(Some(node), Node::parent).find_map(Recipe::cast))
+    else {
+        return actions;
+    };
+    let on_header = recipe.header().filter(|header| {
+        let header = header.byte_range();
+        header.start <= range.end && range.start <= header.end
+    });
+    if wants(&CodeActionKind::REFACTOR_EXTRACT) && !range.is_empty() {
+        actions.extend(context.extract_recipe(recipe, range.clone()));
+    }
+    let Some(header) = on_header else {
+        return actions;
+    };
+    if wants(&CodeActionKind::REFACTOR_REWRITE) {
+        actions.extend(context.convert_kind(header));
+        actions.extend(context.sort_dependencies(header));
+    }
+    if wants(&refactor_move()) && header.kind() != RecipeKind::File {
+        actions.extend(context.move_recipe(recipe, header));
+    }
+    actions
+}
+
+struct Context<'a> {
+    uri: &'a Url,
+    document: &'a Document,
+    workspace: &'a Workspace,
+}
+
+/// Text edits across the files of a workspace.
+#[derive(Default)]
+struct Changes(HashMap<Url, Vec<TextEdit>>);
+
+impl Context<'_> {
+    fn source(&self) -> &str {
+        self.document.text()
+    }
+
+    /// Replace `range` of `module` with `text`.
+    fn edit(&self, changes: &mut Changes, module: ModuleId, range: Range<usize>, text: &str) {
+        let (uri, range) = if module == self.workspace.root() {
+            (self.uri.clone(), self.document.range(range))
+        } else {
+            let module = self.workspace.module(module);
+            let Ok(uri) = Url::from_file_path(&module.path) else {
+                return;
+            };
+            let range = LineIndex::new(&module.source).range(&module.source, range);
+            (uri, range)
+        };
+        changes
+            .0
+            .entry(uri)
+            .or_default()
+            .push(TextEdit::new(range, text.to_string()));
+    }
+
+    /// Move the selected command lines of `recipe` into a new task that
+    /// `recipe` depends on. Dependencies run before the body, so the lines
+    /// keep their order only when they open the body.
+    fn extract_recipe(&self, recipe: Recipe<'_>, range: Range<usize>) -> Option<CodeAction> {
+        let source = self.source();
+        let body = recipe.body()?;
+        let header = recipe.header()?;
+        let name = header.name()?.text(source);
+        let lines: Vec<Node<'_>> = body
+            .lines()
+            .map(|line| line.syntax())
+            .filter(|line| {
+                let line = line_range(source, line.byte_range());
+                line.start < range.end && range.start < line.end
+            })
+            .collect();
+        let (first, last) = (lines.first()?, lines.last()?);
+        let block = line_range(source, first.start_byte()..last.end_byte());
+
+        let title = "Extract lines into a new task".to_string();
+        let kind = CodeActionKind::REFACTOR_EXTRACT;
+        if header.kind() == RecipeKind::File {
+            let reason = "file recipes can only depend on files";
+            return Some(disabled(title, kind, reason));
+        }
+        if lines.iter().any(|line| line.kind() != kinds::COMMAND_LINE) {
+            return Some(disabled(title, kind, "only command lines can be extracted"));
+        }
+        if block_depth(body.syntax(), first.start_byte()) > 0 {
+            let reason = "lines inside @if or @each blocks cannot be extracted";
+            return Some(disabled(title, kind, reason));
+        }
+        let parameter = lines.iter().flat_map(|line| names(*line)).find_map(|node| {
+            match resolve::classify(node, source)? {
+                Symbol::Parameter(_, parameter) => Some(parameter.name()?.text(source)),
+                _ => None,
+            }
+        });
+        if let Some(parameter) = parameter {
+            let reason = format!("the lines use the parameter '{parameter}'");
+            return Some(disabled(title, kind, &reason));
+        }
+
+        let new_name = self.unused_recipe_name(&format!("{name}-part"));
+        let root = self.workspace.root();
+        let mut changes = Changes::default();
+        let task = format!("task {new_name}:\n{}\n", &source[block.clone()]);
+        self.edit(
+            &mut changes,
+            root,
+            recipe.byte_range().start..recipe.byte_range().start,
+            &task,
+        );
+        let (offset, dependency) = dependency_insertion(header, &new_name)?;
+        self.edit(&mut changes, root, offset..offset, &dependency);
+        self.edit(&mut changes, root, block, "");
+        Some(action(title, kind, changes))
+    }
+
+    /// `base`, or `base-2`, `base-3`, ... if a recipe already has that name.
+    fn unused_recipe_name(&self, base: &str) -> String {
+        let taken = |name: &str| {
+            self.workspace.recipe(name).is_some()
+                || self
+                    .workspace
+                    .module(self.workspace.root())
+                    .index
+                    .recipe(name)
+                    .is_some()
+        };
+        std::iter::once(base.to_string())
+            .chain((2..).map(|n| format!("{base}-{n}")))
+            .find(|name| !taken(name))
+            .expect("the candidates are unbounded")
+    }
+
+    /// Replace every use of the variable at `offset` with its value and
+    /// remove its assignment.
+    fn inline_variable(&self, offset: usize) -> Option<CodeAction> {
+        let workspace = self.workspace;
+        let node = resolve::name_at(self.document.tree().root_node(), offset)?;
+        let Some(Symbol::Variable(name)) = resolve::classify(node, self.source()) else {
+            return None;
+        };
+        let variable = workspace.variable(name)?;
+        let entry = workspace.variable_entry(variable);
+        let module = workspace.module(variable.module);
+        let assignment = module
+            .tree
+            .root_node()
+            .named_descendant_for_byte_range(entry.range.start_byte, entry.range.start_byte)
+            .and_then(|node| {
+                std::iter::successors(Some(node), Node::parent).find_map(Assignment::cast)
+            })?;
+        let value = assignment.value()?;
+
+        let title = format!("Inline variable '{name}'");
+        let kind = CodeActionKind::REFACTOR_INLINE;
+        let exported = workspace.modules().any(|(_, module)| {
+            names(module.tree.root_node()).into_iter().any(|node| {
+                node.utf8_text(module.source.as_bytes()) == Ok(name)
+                    && node.parent().is_some_and(|parent| {
+                        matches!(
+                            parent.kind(),
+                            kinds::EXPORT_DIRECTIVE | kinds::BODY_EXPORT_DIRECTIVE
+                        )
+                    })
+            })
+        });
+        if exported {
+            let reason = format!("'{name}' is exported to commands with @export");
+            return Some(disabled(title, kind, &reason));
+        }
+
+        let value_text = value.text(&module.source);
+        let literal = match value.kind() {
+            Some(ExpressionKind::Value(value)) => match value.kind() {
+                Some(ValueKind::String(string)) => Some(string.value(&module.source)),
+                _ => None,
+            },
+            _ => None,
+        };
+        let operand = match value.kind() {
+            Some(ExpressionKind::Value(_)) => value_text.to_string(),
+            _ => format!("({value_text})"),
+        };
+
+        let mut changes = Changes::default();
+        let references = references::references(workspace, offset)?;
+        for reference in references
+            .iter()
+            .filter(|reference| !reference.is_definition)
+        {
+            let target = reference.target;
+            let tree = &workspace.module(target.module).tree;
+            let Some(identifier) = tree
+                .root_node()
+                .named_descendant_for_byte_range(target.range.start_byte, target.range.end_byte)
+            else {
+                continue;
+            };
+            // A plain `{{name}}` of a string becomes the string itself.
+            let interpolation = plain_interpolation(identifier);
+            match (interpolation, &literal) {
+                (Some(interpolation), Some(literal)) => self.edit(
+                    &mut changes,
+                    target.module,
+                    interpolation.byte_range(),
+                    literal,
+                ),
+                _ => self.edit(
+                    &mut changes,
+                    target.module,
+                    identifier.byte_range(),
+                    &operand,
+                ),
+            }
+        }
+        let line = line_range(&module.source, assignment.byte_range());
+        self.edit(&mut changes, variable.module, line, "");
+        Some(action(title, kind, changes))
+    }
+
+    /// Turn a simple recipe into a `task` or `file` recipe, or a `task`
+    /// recipe into a `file` recipe.
+    fn convert_kind(&self, header: RecipeHeader<'_>) -> Vec<CodeAction> {
+        let source = self.source();
+        let root = self.workspace.root();
+        let kind = CodeActionKind::REFACTOR_REWRITE;
+        let Some(name) = header.name() else {
+            return Vec::new();
+        };
+        let start = name.byte_range().start;
+        let mut actions = Vec::new();
+
+        if header.kind() == RecipeKind::Simple {
+            let mut changes = Changes::default();
+            self.edit(&mut changes, root, start..start, "task ");
+            actions.push(action(
+                "Convert to task recipe".to_string(),
+                kind.clone(),
+                changes,
+            ));
+        }
+
+        let title = "Convert to file recipe".to_string();
+        let mut changes = Changes::default();
+        match header.kind_keyword() {
+            Some(keyword) => self.edit(&mut changes, root, keyword.byte_range(), "file"),
+            None => self.edit(&mut changes, root, start..start, "file "),
+        }
+        if !header.parameters().is_empty() {
+            actions.push(disabled(title, kind, "file recipes take no parameters"));
+            return actions;
+        }
+        // File recipes run their inputs' recipes only when those are file
+        // recipes themselves.
+        let recipe_dependency = header
+            .dependencies()
+            .into_iter()
+            .find(|dependency| !dependency.is_path(source) && !self.is_file_recipe(dependency));
+        match recipe_dependency {
+            Some(dependency) => {
+                let name = dependency.name(source);
+                let reason = format!("'{name}' is not a file, so it would no longer run first");
+                actions.push(disabled(title, kind, &reason));
+            }
+            None => actions.push(action(title, kind, changes)),
+        }
+        actions
+    }
+
+    fn is_file_recipe(&self, dependency: &Dependency<'_>) -> bool {
+        let workspace = self.workspace;
+        resolve::resolve_recipe(workspace, workspace.root(), dependency.name(self.source()))
+            .is_some_and(|recipe| workspace.entry(recipe).kind == RecipeKind::File)
+    }
+
+    /// Sort the dependencies of `header` by name, keeping the separators
+    /// and comments between them.
+    fn sort_dependencies(&self, header: RecipeHeader<'_>) -> Option<CodeAction> {
+        let source = self.source();
+        let nodes: Vec<Node<'_>> = match header.kind() {
+            RecipeKind::File => header.file_dependencies(),
+            _ => header
+                .dependencies()
+                .iter()
+                .map(|dependency| dependency.syntax())
+                .collect(),
+        };
+        let names: Vec<&str> = nodes.iter().map(|node| node_text(*node, source)).collect();
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+        if sorted == names {
+            return None;
+        }
+        let mut changes = Changes::default();
+        for ((node, name), new_name) in nodes.iter().zip(&names).zip(&sorted) {
+            if name != new_name {
+                self.edit(
+                    &mut changes,
+                    self.workspace.root(),
+                    node.byte_range(),
+                    new_name,
+                );
+            }
+        }
+        let kind = CodeActionKind::REFACTOR_REWRITE;
+        Some(action("Sort dependencies".to_string(), kind, changes))
+    }
+
+    /// Move `recipe` to the end of each file the document imports, qualifying
+    /// the names that refer to it.
+    fn move_recipe(&self, recipe: Recipe<'_>, header: RecipeHeader<'_>) -> Vec<CodeAction> {
+        let workspace = self.workspace;
+        let source = self.source();
+        let root = workspace.root();
+        let Some(name) = header.name() else {
+            return Vec::new();
+        };
+        let name_text = name.text(source);
+        let kind = refactor_move();
+        let mut actions = Vec::new();
+
+        for import in workspace.imports() {
+            let Some(target) = import
+                .to
+                .filter(|_| import.from == root && !import.is_repeat)
+            else {
+                continue;
+            };
+            let module = workspace.module(target);
+            let file = module.path.file_name().map_or_else(
+                || import.path.clone(),
+                |name| name.to_string_lossy().into_owned(),
+            );
+            let title = format!("Move '{name_text}' to {file}");
+
+            if module.index.recipe(name_text).is_some() {
+                let reason = format!("{file} already has a recipe named '{name_text}'");
+                actions.push(disabled(title, kind.clone(), &reason));
+                continue;
+            }
+            // The moved recipe sees the imported file's recipes unqualified,
+            // and cannot reach back into this one.
+            let mut text = node_text(recipe.syntax(), source).to_string();
+            let mut blocked = None;
+            for dependency in header.dependencies().iter().rev() {
+                let Some(node) = dependency.name_node() else {
+                    continue;
+                };
+                let dependency_name = node_text(node, source);
+                let Some(found) = resolve::resolve_recipe(workspace, root, dependency_name) else {
+                    continue;
+                };
+                if found.module != target {
+                    blocked = Some(dependency_name);
+                    break;
+                }
+                let start = node.start_byte() - recipe.syntax().start_byte();
+                let local = &workspace.entry(found).name;
+                text.replace_range(start..start + dependency_name.len(), local);
+            }
+            if let Some(dependency) = blocked {
+                let reason = format!("'{name_text}' depends on '{dependency}', outside {file}");
+                actions.push(disabled(title, kind.clone(), &reason));
+                continue;
+            }
+
+            let mut changes = Changes::default();
+            // The recipe node takes in the blank lines after it.
+            text.truncate(text.trim_end_matches('\n').len());
+            text.push('\n');
+            let end = module.source.len();
+            let separator = append_separator(&module.source);
+            self.edit(
+                &mut changes,
+                target,
+                end..end,
+                &format!("{separator}{text}"),
+            );
+            self.edit(
+                &mut changes,
+                root,
+                removal_range(source, recipe.byte_range()),
+                "",
+            );
+            if let Some(namespace) = &import.namespace {
+                let offset = name.byte_range().start;
+                let qualified = format!("{namespace}:{name_text}");
+                for reference in references::references(workspace, offset).unwrap_or_default() {
+                    if reference.is_definition || reference.target.module == target {
+                        continue;
+                    }
+                    let range = reference.target.range;
+                    let module = reference.target.module;
+                    self.edit(
+                        &mut changes,
+                        module,
+                        range.start_byte..range.end_byte,
+                        &qualified,
+                    );
+                }
+            }
+            actions.push(action(title, kind.clone(), changes));
+        }
+        actions
+    }
+}
+
+/// The `{{name}}` interpolation that consists of nothing but `identifier`.
+fn plain_interpolation(identifier: Node<'_>) -> Option<Interpolation<'_>> {
+    let interpolation =
+        std::iter::successors(identifier.parent(), Node::parent).find_map(Interpolation::cast)?;
+    let Some(ExpressionKind::Value(value)) = interpolation.expression()?.kind() else {
+        return None;
+    };
+    match value.kind()? {
+        ValueKind::Identifier(name) if name.syntax() == identifier => Some(interpolation),
+        _ => None,
+    }
+}
+
+/// Where to add `name` to the dependencies of `header`, and the text to
+/// insert there.
+fn dependency_insertion(header: RecipeHeader<'_>, name: &str) -> Option<(usize, String)> {
+    if let Some(list) = header.dependency_list() {
+        return match header.dependencies().last() {
+            Some(last) => Some((last.byte_range().end, format!(", {name}"))),
+            None => {
+                let open = list.child(0)?;
+                Some((open.end_byte(), name.to_string()))
+            }
+        };
+    }
+    let mut cursor = header.syntax().walk();
+    let colon = header
+        .syntax()
+        .children(&mut cursor)
+        .find(|child| child.kind() == ":")?;
+    Some((colon.end_byte(), format!(" [{name}]")))
+}
+
+/// How many `@if` and `@each` blocks of `body` are open at `offset`.
+fn block_depth(body: Node<'_>, offset: usize) -> usize {
+    let mut depth: usize = 0;
+    let mut cursor = body.walk();
+    for line in body.named_children(&mut cursor) {
+        if line.start_byte() >= offset {
+            break;
+        }
+        let keyword = line.child(0).map(|inner| inner.kind());
+        match keyword {
+            Some(kinds::IF_DIRECTIVE | kinds::EACH_DIRECTIVE) => depth += 1,
+            Some(kinds::END_DIRECTIVE) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// Identifiers and dependency names under `node`.
+fn names(node: Node<'_>) -> Vec<Node<'_>> {
+    let mut names = tree_sitter_jake::ast::descendants_of_kind(node, kinds::IDENTIFIER);
+    names.extend(tree_sitter_jake::ast::descendants_of_kind(
+        node,
+        kinds::DEPENDENCY_NAME,
+    ));
+    names
+}
+
+fn node_text<'s>(node: Node<'_>, source: &'s str) -> &'s str {
+    &source[node.byte_range()]
+}
+
+/// The whole lines covering `range`, including the final newline.
+fn line_range(source: &str, range: Range<usize>) -> Range<usize> {
+    let start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    if range.end > range.start && source[..range.end].ends_with('\n') {
+        return start..range.end;
+    }
+    let end = source[range.end..]
+        .find('\n')
+        .map_or(source.len(), |i| range.end + i + 1);
+    start..end
+}
+
+/// The lines of `range` and the blank line after them, so removing a recipe
+/// does not leave two blank lines behind.
+fn removal_range(source: &str, range: Range<usize>) -> Range<usize> {
+    let lines = line_range(source, range);
+    match source[lines.end..].strip_prefix('\n') {
+        Some(_) => lines.start..lines.end + 1,
+        None => lines,
+    }
+}
+
+fn action(title: String, kind: CodeActionKind, changes: Changes) -> CodeAction {
+    CodeAction {
+        title,
+        kind: Some(kind),
+        edit: Some(WorkspaceEdit::new(changes.0)),
+        ..CodeAction::default()
+    }
+}
+
+fn disabled(title: String, kind: CodeActionKind, reason: &str) -> CodeAction {
+    CodeAction {
+        title,
+        kind: Some(kind),
+        disabled: Some(CodeActionDisabled {
+            reason: reason.to_string(),
+        }),
+        ..CodeAction::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removal_range() {
+        let source = "a:\n    x\n\nb:\n    y\n";
+        let b = source.find("b:").unwrap();
+        assert_eq!(removal_range(source, 0..9), 0..10);
+        assert_eq!(removal_range(source, b..source.len()), b..source.len());
+    }
+}
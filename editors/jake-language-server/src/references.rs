@@ -0,0 +1,347 @@
+//! Find-references and rename.
+//!
+//! Every identifier and dependency name in the workspace is classified with
+//! [`resolve::classify`] and reduced to a [`Key`] naming what it refers to.
+//! References are the names that share the key of the one under the cursor,
+//! so a parameter only matches uses inside its recipe and `docker:build`
+//! matches `build` inside `docker.jake`.
+
+use std::fmt;
+
+use tree_sitter::{Node, Point, Range};
+use tree_sitter_jake::ast::{kinds, AstNode, RecipeKind};
+use tree_sitter_jake::workspace::{ModuleId, Workspace};
+
+use crate::resolve::{self, Symbol, Target};
+
+/// The identity of a renamable definition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Key {
+    /// A recipe, by its position in the workspace.
+    Recipe(ModuleId, usize),
+    /// An alias of a recipe. Aliases are renamed on their own.
+    Alias(ModuleId, usize, String),
+    /// A variable. Variables share one global scope.
+    Variable(String),
+    /// A parameter, by the start of its name.
+    Parameter(ModuleId, usize),
+}
+
+/// A name that refers to a definition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reference {
+    /// The part of the name to replace on rename: `build` in `docker:build`.
+    pub target: Target,
+    pub is_definition: bool,
+}
+
+/// Why a rename was refused.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RenameError {
+    /// The cursor is on a builtin, an `@each` item or a `file` recipe.
+    NotRenamable(&'static str),
+    /// The new name is not an identifier.
+    InvalidName(String),
+    /// Another definition already uses the new name.
+    Collision(String),
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::NotRenamable(what) => write!(f, "{what} cannot be renamed"),
+            RenameError::InvalidName(name) => write!(f, "`{name}` is not a valid name"),
+            RenameError::Collision(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// Every reference to the name at `offset` in the root module, definitions
+/// first and then in module and source order. `None` when the cursor is not
+/// on a name with a definition.
+pub fn references(workspace: &Workspace, offset: usize) -> Option<Vec<Reference>> {
+    let (key, _) = key_at(workspace, offset).ok()??;
+    Some(collect(workspace, &key))
+}
+
+/// The range to rename for the name at `offset`, in the root module.
+pub fn prepare_rename(workspace: &Workspace, offset: usize) -> Result<Option<Range>, RenameError> {
+    Ok(key_at(workspace, offset)?.map(|(_, range)| range))
+}
+
+/// The references to rewrite to rename the name at `offset` to `new_name`.
+pub fn rename(
+    workspace: &Workspace,
+    offset: usize,
+    new_name: &str,
+) -> Result<Vec<Reference>, RenameError> {
+    if !is_identifier(new_name) {
+        return Err(RenameError::InvalidName(new_name.to_string()));
+    }
+    let Some((key, _)) = key_at(workspace, offset)? else {
+        return Ok(Vec::new());
+    };
+    let references = collect(workspace, &key);
+    check_collision(workspace, &key, &references, new_name)?;
+    Ok(references)
+}
+
+/// The key of the name at `offset` and the part of it a rename replaces.
+fn key_at(workspace: &Workspace, offset: usize) -> Result<Option<(Key, Range)>, RenameError> {
+    let root = workspace.root();
+    let module = workspace.module(root);
+    let Some(node) = resolve::name_at(module.tree.root_node(), offset) else {
+        return Ok(None);
+    };
+    match resolve::classify(node, &module.source) {
+        Some(Symbol::Function(_)) => Err(RenameError::NotRenamable("a builtin function")),
+        Some(Symbol::EachItem(_)) => Err(RenameError::NotRenamable("`item`")),
+        Some(symbol) => Ok(key(workspace, root, node, symbol)?.map(|(key, range, _)| (key, range))),
+        None => Ok(None),
+    }
+}
+
+/// What `symbol`, classified from `node` in `module`, refers to, the range
+/// to rename and whether `node` is the definition.
+fn key(
+    workspace: &Workspace,
+    module: ModuleId,
+    node: Node<'_>,
+    symbol: Symbol<'_>,
+) -> Result<Option<(Key, Range, bool)>, RenameError> {
+    let parent_kind = node.parent().map(|parent| parent.kind());
+    let key = match symbol {
+        Symbol::Recipe(name) => {
+            let Some(recipe) = resolve::resolve_recipe(workspace, module, name) else {
+                return Ok(None);
+            };
+            let entry = workspace.entry(recipe);
+            if entry.kind == RecipeKind::File {
+                return Err(RenameError::NotRenamable("a `file` recipe"));
+            }
+            if entry.aliases.iter().any(|alias| alias.name == name) {
+                let key = Key::Alias(recipe.module, recipe.entry, name.to_string());
+                let is_definition = parent_kind == Some(kinds::RECIPE_ATTRIBUTE);
+                (key, node.range(), is_definition)
+            } else {
+                let key = Key::Recipe(recipe.module, recipe.entry);
+                let is_definition = parent_kind == Some(kinds::RECIPE_HEADER);
+                (key, suffix(node, entry.name.len()), is_definition)
+            }
+        }
+        Symbol::Variable(name) => {
+            if workspace.variable(name).is_none() {
+                return Ok(None);
+            }
+            let is_definition = parent_kind == Some(kinds::ASSIGNMENT);
+            (Key::Variable(name.to_string()), node.range(), is_definition)
+        }
+        Symbol::Parameter(_, parameter) => {
+            let Some(name) = parameter.name() else {
+                return Ok(None);
+            };
+            let key = Key::Parameter(module, name.syntax().start_byte());
+            (key, node.range(), parent_kind == Some(kinds::PARAMETER))
+        }
+        Symbol::EachItem(_) | Symbol::Function(_) => return Ok(None),
+    };
+    Ok(Some(key))
+}
+
+/// Every name in the workspace with `key`.
+fn collect(workspace: &Workspace, key: &Key) -> Vec<Reference> {
+    let mut references = Vec::new();
+    for (id, module) in workspace.modules() {
+        for node in names(module.tree.root_node()) {
+            let Some(symbol) = resolve::classify(node, &module.source) else {
+                continue;
+            };
+            if let Ok(Some((found, range, is_definition))) = self::key(workspace, id, node, symbol)
+            {
+                if found == *key {
+                    references.push(Reference {
+                        target: Target { module: id, range },
+                        is_definition,
+                    });
+                }
+            }
+        }
+    }
+    references.sort_by_key(|reference| !reference.is_definition);
+    references
+}
+
+/// Refuse renames that would make the references resolve elsewhere.
+fn check_collision(
+    workspace: &Workspace,
+    key: &Key,
+    references: &[Reference],
+    new_name: &str,
+) -> Result<(), RenameError> {
+    let collision = |message: String| Err(RenameError::Collision(message));
+    match key {
+        Key::Recipe(module, _) => {
+            let namespace = workspace.module(*module).namespace.as_deref();
+            let qualified = match namespace {
+                Some(namespace) => format!("{namespace}.{new_name}"),
+                None => new_name.to_string(),
+            };
+            let in_module = workspace.module(*module).index.recipe(new_name);
+            if workspace.recipe(&qualified).is_some() || in_module.is_some() {
+                return collision(format!("a recipe named `{new_name}` already exists"));
+            }
+        }
+        Key::Alias(..) => {
+            if workspace.recipe(new_name).is_some() {
+                return collision(format!("a recipe named `{new_name}` already exists"));
+            }
+        }
+        Key::Variable(_) => {
+            if workspace.variable(new_name).is_some() {
+                return collision(format!("a variable named `{new_name}` already exists"));
+            }
+            // A use inside a recipe with a parameter of the new name would
+            // read the parameter instead.
+            for reference in references {
+                let module = workspace.module(reference.target.module);
+                let node = node_at(module.tree.root_node(), reference.target.range);
+                let recipe = node.and_then(resolve::enclosing_recipe);
+                if let Some(recipe) = recipe {
+                    if resolve::find_parameter(recipe, &module.source, new_name).is_some() {
+                        let name = recipe.name(&module.source).unwrap_or_default();
+                        return collision(format!(
+                            "`{new_name}` would be shadowed by a parameter of `{name}`"
+                        ));
+                    }
+                }
+            }
+        }
+        Key::Parameter(module, start) => {
+            let module = workspace.module(*module);
+            let node = module
+                .tree
+                .root_node()
+                .named_descendant_for_byte_range(*start, *start);
+            let Some(recipe) = node.and_then(resolve::enclosing_recipe) else {
+                return Ok(());
+            };
+            let name = recipe.name(&module.source).unwrap_or_default();
+            if resolve::find_parameter(recipe, &module.source, new_name).is_some() {
+                return collision(format!("`{name}` already has a parameter `{new_name}`"));
+            }
+            // The renamed parameter would hide a variable the recipe reads.
+            let captures = names(recipe.syntax()).into_iter().any(|node| {
+                resolve::classify(node, &module.source) == Some(Symbol::Variable(new_name))
+            });
+            if captures {
+                return collision(format!(
+                    "`{name}` already uses a variable named `{new_name}`"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Identifiers, dependency names and hook targets under `node`, in source
+/// order.
+fn names(node: Node<'_>) -> Vec<Node<'_>> {
+    fn collect_names<'tree>(node: Node<'tree>, names: &mut Vec<Node<'tree>>) {
+        if matches!(
+            node.kind(),
+            kinds::IDENTIFIER | kinds::DEPENDENCY_NAME | kinds::HOOK_TARGET
+        ) {
+            names.push(node);
+            return;
+        }
+        // Operands of `+` and `/` are anonymous `expression` aliases, so
+        // anonymous children are walked too.
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            collect_names(child, names);
+        }
+    }
+    let mut names = Vec::new();
+    collect_names(node, &mut names);
+    names
+}
+
+/// The name node covering `range`.
+fn node_at(root: Node<'_>, range: Range) -> Option<Node<'_>> {
+    root.named_descendant_for_byte_range(range.start_byte, range.end_byte)
+}
+
+/// The last `len` bytes of a single-line `node`, where the recipe's own
+/// name sits in `ns:name`.
+fn suffix(node: Node<'_>, len: usize) -> Range {
+    let range = node.range();
+    let len = len.min(range.end_byte - range.start_byte);
+    Range {
+        start_byte: range.end_byte - len,
+        start_point: Point::new(range.end_point.row, range.end_point.column - len),
+        ..range
+    }
+}
+
+/// Whether `name` matches the grammar's `identifier`.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use tree_sitter_jake::workspace::MemoryFileSystem;
+
+    use super::*;
+
+    fn workspace(source: &str) -> Workspace {
+        Workspace::with_root_source(&MemoryFileSystem::new(), "/Jakefile", source.to_string())
+    }
+
+    fn texts<'a>(workspace: &'a Workspace, references: &[Reference]) -> Vec<&'a str> {
+        let source = &workspace.module(workspace.root()).source;
+        references
+            .iter()
+            .map(|reference| {
+                let range = reference.target.range;
+                &source[range.start_byte..range.end_byte]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parameters_are_scoped_to_their_recipe() {
+        let source = "env = \"prod\"\n\ntask build env=\"dev\":\n    echo {{env}}\n\ntask deploy:\n    echo {{env}}\n";
+        let workspace = workspace(source);
+
+        let offset = source.find("{{env").unwrap() + 2;
+        let parameter = references(&workspace, offset).unwrap();
+        let starts: Vec<_> = parameter
+            .iter()
+            .map(|reference| reference.target.range.start_byte)
+            .collect();
+        assert_eq!(texts(&workspace, &parameter), ["env", "env"]);
+        assert_eq!(starts, [source.find("env=").unwrap(), offset]);
+
+        // `deploy` has no parameter, so its `env` is the variable.
+        let offset = source.rfind("{{env").unwrap() + 2;
+        let variable = references(&workspace, offset).unwrap();
+        assert_eq!(variable[0].target.range.start_byte, 0);
+        assert_eq!(variable.len(), 2);
+    }
+
+    #[test]
+    fn test_identifier_names() {
+        assert!(is_identifier("build-all_2"));
+        assert!(is_identifier("_private"));
+        assert!(!is_identifier(""));
+        assert!(!is_identifier("2fast"));
+        assert!(!is_identifier("docker:build"));
+    }
+}
@@ -0,0 +1,225 @@
+//! Name resolution: what a name in a document refers to and where that is
+//! defined.
+//!
+//! Scoping follows `queries/jake/locals.scm`: each recipe is a scope holding
+//! its parameters, while variables, recipes and import namespaces are global.
+//! `item` is bound by the innermost enclosing `@each`. Recipes and variables
+//! are looked up in the [`Workspace`] rooted at the document, so names from
+//! imported files resolve to the file that defines them.
+
+use tree_sitter::{Node, Range};
+use tree_sitter_jake::ast::{
+    fields, kinds, node_text, AstNode, AttributeKind, BodyDirective, Parameter, Recipe,
+    RecipeAttribute,
+};
+use tree_sitter_jake::blocks::{BlockKind, BlockTree};
+use tree_sitter_jake::workspace::{ModuleId, Workspace, WorkspaceRecipe};
+
+/// What a name refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symbol<'tree> {
+    /// A recipe, by the name written at the reference: plain, `ns:recipe`,
+    /// `ns.recipe`, an alias or the output path of a `file` recipe.
+    Recipe(&'tree str),
+    /// A top-level variable.
+    Variable(&'tree str),
+    /// A parameter of the recipe the name appears in.
+    Parameter(Recipe<'tree>, Parameter<'tree>),
+    /// `item`, bound by an `@each` directive.
+    EachItem(BodyDirective<'tree>),
+    /// A builtin function or condition. Builtins are part of the runtime and
+    /// have no definition in any Jakefile.
+    Function(&'tree str),
+}
+
+/// A range in one module of a workspace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Target {
+    pub module: ModuleId,
+    pub range: Range,
+}
+
+/// Classify the identifier, dependency name or hook target `node`.
+///
+/// `source` is the text `node` was parsed from; the returned names borrow
+/// from it. Returns `None` for names that do not refer to anything, such as
+/// platform names or `@each` items.
+pub fn classify<'tree>(node: Node<'tree>, source: &'tree str) -> Option<Symbol<'tree>> {
+    let name = node_text(node, source);
+    if matches!(node.kind(), kinds::DEPENDENCY_NAME | kinds::HOOK_TARGET) {
+        return Some(Symbol::Recipe(name));
+    }
+    if node.kind() != kinds::IDENTIFIER {
+        return None;
+    }
+    // `@needs cmd -> task`: the identifier after the arrow.
+    if node.prev_sibling().is_some_and(|prev| prev.kind() == "->") {
+        return Some(Symbol::Recipe(name));
+    }
+
+    let parent = node.parent()?;
+    let is_field = |field| {
+        let mut cursor = parent.walk();
+        let mut children = parent.children_by_field_name(field, &mut cursor);
+        children.any(|child| child == node)
+    };
+    match parent.kind() {
+        kinds::RECIPE_HEADER if is_field(fields::NAME) => Some(Symbol::Recipe(name)),
+        kinds::RECIPE_ATTRIBUTE
+            if is_field(fields::NAME)
+                && RecipeAttribute::cast(parent)?.kind() == Some(AttributeKind::Alias) =>
+        {
+            Some(Symbol::Recipe(name))
+        }
+        kinds::ASSIGNMENT if is_field(fields::NAME) => Some(Symbol::Variable(name)),
+        kinds::FUNCTION_CALL | kinds::CONDITION_FUNCTION if is_field(fields::NAME) => {
+            Some(Symbol::Function(name))
+        }
+        kinds::PARAMETER if is_field(fields::NAME) => {
+            let parameter = Parameter::cast(parent)?;
+            Some(Symbol::Parameter(enclosing_recipe(node)?, parameter))
+        }
+        kinds::VALUE => Some(classify_value(node, source, name)),
+        _ => None,
+    }
+}
+
+/// A name read by an expression: a parameter, `item` or a variable.
+fn classify_value<'tree>(node: Node<'tree>, source: &str, name: &'tree str) -> Symbol<'tree> {
+    let Some(recipe) = enclosing_recipe(node) else {
+        return Symbol::Variable(name);
+    };
+    if let Some(parameter) = find_parameter(recipe, source, name) {
+        return Symbol::Parameter(recipe, parameter);
+    }
+    if name == "item" {
+        if let Some(each) = enclosing_each(recipe, node) {
+            return Symbol::EachItem(each);
+        }
+    }
+    Symbol::Variable(name)
+}
+
+/// Where `symbol` is defined.
+///
+/// Recipes land on the name in their header and variables on the name of
+/// their first assignment, in whichever module defines them. Parameters and
+/// `@each` items are always in the root module, which is the document the
+/// symbol was classified in.
+pub fn definition(workspace: &Workspace, symbol: Symbol<'_>) -> Option<Target> {
+    match symbol {
+        Symbol::Recipe(name) => {
+            let recipe = resolve_recipe(workspace, workspace.root(), name)?;
+            Some(Target {
+                module: recipe.module,
+                range: workspace.entry(recipe).name_range,
+            })
+        }
+        Symbol::Variable(name) => {
+            let variable = workspace.variable(name)?;
+            Some(Target {
+                module: variable.module,
+                range: workspace.variable_entry(variable).name_range,
+            })
+        }
+        Symbol::Parameter(_, parameter) => Some(Target {
+            module: workspace.root(),
+            range: parameter.name()?.range(),
+        }),
+        Symbol::EachItem(each) => Some(Target {
+            module: workspace.root(),
+            range: each.range(),
+        }),
+        Symbol::Function(_) => None,
+    }
+}
+
+/// The recipe `name` refers to when written in `module`.
+///
+/// Imported modules see their own recipes, and those of their imports,
+/// without the namespace prefix; the loader qualifies such names the same
+/// way when it merges an import.
+pub fn resolve_recipe<'w>(
+    workspace: &'w Workspace,
+    module: ModuleId,
+    name: &str,
+) -> Option<&'w WorkspaceRecipe> {
+    let qualified = workspace
+        .module(module)
+        .namespace
+        .as_ref()
+        .and_then(|namespace| workspace.recipe(&format!("{namespace}.{name}")));
+    qualified.or_else(|| workspace.recipe(name))
+}
+
+/// The syntax node of a workspace recipe, in the tree of its module.
+pub fn recipe_node<'w>(workspace: &'w Workspace, recipe: &WorkspaceRecipe) -> Option<Recipe<'w>> {
+    let range = workspace.entry(recipe).range;
+    let root = workspace.module(recipe.module).tree.root_node();
+    let node = root.named_descendant_for_byte_range(range.start_byte, range.end_byte)?;
+    std::iter::successors(Some(node), Node::parent).find_map(Recipe::cast)
+}
+
+/// The identifier, dependency name or hook target containing `offset`, or
+/// ending right before it.
+pub fn name_at(root: Node<'_>, offset: usize) -> Option<Node<'_>> {
+    [Some(offset), offset.checked_sub(1)]
+        .into_iter()
+        .flatten()
+        .filter_map(|offset| root.named_descendant_for_byte_range(offset, offset))
+        .find(|node| {
+            matches!(
+                node.kind(),
+                kinds::IDENTIFIER | kinds::DEPENDENCY_NAME | kinds::HOOK_TARGET
+            )
+        })
+}
+
+/// The name of the `@if` condition containing `offset`, or ending right
+/// before it. Condition names are keywords rather than identifiers, so
+/// [`name_at`] does not find them.
+pub fn condition_name_at(root: Node<'_>, offset: usize) -> Option<Node<'_>> {
+    [Some(offset), offset.checked_sub(1)]
+        .into_iter()
+        .flatten()
+        .filter_map(|offset| root.descendant_for_byte_range(offset, offset))
+        .find(|node| {
+            node.parent().is_some_and(|parent| {
+                parent.kind() == kinds::CONDITION_FUNCTION
+                    && parent.child_by_field_name(fields::NAME) == Some(*node)
+            })
+        })
+}
+
+/// The recipe containing `node`, if any.
+pub fn enclosing_recipe(node: Node<'_>) -> Option<Recipe<'_>> {
+    std::iter::successors(node.parent(), Node::parent).find_map(Recipe::cast)
+}
+
+/// The parameter of `recipe` called `name`.
+pub fn find_parameter<'tree>(
+    recipe: Recipe<'tree>,
+    source: &str,
+    name: &str,
+) -> Option<Parameter<'tree>> {
+    recipe
+        .parameters()
+        .into_iter()
+        .find(|parameter| parameter.name().is_some_and(|id| id.text(source) == name))
+}
+
+/// The innermost `@each` block of `recipe` whose body contains `node`.
+fn enclosing_each<'tree>(recipe: Recipe<'tree>, node: Node<'tree>) -> Option<BodyDirective<'tree>> {
+    let blocks = BlockTree::build(recipe.body()?);
+    let offset = node.start_byte();
+    blocks
+        .blocks()
+        .into_iter()
+        .rfind(|block| {
+            let range = block.range();
+            block.kind == BlockKind::Each
+                && block.opener().syntax().end_byte() <= offset
+                && offset < range.end_byte
+        })
+        .map(|block| block.opener())
+}
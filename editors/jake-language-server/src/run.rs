@@ -0,0 +1,348 @@
+//! Running recipes from the editor: the `jake.*` commands behind the code
+//! lenses, executed through `workspace/executeCommand`.
+//!
+//! A run spawns the configured `jake` binary in the directory of the
+//! Jakefile and streams its output back line by line, as `$/progress`
+//! reports when the client has a progress token for it and as
+//! `window/logMessage` otherwise. A token the server asks the client to
+//! create is only used once the client has answered; until then the output
+//! waits in the pipes. Runs, including `-w` watchers, last until they exit,
+//! the client cancels their progress or the server shuts down.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use lsp_server::{Message, Notification};
+use lsp_types::notification::{LogMessage, Notification as _, Progress};
+use lsp_types::{
+    LogMessageParams, MessageType, ProgressParams, ProgressParamsValue, ProgressToken, Url,
+    WorkDoneProgress, WorkDoneProgressBegin, WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+use serde_json::Value;
+
+/// Run a recipe.
+pub const RUN: &str = "jake.run";
+/// Run a recipe with `-n`, printing the commands without running them.
+pub const DRY_RUN: &str = "jake.dryRun";
+/// Run a recipe with `-w`, again whenever a watched file changes.
+pub const WATCH: &str = "jake.watch";
+/// Run a recipe with extra arguments: `name=value` for parameters, and
+/// positional arguments for `{{$1}}` and `{{$@}}`.
+pub const RUN_WITH_ARGS: &str = "jake.runWithArgs";
+
+/// The commands `workspace/executeCommand` accepts.
+pub fn commands() -> Vec<String> {
+    [RUN, DRY_RUN, WATCH, RUN_WITH_ARGS]
+        .map(String::from)
+        .to_vec()
+}
+
+/// A `jake` command line, parsed from the arguments of a `jake.*` command:
+/// the URI of the Jakefile, the recipe and, for [`RUN_WITH_ARGS`], a list of
+/// arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invocation {
+    pub jakefile: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl Invocation {
+    pub fn parse(command: &str, arguments: &[Value]) -> Result<Self, String> {
+        let flag = match command {
+            RUN | RUN_WITH_ARGS => None,
+            DRY_RUN => Some("-n"),
+            WATCH => Some("-w"),
+            _ => return Err(format!("unknown command '{command}'")),
+        };
+        let (uri, recipe) = match arguments {
+            [Value::String(uri), Value::String(recipe), ..] => (uri, recipe),
+            _ => return Err(format!("{command} takes a Jakefile URI and a recipe name")),
+        };
+        let jakefile = Url::parse(uri)
+            .ok()
+            .and_then(|uri| uri.to_file_path().ok())
+            .ok_or_else(|| format!("'{uri}' is not a file"))?;
+        let file_name = jakefile
+            .file_name()
+            .ok_or_else(|| format!("'{uri}' is not a file"))?;
+
+        let mut args = vec!["-f".to_string(), file_name.to_string_lossy().into_owned()];
+        args.extend(flag.map(String::from));
+        args.push(recipe.clone());
+        if command == RUN_WITH_ARGS {
+            match arguments.get(2) {
+                None | Some(Value::Null) => {}
+                Some(Value::Array(extra)) => {
+                    for arg in extra {
+                        let Value::String(arg) = arg else {
+                            return Err(format!("{command} arguments must be strings"));
+                        };
+                        args.push(arg.clone());
+                    }
+                }
+                Some(_) => return Err(format!("{command} arguments must be a list")),
+            }
+        }
+        Ok(Self { jakefile, args })
+    }
+
+    /// How the run is titled: the command line, without the Jakefile.
+    pub fn title(&self) -> String {
+        let args = self.args.get(2..).unwrap_or_default();
+        format!("jake {}", args.join(" "))
+    }
+}
+
+/// Where the output of a run goes.
+#[derive(Clone, Debug)]
+pub enum Output {
+    Progress(ProgressToken),
+    Log,
+}
+
+/// A running `jake` process.
+struct Run {
+    child: Arc<Mutex<Child>>,
+    /// Starts the reporting of a run that waits for its output.
+    start: Option<mpsc::Sender<Output>>,
+}
+
+/// The runs in progress, by the token reporting their progress. A run
+/// leaves the map when its process exits.
+#[derive(Clone, Default)]
+pub struct Runs(Arc<Mutex<HashMap<ProgressToken, Run>>>);
+
+impl Runs {
+    /// Start `jake` for `invocation` and forward its output with `send` from
+    /// a background thread. Without an `output`, the output waits in the
+    /// pipes until [`Runs::start`] says where it goes.
+    pub fn spawn(
+        &self,
+        jake: &Path,
+        invocation: &Invocation,
+        key: ProgressToken,
+        output: Option<Output>,
+        send: impl Fn(Message) + Clone + Send + 'static,
+    ) -> std::io::Result<()> {
+        let directory = invocation.jakefile.parent().unwrap_or(Path::new("."));
+        let mut child = Command::new(jake)
+            .args(&invocation.args)
+            .current_dir(directory)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let child = Arc::new(Mutex::new(child));
+
+        let (start, started) = mpsc::channel();
+        let start = match output {
+            Some(output) => {
+                let _ = start.send(output);
+                None
+            }
+            None => Some(start),
+        };
+        let run = Run {
+            child: Arc::clone(&child),
+            start,
+        };
+        if let Ok(mut runs) = self.0.lock() {
+            runs.insert(key.clone(), run);
+        }
+
+        let title = invocation.title();
+        let runs = self.clone();
+        thread::spawn(move || {
+            // A run cancelled before it started reports to the log.
+            let output = started.recv().unwrap_or(Output::Log);
+            send(begin(&output, &title));
+            let errors = stderr.map(|pipe| {
+                let (output, send) = (output.clone(), send.clone());
+                thread::spawn(move || forward(pipe, &output, &send))
+            });
+            if let Some(pipe) = stdout {
+                forward(pipe, &output, &send);
+            }
+            if let Some(errors) = errors {
+                let _ = errors.join();
+            }
+            let status = child.lock().ok().and_then(|mut child| child.wait().ok());
+            runs.remove(&key, &child);
+            send(end(&output, &title, status));
+        });
+        Ok(())
+    }
+
+    /// Report the output of the run waiting under `key` to `output`.
+    pub fn start(&self, key: &ProgressToken, output: Output) {
+        let Ok(mut runs) = self.0.lock() else {
+            return;
+        };
+        if let Some(start) = runs.get_mut(key).and_then(|run| run.start.take()) {
+            let _ = start.send(output);
+        }
+    }
+
+    /// Stop the process of the run under `key`. Its end is still reported
+    /// once the output closes.
+    pub fn cancel(&self, key: &ProgressToken) {
+        let Ok(mut runs) = self.0.lock() else {
+            return;
+        };
+        if let Some(run) = runs.get_mut(key) {
+            run.cancel();
+        }
+    }
+
+    /// Stop every run.
+    pub fn cancel_all(&self) {
+        let Ok(mut runs) = self.0.lock() else {
+            return;
+        };
+        for (_, mut run) in runs.drain() {
+            run.cancel();
+        }
+    }
+
+    /// Forget the run under `key` if it is still the one with `child`; the
+    /// client may reuse a token once its run ended.
+    fn remove(&self, key: &ProgressToken, child: &Arc<Mutex<Child>>) {
+        let Ok(mut runs) = self.0.lock() else {
+            return;
+        };
+        if runs
+            .get(key)
+            .is_some_and(|run| Arc::ptr_eq(&run.child, child))
+        {
+            runs.remove(key);
+        }
+    }
+}
+
+impl Run {
+    fn cancel(&mut self) {
+        // Unblock a run still waiting to start.
+        self.start = None;
+        if let Ok(mut child) = self.child.lock() {
+            // The process may have exited already.
+            let _ = child.kill();
+        }
+    }
+}
+
+/// Send each line read from `pipe`.
+fn forward(pipe: impl Read, output: &Output, send: &impl Fn(Message)) {
+    for line in BufReader::new(pipe).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        send(match output {
+            Output::Progress(token) => progress(
+                token,
+                WorkDoneProgress::Report(WorkDoneProgressReport {
+                    message: Some(line),
+                    ..WorkDoneProgressReport::default()
+                }),
+            ),
+            Output::Log => log(line),
+        });
+    }
+}
+
+fn begin(output: &Output, title: &str) -> Message {
+    match output {
+        Output::Progress(token) => progress(
+            token,
+            WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                title: title.to_string(),
+                cancellable: Some(true),
+                ..WorkDoneProgressBegin::default()
+            }),
+        ),
+        Output::Log => log(format!("$ {title}")),
+    }
+}
+
+fn end(output: &Output, title: &str, status: Option<ExitStatus>) -> Message {
+    let message = match status {
+        Some(status) if status.success() => format!("{title} finished"),
+        Some(status) => format!("{title} failed: {status}"),
+        None => format!("{title} stopped"),
+    };
+    match output {
+        Output::Progress(token) => progress(
+            token,
+            WorkDoneProgress::End(WorkDoneProgressEnd {
+                message: Some(message),
+            }),
+        ),
+        Output::Log => log(message),
+    }
+}
+
+fn progress(token: &ProgressToken, value: WorkDoneProgress) -> Message {
+    let params = ProgressParams {
+        token: token.clone(),
+        value: ProgressParamsValue::WorkDone(value),
+    };
+    Notification::new(Progress::METHOD.to_string(), params).into()
+}
+
+fn log(message: String) -> Message {
+    let params = LogMessageParams {
+        typ: MessageType::LOG,
+        message,
+    };
+    Notification::new(LogMessage::METHOD.to_string(), params).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_invocation() {
+        let jakefile = std::env::temp_dir().join("project").join("Jakefile");
+        let uri = Value::String(Url::from_file_path(&jakefile).unwrap().to_string());
+        let recipe = Value::String("deploy".to_string());
+
+        let invocation = Invocation::parse(WATCH, &[uri.clone(), recipe.clone()]).unwrap();
+        assert_eq!(invocation.jakefile, jakefile);
+        assert_eq!(invocation.args, ["-f", "Jakefile", "-w", "deploy"]);
+        assert_eq!(invocation.title(), "jake -w deploy");
+
+        let extra = serde_json::json!(["env=prod", "eu"]);
+        let invocation = Invocation::parse(RUN_WITH_ARGS, &[uri.clone(), recipe, extra]).unwrap();
+        assert_eq!(invocation.title(), "jake deploy env=prod eu");
+
+        assert!(Invocation::parse(RUN, std::slice::from_ref(&uri)).is_err());
+        assert!(Invocation::parse("jake.unknown", &[uri]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_finished_runs_are_removed() {
+        let invocation = Invocation {
+            jakefile: std::env::temp_dir().join("Jakefile"),
+            args: Vec::new(),
+        };
+        let runs = Runs::default();
+        let (sender, messages) = mpsc::channel();
+        let send = move |message| {
+            let _ = sender.send(message);
+        };
+        let key = ProgressToken::String("run".to_string());
+        runs.spawn(Path::new("true"), &invocation, key, Some(Output::Log), send)
+            .unwrap();
+
+        // The channel closes once the run has ended.
+        assert_eq!(messages.iter().count(), 2);
+        assert!(runs.0.lock().unwrap().is_empty());
+    }
+}
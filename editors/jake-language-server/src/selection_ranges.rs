@@ -0,0 +1,64 @@
+//! `textDocument/selectionRange`: expand from the name or token at the
+//! cursor through the syntax nodes around it, e.g. an interpolation, its
+//! command line, the recipe body and the recipe, then the run of recipes in
+//! the recipe's `@group` and finally the whole file.
+
+use std::ops::Range;
+
+use lsp_types::{Position, SelectionRange};
+use tree_sitter::Node;
+
+use crate::document::Document;
+use crate::folding_ranges::{group_ranges, trim_end};
+
+pub fn selection_ranges(document: &Document, positions: &[Position]) -> Vec<SelectionRange> {
+    positions
+        .iter()
+        .map(|&position| selection_range(document, document.offset(position)))
+        .collect()
+}
+
+fn selection_range(document: &Document, offset: usize) -> SelectionRange {
+    let source = document.text();
+    let root = document.tree().root_node();
+    let leaf = root
+        .descendant_for_byte_range(offset, offset)
+        .unwrap_or(root);
+
+    // Innermost first. Nodes that only differ by trailing whitespace, or
+    // wrap a single child, select the same text and are skipped.
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut push = |range: Range<usize>| {
+        let range = trim_end(source, range);
+        if ranges
+            .last()
+            .is_none_or(|last| range.start < last.start || range.end > last.end)
+        {
+            ranges.push(range);
+        }
+    };
+    for node in std::iter::successors(Some(leaf), Node::parent) {
+        if node.id() == root.id() {
+            break;
+        }
+        push(node.byte_range());
+    }
+    if let Some(group) = group_ranges(document)
+        .into_iter()
+        .find(|group| group.start <= offset && offset < group.end)
+    {
+        push(group);
+    }
+    push(root.byte_range());
+
+    ranges
+        .into_iter()
+        .rev()
+        .fold(None, |parent, range| {
+            Some(SelectionRange {
+                range: document.range(range),
+                parent: parent.map(Box::new),
+            })
+        })
+        .expect("the root is always a selection range")
+}
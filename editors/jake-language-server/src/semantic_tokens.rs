@@ -0,0 +1,278 @@
+//! `textDocument/semanticTokens` from the tree-sitter tree.
+//!
+//! The grammar-based highlighters in each editor only see syntax. These
+//! tokens add what needs resolving: whether a dependency is a task, a file
+//! or a simple recipe, whether `{{name}}` reads a parameter or a global, and
+//! which names resolve to nothing.
+
+use std::ops::Range;
+
+use lsp_types::{SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend};
+use tree_sitter::Node;
+use tree_sitter_jake::ast::{fields, kinds, node_text, Assignment, AstNode, RecipeKind};
+use tree_sitter_jake::workspace::Workspace;
+
+use crate::builtins;
+use crate::document::Document;
+use crate::resolve::{self, Symbol};
+
+/// Token types, in legend order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TokenType {
+    /// An import namespace, and the `ns:` of a namespaced dependency.
+    Namespace,
+    TaskRecipe,
+    FileRecipe,
+    SimpleRecipe,
+    /// A recipe parameter or the `item` of `@each`.
+    Parameter,
+    /// A global variable.
+    Variable,
+    /// A built-in function or `@if` condition.
+    Function,
+    /// A directive, or the `task`, `file` and `as` keywords.
+    Keyword,
+    /// `$1`, `$@`, `$VAR` and `${VAR}`.
+    ShellVariable,
+    Glob,
+    /// A recipe, variable or function name that resolves to nothing.
+    Unresolved,
+}
+
+const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::new("taskRecipe"),
+    SemanticTokenType::new("fileRecipe"),
+    SemanticTokenType::new("simpleRecipe"),
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::new("shellVariable"),
+    SemanticTokenType::new("glob"),
+    SemanticTokenType::new("unresolved"),
+];
+
+/// Modifier bits, in legend order.
+const DECLARATION: u32 = 1 << 0;
+const DEFAULT_LIBRARY: u32 = 1 << 1;
+/// `NAME := value`, which the grammar accepts for compatibility with `just`
+/// but Jake itself does not.
+const DEPRECATED: u32 = 1 << 2;
+
+const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
+    SemanticTokenModifier::DEPRECATED,
+];
+
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/// Tokens for the whole document, or the part of it overlapping `range`.
+pub fn semantic_tokens(
+    document: &Document,
+    workspace: &Workspace,
+    range: Option<Range<usize>>,
+) -> Vec<SemanticToken> {
+    let mut collector = Collector {
+        source: document.text(),
+        workspace,
+        tokens: Vec::new(),
+    };
+    collector.visit(document.tree().root_node());
+
+    let mut previous = lsp_types::Position::default();
+    let mut data = Vec::new();
+    for token in collector.tokens {
+        if range
+            .as_ref()
+            .is_some_and(|range| token.range.end <= range.start || token.range.start >= range.end)
+        {
+            continue;
+        }
+        let range = document.range(token.range);
+        if range.start.line != range.end.line {
+            continue;
+        }
+        let delta_line = range.start.line - previous.line;
+        let delta_start = match delta_line {
+            0 => range.start.character - previous.character,
+            _ => range.start.character,
+        };
+        data.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: range.end.character - range.start.character,
+            token_type: token.kind as u32,
+            token_modifiers_bitset: token.modifiers,
+        });
+        previous = range.start;
+    }
+    data
+}
+
+struct Token {
+    range: Range<usize>,
+    kind: TokenType,
+    modifiers: u32,
+}
+
+struct Collector<'a> {
+    source: &'a str,
+    workspace: &'a Workspace,
+    tokens: Vec<Token>,
+}
+
+impl Collector<'_> {
+    /// Collect tokens under `node` in document order.
+    fn visit(&mut self, node: Node<'_>) {
+        match node.kind() {
+            kinds::IDENTIFIER | kinds::DEPENDENCY_NAME | kinds::HOOK_TARGET => {
+                return self.name(node)
+            }
+            kinds::OUTPUT => {
+                return self.push(node.byte_range(), TokenType::FileRecipe, DECLARATION)
+            }
+            kinds::SHELL_VARIABLE => {
+                return self.push(node.byte_range(), TokenType::ShellVariable, 0)
+            }
+            kinds::GLOB_PATTERN => return self.push(node.byte_range(), TokenType::Glob, 0),
+            kinds::FILE_DEPENDENCY if node_text(node, self.source).contains(['*', '?']) => {
+                return self.push(node.byte_range(), TokenType::Glob, 0)
+            }
+            kinds::COMMENT | kinds::STRING => return,
+            _ => {}
+        }
+        if node.child_count() == 0 {
+            if is_condition_name(node) {
+                self.push(node.byte_range(), TokenType::Function, DEFAULT_LIBRARY);
+            } else if node.kind().starts_with('@') || matches!(node.kind(), "task" | "file" | "as")
+            {
+                self.push(node.byte_range(), TokenType::Keyword, 0);
+            }
+            return;
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(child);
+        }
+    }
+
+    /// An identifier or dependency name, classified like go-to-definition.
+    fn name(&mut self, node: Node<'_>) {
+        let parent = node.parent();
+        let parent_kind = parent.map(|parent| parent.kind());
+        let declaration = |is_declaration: bool| if is_declaration { DECLARATION } else { 0 };
+        let Some(symbol) = resolve::classify(node, self.source) else {
+            match parent_kind {
+                Some(kinds::IMPORT_STATEMENT) => {
+                    self.push(node.byte_range(), TokenType::Namespace, DECLARATION)
+                }
+                Some(kinds::EXPORT_DIRECTIVE | kinds::BODY_EXPORT_DIRECTIVE) => {
+                    self.push(node.byte_range(), TokenType::Variable, 0)
+                }
+                _ => {}
+            }
+            return;
+        };
+        match symbol {
+            Symbol::Recipe(name) => {
+                // The header name or an `@alias`, but not `@needs cmd -> task`.
+                let is_declaration = match parent_kind {
+                    Some(kinds::RECIPE_HEADER) => true,
+                    Some(kinds::RECIPE_ATTRIBUTE) => {
+                        node.prev_sibling().is_none_or(|prev| prev.kind() != "->")
+                    }
+                    _ => false,
+                };
+                self.recipe(node, name, declaration(is_declaration));
+            }
+            Symbol::Variable(name) => {
+                let is_declaration = parent_kind == Some(kinds::ASSIGNMENT);
+                let mut modifiers = declaration(is_declaration);
+                let assignment = parent.and_then(Assignment::cast);
+                if is_declaration && assignment.is_some_and(|assignment| assignment.uses_walrus()) {
+                    modifiers |= DEPRECATED;
+                }
+                let kind = match self.workspace.variable(name) {
+                    Some(_) => TokenType::Variable,
+                    None => TokenType::Unresolved,
+                };
+                self.push(node.byte_range(), kind, modifiers);
+            }
+            Symbol::Parameter(..) => {
+                let is_declaration = parent_kind == Some(kinds::PARAMETER);
+                self.push(
+                    node.byte_range(),
+                    TokenType::Parameter,
+                    declaration(is_declaration),
+                );
+            }
+            Symbol::EachItem(_) => self.push(node.byte_range(), TokenType::Parameter, 0),
+            Symbol::Function(name) => match builtins::function(name) {
+                Some(_) => self.push(node.byte_range(), TokenType::Function, DEFAULT_LIBRARY),
+                None => self.push(node.byte_range(), TokenType::Unresolved, 0),
+            },
+        }
+    }
+
+    /// A recipe name, with the namespace of `ns:name` as its own token.
+    fn recipe(&mut self, node: Node<'_>, name: &str, modifiers: u32) {
+        let range = node.byte_range();
+        let workspace = self.workspace;
+        let Some(recipe) = resolve::resolve_recipe(workspace, workspace.root(), name) else {
+            return self.push(range, TokenType::Unresolved, modifiers);
+        };
+        let kind = match workspace.entry(recipe).kind {
+            RecipeKind::Task => TokenType::TaskRecipe,
+            RecipeKind::File => return self.push(range, TokenType::FileRecipe, modifiers),
+            RecipeKind::Simple => TokenType::SimpleRecipe,
+        };
+        match name.rfind([':', '.']) {
+            Some(separator) => {
+                let separator = range.start + separator;
+                self.push(range.start..separator, TokenType::Namespace, 0);
+                self.push(separator + 1..range.end, kind, modifiers);
+            }
+            None => self.push(range, kind, modifiers),
+        }
+    }
+
+    fn push(&mut self, range: Range<usize>, kind: TokenType, modifiers: u32) {
+        if !range.is_empty() {
+            self.tokens.push(Token {
+                range,
+                kind,
+                modifiers,
+            });
+        }
+    }
+}
+
+/// The name of an `@if` condition, which is a keyword token.
+fn is_condition_name(node: Node<'_>) -> bool {
+    node.parent().is_some_and(|parent| {
+        parent.kind() == kinds::CONDITION_FUNCTION
+            && parent.child_by_field_name(fields::NAME) == Some(node)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legend_matches_token_types() {
+        assert_eq!(TOKEN_TYPES.len(), TokenType::Unresolved as usize + 1);
+        assert_eq!(TOKEN_TYPES[TokenType::Glob as usize].as_str(), "glob");
+        assert_eq!(
+            TOKEN_MODIFIERS[DEPRECATED.trailing_zeros() as usize],
+            SemanticTokenModifier::DEPRECATED
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! `textDocument/signatureHelp` for built-in functions and recipes.
+//!
+//! Inside the parentheses of `{{dirname(...)}}` or `@if eq(...)` the help
+//! shows the builtin with the argument under the cursor active. On a
+//! dependency or `@before`/`@after` target it shows the parameters of the
+//! recipe, which are passed on the command line as `name=value`.
+
+use lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, Position,
+    SignatureHelp, SignatureInformation,
+};
+use tree_sitter::Node;
+use tree_sitter_jake::ast::{fields, kinds, node_text, AstNode, Parameter, Recipe, RecipeKind};
+use tree_sitter_jake::workspace::Workspace;
+
+use crate::builtins::{self, Builtin};
+use crate::document::Document;
+use crate::guide;
+use crate::resolve::{self, Symbol};
+
+pub fn signature_help(
+    document: &Document,
+    workspace: &Workspace,
+    position: Position,
+) -> Option<SignatureHelp> {
+    let offset = document.offset(position);
+    let source = document.text();
+    if let Some((call, builtin)) = call_at(document.tree().root_node(), offset, source) {
+        return Some(SignatureHelp {
+            signatures: vec![builtin_signature(builtin)],
+            active_signature: Some(0),
+            active_parameter: Some(active_argument(call, offset, builtin)),
+        });
+    }
+
+    let node = document.name_at(position)?;
+    if node.parent()?.kind() == kinds::RECIPE_HEADER {
+        return None;
+    }
+    let Symbol::Recipe(name) = resolve::classify(node, source)? else {
+        return None;
+    };
+    let recipe = resolve::resolve_recipe(workspace, workspace.root(), name)?;
+    let module = workspace.module(recipe.module);
+    let signature = recipe_signature(resolve::recipe_node(workspace, recipe)?, &module.source);
+    Some(SignatureHelp {
+        signatures: vec![signature],
+        active_signature: Some(0),
+        active_parameter: None,
+    })
+}
+
+/// The innermost function call or `@if` condition whose parentheses contain
+/// `offset`, and the builtin it calls.
+fn call_at<'tree>(
+    root: Node<'tree>,
+    offset: usize,
+    source: &str,
+) -> Option<(Node<'tree>, &'static Builtin)> {
+    let node = root.descendant_for_byte_range(offset, offset)?;
+    std::iter::successors(Some(node), Node::parent).find_map(|call| {
+        let lookup = match call.kind() {
+            kinds::FUNCTION_CALL => builtins::function,
+            kinds::CONDITION_FUNCTION => builtins::condition,
+            _ => return None,
+        };
+        let mut cursor = call.walk();
+        let mut children = call.children(&mut cursor);
+        let open = children.find(|child| child.kind() == "(")?;
+        let close = children
+            .find(|child| child.kind() == ")")
+            .map_or(call.end_byte(), |close| close.start_byte());
+        if !(open.end_byte()..=close).contains(&offset) {
+            return None;
+        }
+        let name = call.child_by_field_name(fields::NAME)?;
+        Some((call, lookup(node_text(name, source))?))
+    })
+}
+
+/// The index of the argument at `offset`: the number of commas before it.
+fn active_argument(call: Node<'_>, offset: usize, builtin: &Builtin) -> u32 {
+    let commas = call
+        .child_by_field_name(fields::ARGUMENTS)
+        .map_or(0, |sequence| {
+            let mut cursor = sequence.walk();
+            let commas = sequence
+                .children(&mut cursor)
+                .filter(|child| child.kind() == "," && child.start_byte() < offset)
+                .count();
+            commas
+        });
+    let last = builtin.parameters.len().saturating_sub(1);
+    commas.min(last) as u32
+}
+
+fn builtin_signature(builtin: &Builtin) -> SignatureInformation {
+    let mut label = SignatureLabel::new(builtin.name);
+    label.push("(");
+    for (i, parameter) in builtin.parameters.iter().enumerate() {
+        if i > 0 {
+            label.push(", ");
+        }
+        label.push_parameter(parameter, None);
+    }
+    label.push(")");
+    let description = guide::function(builtin.name).map_or(builtin.summary, |row| row.description);
+    label.finish(Some(description.to_string()))
+}
+
+/// `task name param=default +rest`, with a parameter for each of the
+/// recipe's parameters.
+pub fn recipe_signature(recipe: Recipe<'_>, source: &str) -> SignatureInformation {
+    let mut label = SignatureLabel::new(match recipe.kind() {
+        RecipeKind::Task => "task ",
+        RecipeKind::File => "file ",
+        RecipeKind::Simple => "",
+    });
+    label.push(recipe.name(source).unwrap_or_default());
+    for parameter in recipe.parameters() {
+        label.push(" ");
+        let documentation = match parameter.kleene() {
+            Some("*") => Some("Takes zero or more values"),
+            Some(_) => Some("Takes one or more values"),
+            None => None,
+        };
+        label.push_parameter(&parameter_text(parameter, source), documentation);
+    }
+    let description = recipe
+        .description()
+        .map(|description| description.value(source).into_owned());
+    label.finish(description)
+}
+
+/// A parameter as written, including the `*`/`+` of a variadic parameter.
+pub fn parameter_text(parameter: Parameter<'_>, source: &str) -> String {
+    format!(
+        "{}{}",
+        parameter.kleene().unwrap_or_default(),
+        parameter.text(source)
+    )
+}
+
+/// A signature label and the UTF-16 offsets of its parameters.
+struct SignatureLabel {
+    label: String,
+    parameters: Vec<ParameterInformation>,
+}
+
+impl SignatureLabel {
+    fn new(prefix: &str) -> Self {
+        Self {
+            label: prefix.to_string(),
+            parameters: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        self.label.push_str(text);
+    }
+
+    fn push_parameter(&mut self, text: &str, documentation: Option<&str>) {
+        let start = utf16_len(&self.label);
+        self.label.push_str(text);
+        self.parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, utf16_len(&self.label)]),
+            documentation: documentation.map(|text| Documentation::String(text.to_string())),
+        });
+    }
+
+    fn finish(self, documentation: Option<String>) -> SignatureInformation {
+        SignatureInformation {
+            label: self.label,
+            documentation: documentation.map(|value| {
+                Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value,
+                })
+            }),
+            parameters: Some(self.parameters),
+            active_parameter: None,
+        }
+    }
+}
+
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
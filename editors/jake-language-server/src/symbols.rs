@@ -0,0 +1,301 @@
+//! `textDocument/documentSymbol` and `workspace/symbol`.
+//!
+//! The outline of a file nests recipes under their `@group` and parameters
+//! under their recipe, next to top-level variables and imports. Workspace
+//! symbols search every module reachable from an open document or from a
+//! Jakefile in a workspace folder, under the names Jake runs recipes by.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use lsp_types::{DocumentSymbol, OneOf, SymbolKind, Url, WorkspaceSymbol};
+use tree_sitter_jake::ast::RecipeKind;
+use tree_sitter_jake::index::RecipeEntry;
+use tree_sitter_jake::workspace::{ModuleCache, Workspace};
+
+use crate::document::Document;
+use crate::resolve::Target;
+use crate::workspace::{self, Overlay};
+
+pub fn document_symbols(document: &Document) -> Vec<DocumentSymbol> {
+    let index = document.index();
+    let mut symbols: Vec<DocumentSymbol> = index
+        .imports()
+        .iter()
+        .map(|import| {
+            symbol(
+                document,
+                &import.path,
+                import
+                    .namespace
+                    .as_ref()
+                    .map(|namespace| format!("as {namespace}")),
+                SymbolKind::MODULE,
+                import.range,
+                import.path_range,
+            )
+        })
+        .chain(index.variables().iter().map(|variable| {
+            symbol(
+                document,
+                &variable.name,
+                Some(variable.value.clone()),
+                SymbolKind::VARIABLE,
+                variable.range,
+                variable.name_range,
+            )
+        }))
+        .collect();
+
+    // Groups take the place of their first recipe.
+    let mut groups: Vec<DocumentSymbol> = Vec::new();
+    for recipe in index.recipes() {
+        let child = recipe_symbol(document, recipe);
+        let Some(name) = &recipe.group else {
+            symbols.push(child);
+            continue;
+        };
+        match groups.iter_mut().find(|group| &group.name == name) {
+            Some(group) => {
+                group.range.end = child.range.end;
+                group.children.get_or_insert_with(Vec::new).push(child);
+            }
+            None => {
+                let mut group = symbol(
+                    document,
+                    name,
+                    None,
+                    SymbolKind::NAMESPACE,
+                    recipe.range,
+                    recipe.name_range,
+                );
+                group.children = Some(vec![child]);
+                groups.push(group);
+            }
+        }
+    }
+    symbols.extend(groups);
+    symbols.sort_by_key(|symbol| symbol.range.start);
+    symbols
+}
+
+fn recipe_symbol(document: &Document, recipe: &RecipeEntry) -> DocumentSymbol {
+    let detail = match &recipe.description {
+        Some(description) => format!("{} · {description}", recipe.kind.as_str()),
+        None => recipe.kind.as_str().to_string(),
+    };
+    let mut symbol = symbol(
+        document,
+        &recipe.name,
+        Some(detail),
+        recipe_kind(recipe),
+        recipe.range,
+        recipe.name_range,
+    );
+    let parameters: Vec<DocumentSymbol> = recipe
+        .parameters
+        .iter()
+        .map(|parameter| {
+            let detail = match &parameter.default {
+                Some(default) => Some(default.clone()),
+                None => parameter.is_variadic.then(|| "variadic".to_string()),
+            };
+            self::symbol(
+                document,
+                &parameter.name,
+                detail,
+                SymbolKind::VARIABLE,
+                parameter.range,
+                parameter.range,
+            )
+        })
+        .collect();
+    if !parameters.is_empty() {
+        symbol.children = Some(parameters);
+    }
+    symbol
+}
+
+fn recipe_kind(recipe: &RecipeEntry) -> SymbolKind {
+    match recipe.kind {
+        RecipeKind::File => SymbolKind::FILE,
+        RecipeKind::Task | RecipeKind::Simple => SymbolKind::FUNCTION,
+    }
+}
+
+#[allow(deprecated)]
+fn symbol(
+    document: &Document,
+    name: &str,
+    detail: Option<String>,
+    kind: SymbolKind,
+    range: tree_sitter::Range,
+    selection_range: tree_sitter::Range,
+) -> DocumentSymbol {
+    DocumentSymbol {
+        name: name.to_string(),
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: document.node_range(range),
+        selection_range: document.node_range(selection_range),
+        children: None,
+    }
+}
+
+/// Recipes and variables matching `query` in every module reachable from an
+/// open document or from a Jakefile in one of `roots`.
+///
+/// Recipes are named the way Jake runs them, so an imported `build` is found
+/// as `docker:build`. A module reachable from several places is listed once.
+pub fn workspace_symbols(
+    documents: &HashMap<Url, Document>,
+    modules: &mut ModuleCache,
+    roots: &[PathBuf],
+    query: &str,
+) -> Vec<WorkspaceSymbol> {
+    let mut open: Vec<(&Url, &Document)> = documents.iter().collect();
+    open.sort_by_key(|(uri, _)| uri.as_str());
+    let mut workspaces: Vec<(Workspace, Option<(&Url, &Document)>)> = open
+        .into_iter()
+        .map(|(uri, document)| {
+            (
+                workspace::load(documents, modules, document),
+                Some((uri, document)),
+            )
+        })
+        .collect();
+    let opened: HashSet<&PathBuf> = documents.values().filter_map(Document::path).collect();
+    for path in roots.iter().flat_map(|root| jakefiles(root)) {
+        if opened.contains(&path) {
+            continue;
+        }
+        if let Ok(workspace) = Workspace::load(&Overlay::new(documents), &path) {
+            workspaces.push((workspace, None));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    let mut symbols = Vec::new();
+    for (workspace, document) in &workspaces {
+        for (id, module) in workspace.modules() {
+            if !seen.insert(module.path.clone()) {
+                continue;
+            }
+            let container = module
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned());
+            let locate = |range| {
+                let target = Target { module: id, range };
+                match document {
+                    Some((uri, document)) => workspace::location(uri, document, workspace, target),
+                    None => workspace::module_location(workspace, target),
+                }
+            };
+            let recipes = workspace.recipes().iter();
+            for recipe in recipes.filter(|recipe| recipe.module == id) {
+                let entry = workspace.entry(recipe);
+                let name = display_name(&recipe.name, entry);
+                if !matches(query, &name) {
+                    continue;
+                }
+                let Some(location) = locate(entry.name_range) else {
+                    continue;
+                };
+                symbols.push(workspace_symbol(
+                    name,
+                    recipe_kind(entry),
+                    container.clone(),
+                    location,
+                ));
+            }
+            for variable in module.index.variables() {
+                if !matches(query, &variable.name) {
+                    continue;
+                }
+                let Some(location) = locate(variable.name_range) else {
+                    continue;
+                };
+                symbols.push(workspace_symbol(
+                    variable.name.clone(),
+                    SymbolKind::VARIABLE,
+                    container.clone(),
+                    location,
+                ));
+            }
+        }
+    }
+    symbols.sort_by(|a, b| a.name.cmp(&b.name));
+    symbols
+}
+
+/// `docker:build` for the qualified `docker.build`, as it is written in
+/// dependencies and on the command line. File recipes keep their path.
+fn display_name(qualified: &str, entry: &RecipeEntry) -> String {
+    match entry.kind {
+        RecipeKind::File => qualified.to_string(),
+        _ => qualified.replace('.', ":"),
+    }
+}
+
+/// Whether the characters of `query` appear in `name` in order, ignoring
+/// case, as editors match symbol queries.
+fn matches(query: &str, name: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| name.any(|n| n == c))
+}
+
+/// `Jakefile` and `*.jake` files directly inside `root`.
+fn jakefiles(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && (path.file_name().is_some_and(|name| name == "Jakefile")
+                    || path
+                        .extension()
+                        .is_some_and(|extension| extension == "jake"))
+        })
+        .filter_map(|path| std::fs::canonicalize(path).ok())
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn workspace_symbol(
+    name: String,
+    kind: SymbolKind,
+    container_name: Option<String>,
+    location: lsp_types::Location,
+) -> WorkspaceSymbol {
+    WorkspaceSymbol {
+        name,
+        kind,
+        tags: None,
+        container_name,
+        location: OneOf::Left(location),
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("", "build"));
+        assert!(matches("dkb", "docker:build"));
+        assert!(matches("BUILD", "docker:build"));
+        assert!(!matches("bd", "docker"));
+    }
+}
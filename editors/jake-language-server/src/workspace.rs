@@ -0,0 +1,113 @@
+//! The workspace seen from an open document.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use lsp_types::{Location, Url};
+use tree_sitter_jake::workspace::{
+    normalize_path, FileSystem, MemoryFileSystem, ModuleCache, OsFileSystem, Workspace,
+};
+
+use crate::document::Document;
+use crate::line_index::LineIndex;
+use crate::resolve::Target;
+
+/// Reads open documents from their buffers and everything else from disk,
+/// so imports see unsaved changes.
+pub struct Overlay<'a> {
+    documents: &'a HashMap<Url, Document>,
+}
+
+impl<'a> Overlay<'a> {
+    pub fn new(documents: &'a HashMap<Url, Document>) -> Self {
+        Self { documents }
+    }
+
+    fn document(&self, path: &Path) -> Option<&'a Document> {
+        let path = normalize_path(path);
+        self.documents
+            .values()
+            .find(|document| document.path() == Some(&path))
+    }
+}
+
+impl FileSystem for Overlay<'_> {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match self.document(path) {
+            Some(document) => Ok(document.text().to_string()),
+            None => OsFileSystem.read_to_string(path),
+        }
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        OsFileSystem.canonicalize(path).or_else(|err| {
+            // Buffers that were never saved only exist in the overlay.
+            match self.document(path) {
+                Some(document) => Ok(document.path().cloned().unwrap_or_default()),
+                None => Err(err),
+            }
+        })
+    }
+}
+
+/// The workspace rooted at `document`, with imports read through an
+/// [`Overlay`] of `documents`. Documents without a file path cannot import
+/// anything and get a workspace of their own.
+///
+/// Files that are in `modules` with their current contents are not parsed
+/// again.
+pub fn load(
+    documents: &HashMap<Url, Document>,
+    modules: &mut ModuleCache,
+    document: &Document,
+) -> Workspace {
+    let source = document.text().to_string();
+    match document.path() {
+        Some(path) => Workspace::with_cache(&Overlay::new(documents), path, source, modules),
+        None => Workspace::with_cache(&MemoryFileSystem::new(), UNTITLED, source, modules),
+    }
+}
+
+/// Where documents without a file path are loaded from.
+const UNTITLED: &str = "Jakefile";
+
+/// Cache the tree and index of `document`, which are kept up to date as it
+/// is edited, for workspaces that include it.
+pub fn cache(modules: &mut ModuleCache, document: &Document) {
+    let path = document
+        .path()
+        .map_or(Path::new(UNTITLED), PathBuf::as_path);
+    modules.insert(
+        path,
+        document.text().to_string(),
+        document.tree().clone(),
+        document.index().clone(),
+    );
+}
+
+/// The LSP location of `target`. The root module is `document` itself, at
+/// `uri`; other modules are located by their path.
+pub fn location(
+    uri: &Url,
+    document: &Document,
+    workspace: &Workspace,
+    target: Target,
+) -> Option<Location> {
+    if target.module == workspace.root() {
+        let range = document.node_range(target.range);
+        return Some(Location::new(uri.clone(), range));
+    }
+    module_location(workspace, target)
+}
+
+/// The LSP location of `target` in the file its module was read from.
+pub fn module_location(workspace: &Workspace, target: Target) -> Option<Location> {
+    let module = workspace.module(target.module);
+    let uri = Url::from_file_path(&module.path).ok()?;
+    let range = LineIndex::new(&module.source).range(
+        &module.source,
+        target.range.start_byte..target.range.end_byte,
+    );
+    Some(Location::new(uri, range))
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::{position_of, position_params, uri, TestClient};
+use lsp_types::request::{
+    CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+};
+use lsp_types::{
+    CallHierarchyIncomingCallsParams, CallHierarchyItem, CallHierarchyOutgoingCallsParams,
+    CallHierarchyPrepareParams, Position, SymbolKind, Url,
+};
+
+const SOURCE: &str = "\
+@import \"docker.jake\" as docker
+
+@before release echo \"tagging\"
+@after docker.push echo \"pushed\"
+
+task release: [docker:push, test]
+    @needs kubectl -> install
+    kubectl apply
+
+task test:
+    cargo test
+
+task install:
+    brew install kubectl
+";
+
+const DOCKER: &str = "\
+task build:
+    docker build .
+
+task push: [build]
+    docker push app
+";
+
+fn client() -> (TestClient, Url, Url) {
+    let mut client = TestClient::new();
+    let root = uri("calls/Jakefile");
+    let docker = uri("calls/docker.jake");
+    client.open(&docker, DOCKER);
+    client.open(&root, SOURCE);
+    (client, root, docker)
+}
+
+fn prepare(client: &mut TestClient, uri: &Url, position: Position) -> CallHierarchyItem {
+    let mut items = client
+        .request::<CallHierarchyPrepare>(CallHierarchyPrepareParams {
+            text_document_position_params: position_params(uri, position),
+            work_done_progress_params: Default::default(),
+        })
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    items.remove(0)
+}
+
+fn outgoing(client: &mut TestClient, item: CallHierarchyItem) -> Vec<(String, SymbolKind)> {
+    client
+        .request::<CallHierarchyOutgoingCalls>(CallHierarchyOutgoingCallsParams {
+            item,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .unwrap()
+        .into_iter()
+        .map(|call| (call.to.name, call.to.kind))
+        .collect()
+}
+
+fn incoming(client: &mut TestClient, item: CallHierarchyItem) -> Vec<String> {
+    client
+        .request::<CallHierarchyIncomingCalls>(CallHierarchyIncomingCallsParams {
+            item,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .unwrap()
+        .into_iter()
+        .map(|call| call.from.name)
+        .collect()
+}
+
+#[test]
+fn test_prepare_across_imports() {
+    let (mut client, root, docker) = client();
+    let item = prepare(&mut client, &root, position_of(SOURCE, "docker:push", 8));
+    assert_eq!(item.name, "docker.push");
+    assert_eq!(item.uri, docker);
+    assert_eq!(item.selection_range.start, position_of(DOCKER, "push:", 0));
+}
+
+#[test]
+fn test_outgoing_calls() {
+    let (mut client, root, _) = client();
+    let release = prepare(&mut client, &root, position_of(SOURCE, "release:", 0));
+    assert_eq!(
+        outgoing(&mut client, release),
+        [
+            ("docker.push".to_string(), SymbolKind::FUNCTION),
+            ("test".to_string(), SymbolKind::FUNCTION),
+            ("install".to_string(), SymbolKind::FUNCTION),
+            ("@before release".to_string(), SymbolKind::EVENT),
+        ]
+    );
+
+    // A hook on a recipe in an imported file, by its namespaced name.
+    let push = prepare(&mut client, &root, position_of(SOURCE, "docker:push", 8));
+    assert_eq!(
+        outgoing(&mut client, push),
+        [
+            ("docker.build".to_string(), SymbolKind::FUNCTION),
+            ("@after docker.push".to_string(), SymbolKind::EVENT),
+        ]
+    );
+}
+
+#[test]
+fn test_incoming_calls() {
+    let (mut client, root, _) = client();
+    let push = prepare(&mut client, &root, position_of(SOURCE, "docker:push", 8));
+    assert_eq!(
+        incoming(&mut client, push),
+        ["release", "@after docker.push"]
+    );
+
+    // A hook runs with the recipe it targets.
+    let release = prepare(&mut client, &root, position_of(SOURCE, "release:", 0));
+    let hook = client
+        .request::<CallHierarchyOutgoingCalls>(CallHierarchyOutgoingCallsParams {
+            item: release,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(hook.to.detail.as_deref(), Some("echo \"tagging\""));
+    assert_eq!(incoming(&mut client, hook.to), ["release"]);
+
+    let install = prepare(&mut client, &root, position_of(SOURCE, "install:", 0));
+    assert_eq!(incoming(&mut client, install), ["release"]);
+}
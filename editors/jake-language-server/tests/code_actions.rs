@@ -0,0 +1,199 @@
+mod common;
+
+use common::{apply, position_of, uri, TestClient};
+use lsp_types::request::CodeActionRequest;
+use lsp_types::{
+    CodeAction, CodeActionContext, CodeActionKind, CodeActionOrCommand, CodeActionParams, Range,
+    TextDocumentIdentifier, Url,
+};
+
+/// The code actions for the start of `needle` in `source`, opened as `name`.
+fn code_actions(
+    client: &mut TestClient,
+    name: &str,
+    source: &str,
+    needle: &str,
+    only: Option<Vec<CodeActionKind>>,
+) -> (Url, Vec<CodeAction>) {
+    let uri = uri(name);
+    client.open(&uri, source);
+    let position = position_of(source, needle, 0);
+    let response = client.request::<CodeActionRequest>(CodeActionParams {
+        text_document: TextDocumentIdentifier::new(uri.clone()),
+        range: Range::new(position, position),
+        context: CodeActionContext {
+            only,
+            ..CodeActionContext::default()
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    });
+    let actions = response
+        .unwrap_or_default()
+        .into_iter()
+        .map(|action| match action {
+            CodeActionOrCommand::CodeAction(action) => action,
+            CodeActionOrCommand::Command(command) => panic!("unexpected command {command:?}"),
+        })
+        .collect();
+    (uri, actions)
+}
+
+fn quickfix() -> Option<Vec<CodeActionKind>> {
+    Some(vec![CodeActionKind::QUICKFIX])
+}
+
+fn titles(actions: &[CodeAction]) -> Vec<&str> {
+    actions.iter().map(|action| action.title.as_str()).collect()
+}
+
+/// `source` with the edit of the action titled `title` applied.
+fn apply_action(source: &str, uri: &Url, actions: &[CodeAction], title: &str) -> String {
+    let action = actions
+        .iter()
+        .find(|action| action.title == title)
+        .unwrap_or_else(|| panic!("no action {title:?} in {:?}", titles(actions)));
+    assert_eq!(action.kind, Some(CodeActionKind::QUICKFIX));
+    let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+    apply(source, &changes[uri])
+}
+
+#[test]
+fn test_unknown_dependency() {
+    let mut client = TestClient::new();
+    let source = "task clean:\n    rm -rf dist\n\ntask build: [clena]\n    make\n";
+    let (uri, actions) = code_actions(
+        &mut client,
+        "actions-dep/Jakefile",
+        source,
+        "clena",
+        quickfix(),
+    );
+
+    assert_eq!(
+        titles(&actions),
+        ["Did you mean 'clean'?", "Create recipe 'clena'"]
+    );
+    assert_eq!(actions[0].is_preferred, Some(true));
+    assert_eq!(
+        actions[0].diagnostics.as_ref().unwrap()[0].message,
+        "Recipe 'build' depends on unknown recipe 'clena'"
+    );
+    assert_eq!(
+        apply_action(source, &uri, &actions, "Did you mean 'clean'?"),
+        "task clean:\n    rm -rf dist\n\ntask build: [clean]\n    make\n"
+    );
+    assert_eq!(
+        apply_action(source, &uri, &actions, "Create recipe 'clena'"),
+        "task clean:\n    rm -rf dist\n\ntask build: [clena]\n    make\n\ntask clena:\n"
+    );
+}
+
+#[test]
+fn test_missing_dependency_without_suggestion() {
+    let mut client = TestClient::new();
+    let source = "task build: [deploy, docker:push]\n    make";
+    let (uri, actions) = code_actions(
+        &mut client,
+        "actions-missing/Jakefile",
+        source,
+        "deploy",
+        quickfix(),
+    );
+    assert_eq!(titles(&actions), ["Create recipe 'deploy'"]);
+    assert_eq!(
+        apply_action(source, &uri, &actions, "Create recipe 'deploy'"),
+        "task build: [deploy, docker:push]\n    make\n\ntask deploy:\n"
+    );
+
+    // Namespaced names live in another file.
+    let (_, actions) = code_actions(
+        &mut client,
+        "actions-namespaced/Jakefile",
+        source,
+        "docker",
+        quickfix(),
+    );
+    assert!(actions.is_empty(), "{:?}", titles(&actions));
+}
+
+#[test]
+fn test_undefined_variable() {
+    let mut client = TestClient::new();
+    let source = "VERSION = \"1.0\"\n\ntask build:\n    echo {{VERSION}} {{TARGET}}\n";
+    let (root, actions) = code_actions(
+        &mut client,
+        "actions-var/Jakefile",
+        source,
+        "TARGET",
+        quickfix(),
+    );
+    assert_eq!(titles(&actions), ["Add variable 'TARGET'"]);
+    assert_eq!(
+        apply_action(source, &root, &actions, "Add variable 'TARGET'"),
+        "VERSION = \"1.0\"\nTARGET = \"\"\n\ntask build:\n    echo {{VERSION}} {{TARGET}}\n"
+    );
+
+    // Without variables, the declaration goes after the imports.
+    client.open(
+        &uri("actions-var-import/docker.jake"),
+        "task push:\n    docker push\n",
+    );
+    let source = "@import \"docker.jake\"\n\ntask build:\n    echo {{TARGET}}\n";
+    let (root, actions) = code_actions(
+        &mut client,
+        "actions-var-import/Jakefile",
+        source,
+        "TARGET",
+        quickfix(),
+    );
+    assert_eq!(
+        apply_action(source, &root, &actions, "Add variable 'TARGET'"),
+        "@import \"docker.jake\"\n\nTARGET = \"\"\n\ntask build:\n    echo {{TARGET}}\n"
+    );
+}
+
+#[test]
+fn test_unclosed_if() {
+    let mut client = TestClient::new();
+    let source = "task build:\n    @if env(CI)\n        make ci\n";
+    let (uri, actions) = code_actions(
+        &mut client,
+        "actions-if/Jakefile",
+        source,
+        "@if",
+        quickfix(),
+    );
+    assert_eq!(titles(&actions), ["Insert '@end'"]);
+    assert_eq!(
+        apply_action(source, &uri, &actions, "Insert '@end'"),
+        "task build:\n    @if env(CI)\n        make ci\n    @end\n"
+    );
+}
+
+#[test]
+fn test_walrus_assignment() {
+    let mut client = TestClient::new();
+    let source = "VERSION := \"1.0\"\n\ntask build:\n    echo {{VERSION}}\n";
+    let (uri, actions) = code_actions(
+        &mut client,
+        "actions-walrus/Jakefile",
+        source,
+        ":=",
+        quickfix(),
+    );
+    assert_eq!(titles(&actions), ["Convert to '='"]);
+    assert_eq!(
+        apply_action(source, &uri, &actions, "Convert to '='"),
+        "VERSION = \"1.0\"\n\ntask build:\n    echo {{VERSION}}\n"
+    );
+}
+
+#[test]
+fn test_only_other_kinds() {
+    let mut client = TestClient::new();
+    let source = "task build: [clena]\n    make\n";
+    let only = Some(vec![CodeActionKind::SOURCE]);
+    let (_, actions) = code_actions(&mut client, "actions-only/Jakefile", source, "clena", only);
+    assert!(actions.is_empty());
+}
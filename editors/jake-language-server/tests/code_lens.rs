@@ -0,0 +1,228 @@
+mod common;
+
+use common::{uri, TestClient};
+use lsp_types::request::CodeLensRequest;
+use lsp_types::{CodeLensParams, TextDocumentIdentifier};
+use serde_json::json;
+
+#[test]
+fn test_code_lenses() {
+    let mut client = TestClient::new();
+    let uri = uri("lens/Jakefile");
+    let source = "\
+task build:
+    make
+
+task deploy env=\"staging\" region:
+    echo {{env}} {{region}}
+";
+    client.open(&uri, source);
+    let lenses = client
+        .request::<CodeLensRequest>(CodeLensParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .unwrap();
+
+    let commands: Vec<_> = lenses
+        .iter()
+        .map(|lens| {
+            let command = lens.command.as_ref().unwrap();
+            (
+                lens.range.start.line,
+                command.title.as_str(),
+                command.command.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        commands,
+        [
+            (0, "▶ Run", "jake.run"),
+            (0, "Dry run", "jake.dryRun"),
+            (0, "Watch", "jake.watch"),
+            (0, "Run with args…", "jake.runWithArgs"),
+            (3, "▶ Run", "jake.run"),
+            (3, "Dry run", "jake.dryRun"),
+            (3, "Watch", "jake.watch"),
+            (3, "Run with args…", "jake.runWithArgs"),
+        ]
+    );
+    assert_eq!(
+        lenses[4].command.as_ref().unwrap().arguments,
+        Some(vec![json!(uri.as_str()), json!("deploy")])
+    );
+    assert_eq!(
+        lenses[7].command.as_ref().unwrap().arguments,
+        Some(vec![
+            json!(uri.as_str()),
+            json!("deploy"),
+            json!(["env=staging", "region="])
+        ])
+    );
+}
+
+#[cfg(unix)]
+mod execute {
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+
+    use lsp_types::notification::Progress;
+    use lsp_types::request::{ExecuteCommand, WorkDoneProgressCreate};
+    use lsp_types::{
+        ClientCapabilities, ExecuteCommandParams, InitializeParams, NumberOrString,
+        ProgressParamsValue, Url, WindowClientCapabilities, WorkDoneProgress,
+        WorkDoneProgressParams,
+    };
+    use serde_json::{json, Value};
+
+    use super::common::TestClient;
+
+    /// A directory with a Jakefile and a `jake` stand-in that prints its
+    /// arguments, warns on stderr and fails dry runs.
+    fn project(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir()
+            .join("jake-language-server-run")
+            .join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        let jakefile = dir.join("Jakefile");
+        std::fs::write(&jakefile, "task dev:\n    echo dev\n").unwrap();
+        let jake = dir.join("jake-stub");
+        std::fs::write(
+            &jake,
+            "#!/bin/sh\necho \"args: $*\"\necho warning >&2\ncase \" $* \" in *\" -n \"*) exit 1;; esac\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&jake, std::fs::Permissions::from_mode(0o755)).unwrap();
+        (jakefile, jake)
+    }
+
+    fn start(jake: &Path) -> TestClient {
+        TestClient::with_params(InitializeParams {
+            initialization_options: Some(json!({ "jakePath": jake.to_str().unwrap() })),
+            ..InitializeParams::default()
+        })
+    }
+
+    fn execute(client: &mut TestClient, command: &str, arguments: Vec<Value>, token: &str) {
+        let result = client.request::<ExecuteCommand>(ExecuteCommandParams {
+            command: command.to_string(),
+            arguments,
+            work_done_progress_params: WorkDoneProgressParams {
+                work_done_token: Some(NumberOrString::String(token.to_string())),
+            },
+        });
+        assert_eq!(result, None);
+    }
+
+    /// The title, reported lines and end message of the run reporting with
+    /// `token`.
+    fn progress(client: &mut TestClient, token: &str) -> (String, Vec<String>, String) {
+        let token = NumberOrString::String(token.to_string());
+        let (mut title, mut lines) = (String::new(), Vec::new());
+        loop {
+            let params = client.notification::<Progress>();
+            assert_eq!(params.token, token);
+            let ProgressParamsValue::WorkDone(progress) = params.value;
+            match progress {
+                WorkDoneProgress::Begin(begin) => title = begin.title,
+                WorkDoneProgress::Report(report) => lines.extend(report.message),
+                WorkDoneProgress::End(end) => {
+                    lines.sort();
+                    return (title, lines, end.message.unwrap_or_default());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_streams_output_as_progress() {
+        let (jakefile, jake) = project("run");
+        let uri = json!(Url::from_file_path(&jakefile).unwrap().as_str());
+        let mut client = start(&jake);
+
+        execute(
+            &mut client,
+            "jake.run",
+            vec![uri.clone(), json!("dev")],
+            "run",
+        );
+        assert_eq!(
+            progress(&mut client, "run"),
+            (
+                "jake dev".to_string(),
+                vec!["args: -f Jakefile dev".to_string(), "warning".to_string()],
+                "jake dev finished".to_string()
+            )
+        );
+
+        let arguments = vec![uri.clone(), json!("dev"), json!(["env=prod", "eu"])];
+        execute(&mut client, "jake.runWithArgs", arguments, "args");
+        let (_, lines, _) = progress(&mut client, "args");
+        assert_eq!(lines[0], "args: -f Jakefile dev env=prod eu");
+
+        execute(&mut client, "jake.dryRun", vec![uri, json!("dev")], "dry");
+        let (title, _, end) = progress(&mut client, "dry");
+        assert_eq!(title, "jake -n dev");
+        assert_eq!(end, "jake -n dev failed: exit status: 1");
+    }
+
+    #[test]
+    fn test_run_waits_for_created_token() {
+        let (jakefile, jake) = project("created");
+        let uri = json!(Url::from_file_path(&jakefile).unwrap().as_str());
+        let mut client = TestClient::with_params(InitializeParams {
+            initialization_options: Some(json!({ "jakePath": jake.to_str().unwrap() })),
+            capabilities: ClientCapabilities {
+                window: Some(WindowClientCapabilities {
+                    work_done_progress: Some(true),
+                    ..WindowClientCapabilities::default()
+                }),
+                ..ClientCapabilities::default()
+            },
+            ..InitializeParams::default()
+        });
+
+        let result = client.request::<ExecuteCommand>(ExecuteCommandParams {
+            command: "jake.run".to_string(),
+            arguments: vec![uri, json!("dev")],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        });
+        assert_eq!(result, None);
+        let (id, params) = client.server_request::<WorkDoneProgressCreate>();
+        assert!(!client.has_notification::<Progress>());
+
+        client.respond(id, Value::Null);
+        let NumberOrString::String(token) = params.token else {
+            panic!("expected a string token");
+        };
+        let (title, _, end) = progress(&mut client, &token);
+        assert_eq!(title, "jake dev");
+        assert_eq!(end, "jake dev finished");
+    }
+
+    #[test]
+    fn test_run_errors() {
+        let (jakefile, jake) = project("errors");
+        let uri = json!(Url::from_file_path(&jakefile).unwrap().as_str());
+
+        let mut client = start(&jake);
+        let response = client.request_raw(
+            "workspace/executeCommand",
+            json!({ "command": "jake.run", "arguments": [uri] }),
+        );
+        let error = response.error.unwrap();
+        assert_eq!(
+            error.message,
+            "jake.run takes a Jakefile URI and a recipe name"
+        );
+
+        let mut client = start(&jakefile.with_file_name("missing"));
+        let response = client.request_raw(
+            "workspace/executeCommand",
+            json!({ "command": "jake.watch", "arguments": [uri, "dev"] }),
+        );
+        assert!(response.error.unwrap().message.starts_with("Could not run"));
+    }
+}
@@ -0,0 +1,263 @@
+//! An in-process LSP client for driving the server in tests.
+
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use jake_language_server::LineIndex;
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Exit, Initialized,
+    Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Initialize, Shutdown};
+use lsp_types::{
+    Diagnostic, DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    InitializeParams, InitializeResult, InitializedParams, Position, PublishDiagnosticsParams,
+    Range, TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+    TextDocumentPositionParams, TextEdit, Url, VersionedTextDocumentIdentifier,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A client connected to a server running on a background thread.
+pub struct TestClient {
+    connection: Connection,
+    server: Option<JoinHandle<Result<(), jake_language_server::Error>>>,
+    next_id: i32,
+    /// Notifications that arrived while waiting for something else.
+    notifications: VecDeque<Notification>,
+    /// Requests from the server that arrived while waiting for something else.
+    requests: VecDeque<Request>,
+    pub initialize_result: InitializeResult,
+}
+
+impl TestClient {
+    /// Start a server and complete the `initialize` handshake.
+    pub fn new() -> Self {
+        Self::with_params(InitializeParams::default())
+    }
+
+    /// Start a server, initializing it with `params`.
+    pub fn with_params(params: InitializeParams) -> Self {
+        let (client, server) = Connection::memory();
+        let server = std::thread::spawn(move || jake_language_server::run(server));
+        let mut client = Self {
+            connection: client,
+            server: Some(server),
+            next_id: 0,
+            notifications: VecDeque::new(),
+            requests: VecDeque::new(),
+            initialize_result: InitializeResult::default(),
+        };
+        client.initialize_result = client.request::<Initialize>(params);
+        client.notify::<Initialized>(InitializedParams {});
+        client
+    }
+
+    /// Send a request and wait for its result, panicking on an error response.
+    pub fn request<R: lsp_types::request::Request>(&mut self, params: R::Params) -> R::Result {
+        let response = self.request_raw(R::METHOD, serde_json::to_value(params).unwrap());
+        if let Some(error) = response.error {
+            panic!("{} failed: {}", R::METHOD, error.message);
+        }
+        serde_json::from_value(response.result.unwrap_or_default()).unwrap()
+    }
+
+    /// Send a request by method name and wait for the response.
+    pub fn request_raw(&mut self, method: &str, params: serde_json::Value) -> Response {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        let request = Request::new(id.clone(), method.to_string(), params);
+        self.connection.sender.send(request.into()).unwrap();
+        loop {
+            match self.receive() {
+                Message::Response(response) if response.id == id => return response,
+                Message::Notification(notification) => self.notifications.push_back(notification),
+                Message::Request(request) => self.requests.push_back(request),
+                message => panic!("unexpected message: {message:?}"),
+            }
+        }
+    }
+
+    pub fn notify<N: lsp_types::notification::Notification>(&mut self, params: N::Params) {
+        let notification = Notification::new(N::METHOD.to_string(), params);
+        self.connection.sender.send(notification.into()).unwrap();
+    }
+
+    pub fn open(&mut self, uri: &Url, text: &str) {
+        self.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri.clone(),
+                "jake".to_string(),
+                1,
+                text.to_string(),
+            ),
+        });
+    }
+
+    /// Replace `range` with `text`, as an editor with incremental sync does.
+    pub fn change(&mut self, uri: &Url, version: i32, range: Range, text: &str) {
+        self.notify::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), version),
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: Some(range),
+                range_length: None,
+                text: text.to_string(),
+            }],
+        });
+    }
+
+    pub fn close(&mut self, uri: &Url) {
+        self.notify::<DidCloseTextDocument>(DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+        });
+    }
+
+    /// The next diagnostics published for `uri`.
+    pub fn diagnostics(&mut self, uri: &Url) -> Vec<Diagnostic> {
+        self.published_diagnostics(uri).diagnostics
+    }
+
+    pub fn published_diagnostics(&mut self, uri: &Url) -> PublishDiagnosticsParams {
+        loop {
+            let position = self.notifications.iter().position(|notification| {
+                notification.method == PublishDiagnostics::METHOD
+                    && notification.params["uri"] == uri.as_str()
+            });
+            if let Some(position) = position {
+                let notification = self.notifications.remove(position).unwrap();
+                return serde_json::from_value(notification.params).unwrap();
+            }
+            match self.receive() {
+                Message::Notification(notification) => self.notifications.push_back(notification),
+                Message::Request(request) => self.requests.push_back(request),
+                message => panic!("unexpected message: {message:?}"),
+            }
+        }
+    }
+
+    /// The next notification of type `N`, in the order the server sent them.
+    pub fn notification<N: lsp_types::notification::Notification>(&mut self) -> N::Params {
+        loop {
+            let position = self
+                .notifications
+                .iter()
+                .position(|notification| notification.method == N::METHOD);
+            if let Some(position) = position {
+                let notification = self.notifications.remove(position).unwrap();
+                return serde_json::from_value(notification.params).unwrap();
+            }
+            match self.receive() {
+                Message::Notification(notification) => self.notifications.push_back(notification),
+                Message::Request(request) => self.requests.push_back(request),
+                message => panic!("unexpected message: {message:?}"),
+            }
+        }
+    }
+
+    /// The next request of type `R` from the server, with its id.
+    pub fn server_request<R: lsp_types::request::Request>(&mut self) -> (RequestId, R::Params) {
+        loop {
+            let position = self
+                .requests
+                .iter()
+                .position(|request| request.method == R::METHOD);
+            if let Some(position) = position {
+                let request = self.requests.remove(position).unwrap();
+                return (request.id, serde_json::from_value(request.params).unwrap());
+            }
+            match self.receive() {
+                Message::Notification(notification) => self.notifications.push_back(notification),
+                Message::Request(request) => self.requests.push_back(request),
+                message => panic!("unexpected message: {message:?}"),
+            }
+        }
+    }
+
+    /// Answer the server request `id` with `result`.
+    pub fn respond(&mut self, id: RequestId, result: serde_json::Value) {
+        let response = Response::new_ok(id, result);
+        self.connection.sender.send(response.into()).unwrap();
+    }
+
+    /// Whether a notification of type `N` arrived and was not taken yet.
+    pub fn has_notification<N: lsp_types::notification::Notification>(&self) -> bool {
+        self.notifications
+            .iter()
+            .any(|notification| notification.method == N::METHOD)
+    }
+
+    /// Send `shutdown` and `exit` and wait for the server to stop.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        let Some(server) = self.server.take() else {
+            return;
+        };
+        self.request::<Shutdown>(());
+        self.notify::<Exit>(());
+        server.join().unwrap().unwrap();
+    }
+
+    fn receive(&mut self) -> Message {
+        self.connection
+            .receiver
+            .recv_timeout(TIMEOUT)
+            .expect("server did not respond in time")
+    }
+}
+
+impl Drop for TestClient {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            self.stop();
+        }
+    }
+}
+
+/// A `file:` URI for `name` in a directory that does not exist on disk, so
+/// only open buffers are visible to the server.
+pub fn uri(name: &str) -> Url {
+    let path: PathBuf = std::env::temp_dir()
+        .join("jake-language-server-tests")
+        .join(name);
+    Url::from_file_path(path).unwrap()
+}
+
+/// The position of the first occurrence of `needle` in `text`, plus `offset`
+/// bytes.
+pub fn position_of(text: &str, needle: &str, offset: usize) -> Position {
+    let start = text
+        .find(needle)
+        .unwrap_or_else(|| panic!("{needle:?} not in text"));
+    LineIndex::new(text).position(text, start + offset)
+}
+
+pub fn position_params(uri: &Url, position: Position) -> TextDocumentPositionParams {
+    TextDocumentPositionParams::new(TextDocumentIdentifier::new(uri.clone()), position)
+}
+
+/// The text of `source` after applying `edits`.
+pub fn apply(source: &str, edits: &[TextEdit]) -> String {
+    let index = LineIndex::new(source);
+    let mut edits: Vec<_> = edits
+        .iter()
+        .map(|edit| {
+            let start = index.offset(source, edit.range.start);
+            let end = index.offset(source, edit.range.end);
+            (start..end, edit.new_text.as_str())
+        })
+        .collect();
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut text = source.to_string();
+    for (range, new_text) in edits {
+        text.replace_range(range, new_text);
+    }
+    text
+}
@@ -0,0 +1,157 @@
+mod common;
+
+use common::{position_of, position_params, uri, TestClient};
+use lsp_types::request::Completion;
+use lsp_types::{
+    CompletionItem, CompletionParams, CompletionResponse, CompletionTextEdit, Documentation,
+    Position, Url,
+};
+
+const SOURCE: &str = "\
+@import \"docker.jake\" as docker
+
+version = \"1.0\"
+
+@desc \"Build the app\"
+task build env=\"dev\":
+    @each eu us
+        echo {{
+    @end
+";
+
+const DEPENDENCIES: &str = "\
+@import \"docker.jake\" as docker
+
+task build:
+    make
+
+@alias setup
+task _setup:
+    ./configure
+
+task deploy: [
+";
+
+const DOCKER: &str = "@desc \"Build the image\"\ntask image:\n    docker build .\n";
+
+fn open(client: &mut TestClient, text: &str) -> Url {
+    let root = uri("completion/Jakefile");
+    client.open(&uri("completion/docker.jake"), DOCKER);
+    client.open(&root, text);
+    root
+}
+
+fn complete(client: &mut TestClient, uri: &Url, position: Position) -> Vec<CompletionItem> {
+    let response = client.request::<Completion>(CompletionParams {
+        text_document_position: position_params(uri, position),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        context: None,
+    });
+    match response {
+        Some(CompletionResponse::Array(items)) => items,
+        response => panic!("expected a list of items, got {response:?}"),
+    }
+}
+
+fn labels(items: &[CompletionItem]) -> Vec<&str> {
+    items.iter().map(|item| item.label.as_str()).collect()
+}
+
+fn item<'a>(items: &'a [CompletionItem], label: &str) -> &'a CompletionItem {
+    items
+        .iter()
+        .find(|item| item.label == label)
+        .unwrap_or_else(|| panic!("no completion {label:?} in {:?}", labels(items)))
+}
+
+fn documentation(item: &CompletionItem) -> &str {
+    match &item.documentation {
+        Some(Documentation::MarkupContent(markup)) => &markup.value,
+        documentation => panic!("expected markdown, got {documentation:?}"),
+    }
+}
+
+#[test]
+fn test_dependencies() {
+    let mut client = TestClient::new();
+    let root = open(&mut client, DEPENDENCIES);
+
+    let items = complete(&mut client, &root, position_of(DEPENDENCIES, "[\n", 1));
+    assert_eq!(labels(&items), ["build", "_setup", "docker:image"]);
+    assert_eq!(item(&items, "build").detail.as_deref(), Some("task"));
+    assert_eq!(
+        documentation(item(&items, "docker:image")),
+        "Build the image\n\nDefined in `docker.jake`"
+    );
+}
+
+#[test]
+fn test_interpolation() {
+    let mut client = TestClient::new();
+    let root = open(&mut client, SOURCE);
+
+    let items = complete(&mut client, &root, position_of(SOURCE, "{{\n", 2));
+    let offered = labels(&items);
+    assert_eq!(offered[..5], ["env", "item", "version", "$1", "$@"]);
+    for function in [
+        "uppercase",
+        "dirname",
+        "without_extension",
+        "home",
+        "local_bin",
+        "launch",
+    ] {
+        assert!(offered.contains(&function), "missing {function}");
+    }
+
+    let dirname = item(&items, "dirname");
+    assert_eq!(
+        dirname.text_edit,
+        Some(CompletionTextEdit::Edit(lsp_types::TextEdit::new(
+            lsp_types::Range::new(
+                position_of(SOURCE, "{{\n", 2),
+                position_of(SOURCE, "{{\n", 2)
+            ),
+            "dirname(${1:p})".to_string()
+        )))
+    );
+    assert!(documentation(dirname).contains("Get directory part"));
+    assert!(documentation(item(&items, "$@")).contains("Access all arguments at once"));
+    assert!(documentation(item(&items, "item")).starts_with("Iterate over items"));
+}
+
+#[test]
+fn test_directives_and_conditions() {
+    let mut client = TestClient::new();
+    let text = "task build:\n    @if \n        @\n    @end\n\n@\n";
+    let root = open(&mut client, text);
+
+    // In a body, inside an `@if` block.
+    let items = complete(&mut client, &root, Position::new(2, 9));
+    let offered = labels(&items);
+    for keyword in ["@elif", "@else", "@end", "@each", "@cd"] {
+        assert!(offered.contains(&keyword), "missing {keyword}");
+    }
+    assert!(!offered.contains(&"@import"));
+    assert!(documentation(item(&items, "@cd")).contains("Run commands in a different directory"));
+    let edit = match &item(&items, "@cd").text_edit {
+        Some(CompletionTextEdit::Edit(edit)) => edit,
+        edit => panic!("expected an edit, got {edit:?}"),
+    };
+    assert_eq!(edit.range.start, Position::new(2, 8));
+
+    // At the top level, file directives and recipe metadata only.
+    let items = complete(&mut client, &root, Position::new(5, 1));
+    let offered = labels(&items);
+    assert!(offered.contains(&"@import"));
+    assert!(offered.contains(&"@group"));
+    assert!(!offered.contains(&"@end"));
+
+    let items = complete(&mut client, &root, Position::new(1, 8));
+    let offered = labels(&items);
+    for condition in ["env", "exists", "eq", "neq", "is_watching"] {
+        assert!(offered.contains(&condition), "missing {condition}");
+    }
+    assert!(documentation(item(&items, "exists")).contains("True if file or directory exists"));
+}
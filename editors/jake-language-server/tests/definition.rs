@@ -0,0 +1,110 @@
+mod common;
+
+use common::{position_of, position_params, uri, TestClient};
+use lsp_types::request::GotoDefinition;
+use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Location, Position, Range, Url};
+
+const SOURCE: &str = "\
+@import \"docker.jake\" as docker
+
+version = \"1.0\"
+
+@before build echo \"starting\"
+@after docker.build echo \"built\"
+
+@alias b
+task build env=\"dev\": [docker:build]
+    echo {{version}} {{env}}
+
+task deploy: [b]
+    @needs kubectl -> install
+    @each eu us
+        echo {{item}} {{uppercase(env)}}
+    @end
+
+task install:
+    brew install kubectl
+";
+
+const DOCKER: &str = "task build:\n    docker build .\n";
+
+fn definition(client: &mut TestClient, uri: &Url, position: Position) -> Option<Location> {
+    let response = client.request::<GotoDefinition>(GotoDefinitionParams {
+        text_document_position_params: position_params(uri, position),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    })?;
+    match response {
+        GotoDefinitionResponse::Scalar(location) => Some(location),
+        response => panic!("expected a single location, got {response:?}"),
+    }
+}
+
+fn location(uri: &Url, start: Position, len: u32) -> Location {
+    let end = Position::new(start.line, start.character + len);
+    Location::new(uri.clone(), Range::new(start, end))
+}
+
+#[test]
+fn test_definition() {
+    let mut client = TestClient::new();
+    let root = uri("definition/Jakefile");
+    let docker = uri("definition/docker.jake");
+    client.open(&docker, DOCKER);
+    client.open(&root, SOURCE);
+
+    let build = location(&root, position_of(SOURCE, "build env", 0), 5);
+    let install = location(&root, position_of(SOURCE, "install:", 0), 7);
+
+    // A namespaced dependency lands in the imported buffer.
+    let at = position_of(SOURCE, "docker:build]", 8);
+    assert_eq!(
+        definition(&mut client, &root, at),
+        Some(location(&docker, Position::new(0, 5), 5))
+    );
+
+    // So does a namespaced hook target.
+    let at = position_of(SOURCE, "docker.build echo", 8);
+    assert_eq!(
+        definition(&mut client, &root, at),
+        Some(location(&docker, Position::new(0, 5), 5))
+    );
+
+    // Hook targets, aliases and `@needs` install tasks.
+    let at = position_of(SOURCE, "build echo", 1);
+    assert_eq!(definition(&mut client, &root, at), Some(build.clone()));
+    let at = position_of(SOURCE, "[b]", 1);
+    assert_eq!(definition(&mut client, &root, at), Some(build.clone()));
+    let at = position_of(SOURCE, "-> install", 3);
+    assert_eq!(definition(&mut client, &root, at), Some(install.clone()));
+
+    // A recipe name is its own definition.
+    assert_eq!(
+        definition(&mut client, &root, build.range.start),
+        Some(build)
+    );
+
+    let at = position_of(SOURCE, "{{version", 2);
+    assert_eq!(
+        definition(&mut client, &root, at),
+        Some(location(&root, position_of(SOURCE, "version =", 0), 7))
+    );
+
+    // `env` is a parameter of `build` only.
+    let at = position_of(SOURCE, "{{env", 2);
+    assert_eq!(
+        definition(&mut client, &root, at),
+        Some(location(&root, position_of(SOURCE, "env=", 0), 3))
+    );
+    let at = position_of(SOURCE, "(env)", 1);
+    assert_eq!(definition(&mut client, &root, at), None);
+
+    // `item` is bound by the enclosing `@each`.
+    let at = position_of(SOURCE, "{{item", 2);
+    let each = definition(&mut client, &root, at).unwrap();
+    assert_eq!(each.range.start, position_of(SOURCE, "@each", 0));
+
+    // Builtins have no definition in any Jakefile.
+    let at = position_of(SOURCE, "uppercase", 0);
+    assert_eq!(definition(&mut client, &root, at), None);
+}
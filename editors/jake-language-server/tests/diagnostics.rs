@@ -0,0 +1,126 @@
+mod common;
+
+use common::{uri, TestClient};
+use lsp_types::{DiagnosticSeverity, InitializeParams, NumberOrString, Position, Range};
+use serde_json::json;
+
+fn codes(diagnostics: &[lsp_types::Diagnostic]) -> Vec<&str> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| match &diagnostic.code {
+            Some(NumberOrString::String(code)) => code.as_str(),
+            _ => "",
+        })
+        .collect()
+}
+
+#[test]
+fn test_lint_diagnostics() {
+    let mut client = TestClient::new();
+    let uri = uri("lint/Jakefile");
+    client.open(
+        &uri,
+        "VERSION = \"1.0\"\n\ntask build: [clena]\n    echo {{VERSON}}\n\ntask clean:\n    rm -rf dist\n",
+    );
+
+    let diagnostics = client.diagnostics(&uri);
+    // `VERSION` is unused because of the typo in `{{VERSON}}`.
+    assert_eq!(codes(&diagnostics), ["JK003", "JK001", "JK002"]);
+    assert_eq!(diagnostics[1].severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(
+        diagnostics[1].range,
+        Range::new(Position::new(2, 13), Position::new(2, 18))
+    );
+    assert_eq!(diagnostics[2].severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(diagnostics[2].source.as_deref(), Some("jake"));
+}
+
+#[test]
+fn test_syntax_error() {
+    let mut client = TestClient::new();
+    let uri = uri("syntax/Jakefile");
+    client.open(
+        &uri,
+        "task build: [clean\n    make\n\ntask clean:\n    rm -rf dist\n",
+    );
+
+    let diagnostics = client.diagnostics(&uri);
+    assert!(diagnostics.iter().any(|diagnostic| {
+        diagnostic.message.starts_with("Syntax error")
+            && diagnostic.severity == Some(DiagnosticSeverity::ERROR)
+    }));
+}
+
+#[test]
+fn test_imports_from_open_buffers() {
+    let mut client = TestClient::new();
+    let root = uri("imports/Jakefile");
+    let docker = uri("imports/docker.jake");
+
+    // The import is unresolved until its buffer is open.
+    client.open(
+        &root,
+        "@import \"docker.jake\" as docker\n\ntask deploy: [docker:push]\n    echo deployed\n",
+    );
+    let diagnostics = client.diagnostics(&root);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0]
+        .message
+        .starts_with("imported file not found"));
+    assert_eq!(diagnostics[0].range.start, Position::new(0, 8));
+
+    client.open(&docker, "task push:\n    docker push app\n");
+    assert!(client.diagnostics(&docker).is_empty());
+
+    // Any edit to the root rebuilds its workspace from the open buffers.
+    client.change(
+        &root,
+        2,
+        Range::new(Position::new(3, 4), Position::new(3, 8)),
+        "echo",
+    );
+    assert!(client.diagnostics(&root).is_empty());
+}
+
+#[test]
+fn test_import_changes_update_importers() {
+    let mut client = TestClient::new();
+    let root = uri("importers/Jakefile");
+    let docker = uri("importers/docker.jake");
+    client.open(&docker, "task push:\n    docker push app\n");
+    client.diagnostics(&docker);
+    client.open(
+        &root,
+        "@import \"docker.jake\" as docker\n\ntask deploy: [docker:publish]\n    echo deployed\n",
+    );
+    assert_eq!(codes(&client.diagnostics(&root)), ["JK001"]);
+
+    // Renaming the imported recipe resolves the dependency of the root.
+    client.change(
+        &docker,
+        2,
+        Range::new(Position::new(0, 5), Position::new(0, 9)),
+        "publish",
+    );
+    assert!(client.diagnostics(&docker).is_empty());
+    assert!(client.diagnostics(&root).is_empty());
+}
+
+#[test]
+fn test_lint_severities_from_initialization_options() {
+    let mut client = TestClient::with_params(InitializeParams {
+        initialization_options: Some(json!({
+            "lint": { "JK001": "warning", "undefined-variable": "off", "JK003": "loud" },
+        })),
+        ..InitializeParams::default()
+    });
+    let uri = uri("severities/Jakefile");
+    client.open(
+        &uri,
+        "VERSION = \"1.0\"\n\ntask build: [clena]\n    echo {{VERSON}}\n",
+    );
+
+    let diagnostics = client.diagnostics(&uri);
+    assert_eq!(codes(&diagnostics), ["JK003", "JK001"]);
+    assert_eq!(diagnostics[1].severity, Some(DiagnosticSeverity::WARNING));
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::{position_of, position_params, TestClient};
+use lsp_types::request::{DocumentLinkRequest, HoverRequest};
+use lsp_types::{
+    DiagnosticSeverity, DocumentLink, DocumentLinkParams, HoverContents, HoverParams, Position,
+    Range, TextDocumentIdentifier, Url,
+};
+
+const SOURCE: &str = "\
+@import \"lib.jake\"
+@dotenv \".env.local\"
+@dotenv \".env.missing\"
+
+task build:
+    @cd web
+    @cache src/*.rs Makefile
+    @watch \"docs/*.md\" web/*.html
+    cargo build
+";
+
+/// A project on disk for [`SOURCE`], with `docs/` left empty.
+fn project(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join("jake-language-server-links")
+        .join(name);
+    for subdir in ["src", "web", "docs"] {
+        std::fs::create_dir_all(dir.join(subdir)).unwrap();
+    }
+    for file in [
+        "lib.jake",
+        ".env.local",
+        "Makefile",
+        "src/main.rs",
+        "src/lib.rs",
+        "web/index.html",
+    ] {
+        std::fs::write(dir.join(file), "").unwrap();
+    }
+    std::fs::write(dir.join("Jakefile"), SOURCE).unwrap();
+    std::fs::canonicalize(dir).unwrap()
+}
+
+fn links(client: &mut TestClient, uri: &Url) -> Vec<DocumentLink> {
+    client
+        .request::<DocumentLinkRequest>(DocumentLinkParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_document_links() {
+    let dir = project("project");
+    let uri = Url::from_file_path(dir.join("Jakefile")).unwrap();
+    let mut client = TestClient::new();
+    client.open(&uri, SOURCE);
+    let links = links(&mut client, &uri);
+
+    let targets: Vec<_> = links
+        .iter()
+        .map(|link| {
+            let path = link.target.as_ref().unwrap().to_file_path().unwrap();
+            let path = path.strip_prefix(&dir).unwrap().display().to_string();
+            (link.range.start, path)
+        })
+        .collect();
+    assert_eq!(
+        targets,
+        [
+            (
+                position_of(SOURCE, "\"lib.jake\"", 0),
+                "lib.jake".to_string()
+            ),
+            (
+                position_of(SOURCE, "\".env.local\"", 0),
+                ".env.local".to_string()
+            ),
+            (position_of(SOURCE, "web\n", 0), "web".to_string()),
+            (position_of(SOURCE, "Makefile", 0), "Makefile".to_string()),
+            (
+                position_of(SOURCE, "web/*.html", 0),
+                "web/index.html".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_missing_paths_are_warnings() {
+    let dir = project("project");
+    let uri = Url::from_file_path(dir.join("Jakefile")).unwrap();
+    let mut client = TestClient::new();
+    client.open(&uri, SOURCE);
+    let warnings: Vec<_> = client
+        .diagnostics(&uri)
+        .into_iter()
+        .filter(|diagnostic| {
+            // Lint results carry a code; path warnings do not.
+            diagnostic.severity == Some(DiagnosticSeverity::WARNING) && diagnostic.code.is_none()
+        })
+        .map(|diagnostic| (diagnostic.range.start, diagnostic.message))
+        .collect();
+    assert_eq!(
+        warnings,
+        [
+            (
+                position_of(SOURCE, "\".env.missing\"", 0),
+                "'.env.missing' does not exist".to_string()
+            ),
+            (
+                position_of(SOURCE, "\"docs/*.md\"", 0),
+                "'docs/*.md' matches no files".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_glob_hover_lists_matches() {
+    let dir = project("project");
+    let uri = Url::from_file_path(dir.join("Jakefile")).unwrap();
+    let mut client = TestClient::new();
+    client.open(&uri, SOURCE);
+    let hover = client
+        .request::<HoverRequest>(HoverParams {
+            text_document_position_params: position_params(
+                &uri,
+                position_of(SOURCE, "src/*.rs", 2),
+            ),
+            work_done_progress_params: Default::default(),
+        })
+        .unwrap();
+    let HoverContents::Markup(markup) = hover.contents else {
+        panic!("expected markdown");
+    };
+    assert_eq!(
+        markup.value,
+        "`src/*.rs` matches 2 files:\n\n- `src/lib.rs`\n- `src/main.rs`"
+    );
+}
+
+#[test]
+fn test_paths_are_resolved_once_per_version() {
+    let dir = project("versions");
+    let missing = dir.join(".env.missing");
+    let _ = std::fs::remove_file(&missing);
+    let uri = Url::from_file_path(dir.join("Jakefile")).unwrap();
+    let mut client = TestClient::new();
+    client.open(&uri, SOURCE);
+    let linked = |links: &[DocumentLink]| {
+        let start = position_of(SOURCE, "\".env.missing\"", 0);
+        links.iter().any(|link| link.range.start == start)
+    };
+    assert!(!linked(&links(&mut client, &uri)));
+
+    // The same version keeps what the path resolved to.
+    std::fs::write(&missing, "").unwrap();
+    assert!(!linked(&links(&mut client, &uri)));
+
+    let end = Position::new(SOURCE.lines().count() as u32, 0);
+    client.change(&uri, 2, Range::new(end, end), "# done\n");
+    assert!(linked(&links(&mut client, &uri)));
+}
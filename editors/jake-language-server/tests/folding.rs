@@ -0,0 +1,118 @@
+mod common;
+
+use common::{position_of, uri, TestClient};
+use lsp_types::request::{FoldingRangeRequest, SelectionRangeRequest};
+use lsp_types::{
+    FoldingRangeKind, FoldingRangeParams, Position, SelectionRangeParams, TextDocumentIdentifier,
+};
+
+const SOURCE: &str = "\
+# Build tasks for the app.
+# Run `jake build` first.
+version = \"1.0\"
+
+@group build
+task build:
+    @if env(CI)
+        make ci
+    @else
+        make
+    @end
+
+@group build
+task release: [build]
+    @each linux macos
+        echo {{item}} {{version}}
+    @end
+
+task clean:
+    rm -rf dist
+";
+
+#[test]
+fn test_folding_ranges() {
+    let mut client = TestClient::new();
+    let uri = uri("folding/Jakefile");
+    client.open(&uri, SOURCE);
+    let folds = client
+        .request::<FoldingRangeRequest>(FoldingRangeParams {
+            text_document: TextDocumentIdentifier::new(uri),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .unwrap();
+    let folds: Vec<_> = folds
+        .into_iter()
+        .map(|fold| (fold.start_line, fold.end_line, fold.kind.unwrap()))
+        .collect();
+    assert_eq!(
+        folds,
+        [
+            (0, 1, FoldingRangeKind::Comment),
+            // The group, from the first `@group` to the end of `release`.
+            (4, 16, FoldingRangeKind::Region),
+            (5, 10, FoldingRangeKind::Region),
+            (6, 7, FoldingRangeKind::Region),
+            (8, 9, FoldingRangeKind::Region),
+            (13, 16, FoldingRangeKind::Region),
+            (14, 15, FoldingRangeKind::Region),
+            (18, 19, FoldingRangeKind::Region),
+        ]
+    );
+}
+
+#[test]
+fn test_selection_ranges() {
+    let mut client = TestClient::new();
+    let uri = uri("folding/Jakefile");
+    client.open(&uri, SOURCE);
+    let position = position_of(SOURCE, "{{version}}", 3);
+    let mut ranges = client
+        .request::<SelectionRangeRequest>(SelectionRangeParams {
+            text_document: TextDocumentIdentifier::new(uri),
+            positions: vec![position],
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .unwrap();
+    assert_eq!(ranges.len(), 1);
+
+    let mut selections = Vec::new();
+    let mut range = Some(Box::new(ranges.remove(0)));
+    while let Some(selection) = range {
+        let start = selection.range.start;
+        let end = selection.range.end;
+        selections.push(text(start, end));
+        range = selection.parent;
+    }
+    // The interpolation, its command line, the body, the recipe with its
+    // attributes, the `build` group and the file, with any nodes in between.
+    let expected = [
+        "{{version}}",
+        "echo {{item}} {{version}}",
+        "@each linux macos\n        echo {{item}} {{version}}\n    @end",
+        "@group build\ntask release: [build]\n    @each linux macos\n        echo {{item}} {{version}}\n    @end",
+        &SOURCE[SOURCE.find("@group").unwrap()..SOURCE.find("\n\ntask clean").unwrap()],
+        SOURCE.trim_end(),
+    ];
+    let mut selections = selections.iter().map(|selection| selection.trim_start());
+    for expected in expected {
+        assert!(
+            selections.any(|selection| selection == expected),
+            "no selection of {expected:?}"
+        );
+    }
+}
+
+/// The text of `SOURCE` between two positions.
+fn text(start: Position, end: Position) -> String {
+    let offset = |position: Position| {
+        let line: usize = SOURCE
+            .split_inclusive('\n')
+            .take(position.line as usize)
+            .map(str::len)
+            .sum();
+        line + position.character as usize
+    };
+    SOURCE[offset(start)..offset(end)].to_string()
+}
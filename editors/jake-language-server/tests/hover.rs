@@ -0,0 +1,150 @@
+mod common;
+
+use common::{position_of, position_params, uri, TestClient};
+use lsp_types::request::HoverRequest;
+use lsp_types::{HoverContents, HoverParams, Position, Url};
+
+const SOURCE: &str = "\
+VERSION = \"1.0\"
+
+@desc \"Build the app\"
+@alias b
+task build env=\"dev\" +flags: [clean]
+    echo {{VERSION}} {{env}}
+
+task clean:
+    rm -rf dist
+
+task release: [b]
+    echo {{env}}
+";
+
+fn hover_at(client: &mut TestClient, uri: &Url, position: Position) -> Option<String> {
+    let hover = client.request::<HoverRequest>(HoverParams {
+        text_document_position_params: position_params(uri, position),
+        work_done_progress_params: Default::default(),
+    })?;
+    match hover.contents {
+        HoverContents::Markup(markup) => Some(markup.value),
+        contents => panic!("expected markup, got {contents:?}"),
+    }
+}
+
+fn hover(client: &mut TestClient, position: Position) -> Option<String> {
+    hover_at(client, &uri("hover/Jakefile"), position)
+}
+
+#[test]
+fn test_hover() {
+    let mut client = TestClient::new();
+    client.open(&uri("hover/Jakefile"), SOURCE);
+
+    let build =
+        "```jake\ntask build env=\"dev\" +flags\n```\n\nBuild the app\n\nType: task  \nAliases: `b`";
+    // The header name and a dependency on its alias.
+    let at = position_of(SOURCE, "build env", 2);
+    assert_eq!(hover(&mut client, at).as_deref(), Some(build));
+    let at = position_of(SOURCE, "[b]", 1);
+    assert_eq!(hover(&mut client, at).as_deref(), Some(build));
+
+    let at = position_of(SOURCE, "clean]", 0);
+    assert_eq!(
+        hover(&mut client, at).as_deref(),
+        Some("```jake\ntask clean\n```\n\nType: task")
+    );
+
+    let at = position_of(SOURCE, "{{VERSION", 4);
+    assert_eq!(
+        hover(&mut client, at).as_deref(),
+        Some("```jake\nVERSION = \"1.0\"\n```")
+    );
+
+    let at = position_of(SOURCE, "{{env", 2);
+    assert_eq!(
+        hover(&mut client, at).as_deref(),
+        Some("```jake\nenv=\"dev\"\n```\n\nParameter of `build`")
+    );
+
+    // `env` is not a parameter of `release`, and commands have no hover.
+    let at = position_of(SOURCE, "echo {{env", 7);
+    assert_eq!(hover(&mut client, at), None);
+    let at = position_of(SOURCE, "rm -rf", 1);
+    assert_eq!(hover(&mut client, at), None);
+}
+
+#[test]
+fn test_hover_recipe_metadata() {
+    let source = "\
+@import \"docker.jake\" as docker
+
+# Lint the code
+# before committing
+@group checks
+@platform linux macos
+task lint:
+    cargo clippy
+
+# Not a doc comment
+
+task test: [lint, docker:image]
+    cargo test
+";
+    let docker = "@desc \"Build the image\"\ntask image:\n    docker build .\n";
+    let mut client = TestClient::new();
+    let root = uri("hover-metadata/Jakefile");
+    client.open(&uri("hover-metadata/docker.jake"), docker);
+    client.open(&root, source);
+
+    let at = position_of(source, "lint, docker", 0);
+    assert_eq!(
+        hover_at(&mut client, &root, at).as_deref(),
+        Some(
+            "```jake\ntask lint\n```\n\nLint the code\nbefore committing\n\n\
+             Type: task  \nGroup: `checks`  \nPlatforms: `linux`, `macos`"
+        )
+    );
+
+    // A blank line separates the comment from `test`.
+    let at = position_of(source, "test:", 0);
+    assert_eq!(
+        hover_at(&mut client, &root, at).as_deref(),
+        Some("```jake\ntask test\n```\n\nType: task")
+    );
+
+    let at = position_of(source, "docker:image", 8);
+    assert_eq!(
+        hover_at(&mut client, &root, at).as_deref(),
+        Some(
+            "```jake\ntask image\n```\n\nBuild the image\n\n\
+             Type: task  \nDefined in `docker.jake`"
+        )
+    );
+}
+
+#[test]
+fn test_hover_builtins() {
+    let source = "\
+task build:
+    @cache src/*.rs
+    @if exists(Cargo.toml)
+        echo {{without_extensions(app.tar.gz)}}
+    @end
+";
+    let mut client = TestClient::new();
+    let root = uri("hover-builtins/Jakefile");
+    client.open(&root, source);
+
+    let at = position_of(source, "@cache", 2);
+    let cache = hover_at(&mut client, &root, at).unwrap();
+    assert!(cache.starts_with("```jake\n@cache\n```"), "{cache}");
+    assert!(cache.contains("Skip commands if input files haven't changed"));
+
+    let at = position_of(source, "without_extensions", 3);
+    let function = hover_at(&mut client, &root, at).unwrap();
+    assert!(function.starts_with("```jake\nwithout_extensions(p)\n```"));
+    assert!(function.contains("Remove ALL extensions"));
+
+    let at = position_of(source, "exists", 1);
+    let condition = hover_at(&mut client, &root, at).unwrap();
+    assert!(condition.starts_with("```jake\nexists(path)\n```"));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{position_of, uri, TestClient};
+use lsp_types::{Diagnostic, Position, Range};
+
+/// The longest a keystroke may take on average, from the change
+/// notification to its diagnostics, in a release build.
+const BUDGET: Duration = Duration::from_millis(30);
+
+/// A Jakefile of a few thousand lines that imports `docker.jake`.
+fn large_jakefile() -> String {
+    let mut source = String::from("VERSION = \"1.0\"\n@import \"docker.jake\" as docker\n");
+    for i in 0..400 {
+        source.push_str(&format!(
+            "\n# Step {i} of the pipeline.\n@group stage{}\n",
+            i % 10
+        ));
+        match i {
+            0 => source.push_str("task step0: [docker:build]\n"),
+            _ => source.push_str(&format!("task step{i}: [step{}]\n", i - 1)),
+        }
+        source.push_str(&format!(
+            "    @if env(CI)\n        echo {{{{VERSION}}}} step{i}\n    @else\n        make step{i}\n    @end\n"
+        ));
+    }
+    source
+}
+
+fn docker_jakefile() -> String {
+    let mut source = String::new();
+    for i in 0..50 {
+        source.push_str(&format!("task image{i}:\n    docker build -t app{i} .\n\n"));
+    }
+    source.push_str("task build: [image0]\n    docker build .\n");
+    source
+}
+
+/// Type a new line into a recipe in the middle of the large Jakefile, one
+/// character at a time the way an editor sends it. Returns the time each
+/// keystroke took until its diagnostics arrived, the last diagnostics and
+/// the text they are for.
+fn type_edit_trace(client: &mut TestClient) -> (Vec<Duration>, Vec<Diagnostic>, String) {
+    let root = uri("incremental/Jakefile");
+    let docker = uri("incremental/docker.jake");
+    client.open(&docker, &docker_jakefile());
+    client.diagnostics(&docker);
+    let source = large_jakefile();
+    assert!(source.lines().count() > 3000);
+    client.open(&root, &source);
+    assert!(client.diagnostics(&root).is_empty());
+
+    let typed = "\n        echo {{VERSON}} done";
+    let mut text = source.clone();
+    let mut offset = text.find("make step200").unwrap() + "make step200".len();
+    let mut position = position_of(&source, "make step200", "make step200".len());
+    let mut latencies = Vec::new();
+    let mut edited = Vec::new();
+    for (version, character) in (2..).zip(typed.chars()) {
+        let started = Instant::now();
+        client.change(
+            &root,
+            version,
+            Range::new(position, position),
+            &character.to_string(),
+        );
+        let published = client.published_diagnostics(&root);
+        latencies.push(started.elapsed());
+        assert_eq!(published.version, Some(version));
+        edited = published.diagnostics;
+
+        text.insert(offset, character);
+        offset += character.len_utf8();
+        position = match character {
+            '\n' => Position::new(position.line + 1, 0),
+            _ => Position::new(position.line, position.character + 1),
+        };
+    }
+    (latencies, edited, text)
+}
+
+#[test]
+fn test_edit_trace() {
+    let mut client = TestClient::new();
+    let (_, edited, text) = type_edit_trace(&mut client);
+
+    // The edits end up with the same diagnostics as opening the result.
+    let fresh = uri("incremental/Fresh.jake");
+    client.open(&fresh, &text);
+    let expected = client.diagnostics(&fresh);
+    assert_eq!(edited, expected);
+    assert_eq!(edited.len(), 1);
+    assert_eq!(edited[0].message, "Undefined variable 'VERSON'");
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    ignore = "timings are only meaningful in release builds"
+)]
+fn test_edit_trace_latency() {
+    let mut client = TestClient::new();
+    let (latencies, _, _) = type_edit_trace(&mut client);
+    let average = latencies.iter().sum::<Duration>() / latencies.len() as u32;
+    assert!(
+        average < BUDGET,
+        "a keystroke took {average:?} on average, over the budget of {BUDGET:?}"
+    );
+}
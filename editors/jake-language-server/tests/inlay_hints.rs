@@ -0,0 +1,99 @@
+mod common;
+
+use common::{uri, TestClient};
+use jake_language_server::LineIndex;
+use lsp_types::request::InlayHintRequest;
+use lsp_types::{
+    InlayHintLabel, InlayHintParams, InlayHintTooltip, Position, Range, TextDocumentIdentifier,
+};
+
+const SOURCE: &str = "\
+@require API_KEY
+@import \"paths.jake\"
+
+VERSION = \"1.0\"
+NAME = \"app-\" + VERSION
+OUT = \"dist\" / NAME
+CHANNEL = if VERSION == \"1.0\" { \"stable\" } else { \"beta\" }
+SHA = `git rev-parse HEAD`
+
+task release file=\"src/main.rs\" target:
+    echo {{NAME}} {{OUT}} {{CHANNEL}} {{BIN}}
+    echo {{basename(file)}} {{uppercase(NAME)}} {{home()}}
+    echo {{API_KEY}} {{SHA}} {{file}} {{target}} {{\"literal\"}}
+";
+
+/// Each hint as the interpolation it follows, its label and its tooltip.
+fn inlay_hints(range: Range) -> Vec<(String, String, Option<String>)> {
+    let mut client = TestClient::new();
+    client.open(&uri("inlay/paths.jake"), "BIN = \"bin\" / \"jake\"\n");
+    client.open(&uri("inlay/Jakefile"), SOURCE);
+    let hints = client
+        .request::<InlayHintRequest>(InlayHintParams {
+            text_document: TextDocumentIdentifier::new(uri("inlay/Jakefile")),
+            range,
+            work_done_progress_params: Default::default(),
+        })
+        .unwrap();
+    let index = LineIndex::new(SOURCE);
+    hints
+        .into_iter()
+        .map(|hint| {
+            let end = index.offset(SOURCE, hint.position);
+            let start = SOURCE[..end].rfind("{{").unwrap();
+            let InlayHintLabel::String(label) = hint.label else {
+                panic!("unexpected label parts");
+            };
+            let tooltip = hint.tooltip.map(|tooltip| match tooltip {
+                InlayHintTooltip::String(tooltip) => tooltip,
+                InlayHintTooltip::MarkupContent(markup) => markup.value,
+            });
+            (SOURCE[start..end].to_string(), label, tooltip)
+        })
+        .collect()
+}
+
+fn labels(hints: &[(String, String, Option<String>)]) -> Vec<(&str, &str)> {
+    hints
+        .iter()
+        .map(|(interpolation, label, _)| (interpolation.as_str(), label.as_str()))
+        .collect()
+}
+
+#[test]
+fn test_inlay_hints() {
+    let hints = inlay_hints(Range::new(Position::new(0, 0), Position::new(20, 0)));
+    assert_eq!(
+        labels(&hints),
+        [
+            ("{{NAME}}", "= \"app-1.0\""),
+            ("{{OUT}}", "= \"dist/app-1.0\""),
+            ("{{CHANNEL}}", "= \"stable\""),
+            ("{{BIN}}", "= \"bin/jake\""),
+            ("{{basename(file)}}", "= \"main.rs\""),
+            ("{{uppercase(NAME)}}", "= \"APP-1.0\""),
+            ("{{home()}}", "= <env>"),
+            ("{{API_KEY}}", "= <env>"),
+            ("{{SHA}}", "= <shell>"),
+            ("{{file}}", "= \"src/main.rs\""),
+        ]
+    );
+    let file = hints.iter().find(|hint| hint.0 == "{{file}}").unwrap();
+    assert_eq!(file.2.as_deref(), Some("Default of parameter 'file'"));
+}
+
+#[test]
+fn test_inlay_hints_in_range() {
+    let line = SOURCE
+        .lines()
+        .position(|line| line.contains("{{home()}}"))
+        .unwrap() as u32;
+    let hints = inlay_hints(Range::new(Position::new(line, 0), Position::new(line, 30)));
+    assert_eq!(
+        labels(&hints),
+        [
+            ("{{basename(file)}}", "= \"main.rs\""),
+            ("{{uppercase(NAME)}}", "= \"APP-1.0\""),
+        ]
+    );
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{uri, TestClient};
+use lsp_server::ErrorCode;
+use lsp_types::{Position, Range, TextDocumentSyncCapability, TextDocumentSyncKind};
+
+#[test]
+fn test_initialize_and_shutdown() {
+    let client = TestClient::new();
+    let result = &client.initialize_result;
+    assert_eq!(
+        result.capabilities.text_document_sync,
+        Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL
+        ))
+    );
+    assert!(result.capabilities.hover_provider.is_some());
+    assert!(result.capabilities.document_symbol_provider.is_some());
+    assert_eq!(
+        result.server_info.as_ref().unwrap().name,
+        "jake-language-server"
+    );
+    client.shutdown();
+}
+
+#[test]
+fn test_unknown_request() {
+    let mut client = TestClient::new();
+    let response = client.request_raw("jake/unknown", serde_json::Value::Null);
+    assert_eq!(
+        response.error.unwrap().code,
+        ErrorCode::MethodNotFound as i32
+    );
+}
+
+#[test]
+fn test_incremental_sync() {
+    let mut client = TestClient::new();
+    let uri = uri("sync/Jakefile");
+    client.open(&uri, "task build: [test]\n    make\n");
+    assert_eq!(client.diagnostics(&uri).len(), 1);
+
+    // Insert the missing recipe, then edit inside it.
+    client.change(
+        &uri,
+        2,
+        Range::new(Position::new(2, 0), Position::new(2, 0)),
+        "\ntask tset:\n    cargo test\n",
+    );
+    assert_eq!(client.diagnostics(&uri).len(), 1);
+    client.change(
+        &uri,
+        3,
+        Range::new(Position::new(3, 5), Position::new(3, 9)),
+        "test",
+    );
+    let published = client.published_diagnostics(&uri);
+    assert_eq!(published.version, Some(3));
+    assert!(published.diagnostics.is_empty());
+
+    client.close(&uri);
+    assert!(client.diagnostics(&uri).is_empty());
+}
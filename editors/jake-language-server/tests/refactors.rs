@@ -0,0 +1,236 @@
+mod common;
+
+use common::{apply, position_of, uri, TestClient};
+use lsp_types::request::CodeActionRequest;
+use lsp_types::{
+    CodeAction, CodeActionContext, CodeActionKind, CodeActionOrCommand, CodeActionParams, Range,
+    TextDocumentIdentifier, Url,
+};
+
+/// The refactors for `range` of the document at `uri`.
+fn refactors(client: &mut TestClient, uri: &Url, range: Range) -> Vec<CodeAction> {
+    let response = client.request::<CodeActionRequest>(CodeActionParams {
+        text_document: TextDocumentIdentifier::new(uri.clone()),
+        range,
+        context: CodeActionContext {
+            only: Some(vec![CodeActionKind::REFACTOR]),
+            ..CodeActionContext::default()
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    });
+    response
+        .unwrap_or_default()
+        .into_iter()
+        .map(|action| match action {
+            CodeActionOrCommand::CodeAction(action) => action,
+            CodeActionOrCommand::Command(command) => panic!("unexpected command {command:?}"),
+        })
+        .collect()
+}
+
+/// The refactors with the cursor at the start of `needle`.
+fn refactors_at(client: &mut TestClient, uri: &Url, source: &str, needle: &str) -> Vec<CodeAction> {
+    let position = position_of(source, needle, 0);
+    refactors(client, uri, Range::new(position, position))
+}
+
+fn find<'a>(actions: &'a [CodeAction], title: &str) -> &'a CodeAction {
+    actions
+        .iter()
+        .find(|action| action.title == title)
+        .unwrap_or_else(|| {
+            let titles: Vec<_> = actions.iter().map(|action| &action.title).collect();
+            panic!("no action {title:?} in {titles:?}")
+        })
+}
+
+/// `source` of the document at `uri` after applying the action titled `title`.
+fn apply_action(actions: &[CodeAction], title: &str, uri: &Url, source: &str) -> String {
+    let action = find(actions, title);
+    assert_eq!(action.disabled, None, "{title} is disabled");
+    let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+    apply(source, &changes[uri])
+}
+
+fn disabled_reason<'a>(actions: &'a [CodeAction], title: &str) -> &'a str {
+    let action = find(actions, title);
+    assert_eq!(action.edit, None);
+    &action.disabled.as_ref().expect("action is enabled").reason
+}
+
+#[test]
+fn test_extract_recipe() {
+    let mut client = TestClient::new();
+    let uri = uri("refactor-extract/Jakefile");
+    let source = "\
+task build: [lint]
+    # compile and test
+    cargo build
+    cargo test
+    echo done
+
+task deploy env:
+    echo {{env}}
+";
+    client.open(&uri, source);
+
+    let start = position_of(source, "cargo build", 2);
+    let end = position_of(source, "cargo test", 4);
+    let actions = refactors(&mut client, &uri, Range::new(start, end));
+    assert_eq!(
+        apply_action(&actions, "Extract lines into a new task", &uri, source),
+        "\
+task build-part:
+    cargo build
+    cargo test
+
+task build: [lint, build-part]
+    # compile and test
+    echo done
+
+task deploy env:
+    echo {{env}}
+"
+    );
+
+    let start = position_of(source, "echo {{env", 0);
+    let end = position_of(source, "echo {{env", 5);
+    let actions = refactors(&mut client, &uri, Range::new(start, end));
+    assert_eq!(
+        disabled_reason(&actions, "Extract lines into a new task"),
+        "the lines use the parameter 'env'"
+    );
+}
+
+#[test]
+fn test_inline_variable() {
+    let mut client = TestClient::new();
+    let uri = uri("refactor-inline/Jakefile");
+    let source = "\
+VERSION = \"1.0\"
+NAME = \"app-\" + VERSION
+
+task release:
+    # package
+    echo {{VERSION}}
+    tar czf {{NAME}}-{{VERSION}}.tgz dist
+";
+    client.open(&uri, source);
+
+    let actions = refactors_at(&mut client, &uri, source, "VERSION");
+    assert_eq!(
+        apply_action(&actions, "Inline variable 'VERSION'", &uri, source),
+        "\
+NAME = \"app-\" + \"1.0\"
+
+task release:
+    # package
+    echo 1.0
+    tar czf {{NAME}}-1.0.tgz dist
+"
+    );
+}
+
+#[test]
+fn test_convert_recipe_kind() {
+    let mut client = TestClient::new();
+    let uri = uri("refactor-convert/Jakefile");
+    let source = "\
+build: [lint]
+    make
+
+lint:
+    cargo clippy
+
+task bundle: [dist/app.js]
+    esbuild
+
+file dist/app.js: src/*.ts
+    tsc
+";
+    client.open(&uri, source);
+
+    let actions = refactors_at(&mut client, &uri, source, "build:");
+    assert_eq!(
+        apply_action(&actions, "Convert to task recipe", &uri, source),
+        source.replace("build: [lint]", "task build: [lint]")
+    );
+    assert_eq!(
+        disabled_reason(&actions, "Convert to file recipe"),
+        "'lint' is not a file, so it would no longer run first"
+    );
+
+    let actions = refactors_at(&mut client, &uri, source, "bundle");
+    assert_eq!(
+        apply_action(&actions, "Convert to file recipe", &uri, source),
+        source.replace("task bundle", "file bundle")
+    );
+}
+
+#[test]
+fn test_sort_dependencies() {
+    let mut client = TestClient::new();
+    let uri = uri("refactor-sort/Jakefile");
+    let source = "\
+task all: [test, build,lint]
+    echo done
+
+task build:
+    make
+";
+    client.open(&uri, source);
+
+    let actions = refactors_at(&mut client, &uri, source, "all");
+    assert_eq!(
+        apply_action(&actions, "Sort dependencies", &uri, source),
+        source.replace("[test, build,lint]", "[build, lint,test]")
+    );
+
+    // Already sorted.
+    let actions = refactors_at(&mut client, &uri, source, "build:");
+    assert!(actions
+        .iter()
+        .all(|action| action.title != "Sort dependencies"));
+}
+
+#[test]
+fn test_move_recipe() {
+    let mut client = TestClient::new();
+    let uri_root = uri("refactor-move/Jakefile");
+    let uri_docker = uri("refactor-move/docker.jake");
+    let docker = "task push:\n    docker push\n";
+    let source = "\
+@import \"docker.jake\" as docker
+
+task image: [docker:push]
+    docker build .
+
+task release: [image]
+    make release
+";
+    client.open(&uri_docker, docker);
+    client.open(&uri_root, source);
+
+    let actions = refactors_at(&mut client, &uri_root, source, "image:");
+    let title = "Move 'image' to docker.jake";
+    assert_eq!(
+        apply_action(&actions, title, &uri_docker, docker),
+        "task push:\n    docker push\n\ntask image: [push]\n    docker build .\n"
+    );
+    assert_eq!(
+        apply_action(&actions, title, &uri_root, source),
+        "\
+@import \"docker.jake\" as docker
+
+task release: [docker:image]
+    make release
+"
+    );
+
+    let actions = refactors_at(&mut client, &uri_root, source, "release");
+    assert_eq!(
+        disabled_reason(&actions, "Move 'release' to docker.jake"),
+        "'release' depends on 'image', outside docker.jake"
+    );
+}
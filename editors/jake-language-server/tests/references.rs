@@ -0,0 +1,216 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::{apply, position_of, position_params, uri, TestClient};
+use lsp_server::ErrorCode;
+use lsp_types::request::{PrepareRenameRequest, References, Rename, Request as _};
+use lsp_types::{
+    Location, Position, PrepareRenameResponse, Range, ReferenceContext, ReferenceParams,
+    RenameParams, TextEdit, Url, WorkspaceEdit,
+};
+
+const SOURCE: &str = "\
+@import \"docker.jake\" as docker
+
+version = \"1.0\"
+
+@before build echo \"starting\"
+@after docker.push echo \"pushed\"
+
+@alias b
+task build env=\"dev\": [docker:build]
+    echo {{version}} {{env}}
+
+task deploy: [b, build]
+    echo {{env}} {{version}}
+";
+
+const DOCKER: &str = "\
+task build:
+    docker build .
+
+task push: [build]
+    docker push
+";
+
+fn open(client: &mut TestClient) -> (Url, Url) {
+    let root = uri("references/Jakefile");
+    let docker = uri("references/docker.jake");
+    client.open(&docker, DOCKER);
+    client.open(&root, SOURCE);
+    (root, docker)
+}
+
+fn references(client: &mut TestClient, uri: &Url, position: Position) -> Vec<Location> {
+    client
+        .request::<References>(ReferenceParams {
+            text_document_position: position_params(uri, position),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: ReferenceContext {
+                include_declaration: true,
+            },
+        })
+        .unwrap_or_default()
+}
+
+fn rename_params(uri: &Url, position: Position, new_name: &str) -> RenameParams {
+    RenameParams {
+        text_document_position: position_params(uri, position),
+        new_name: new_name.to_string(),
+        work_done_progress_params: Default::default(),
+    }
+}
+
+fn edits(edit: WorkspaceEdit) -> HashMap<Url, Vec<TextEdit>> {
+    edit.changes.expect("rename returns plain changes")
+}
+
+#[test]
+fn test_references() {
+    let mut client = TestClient::new();
+    let (root, docker) = open(&mut client);
+
+    // `build` in the root file, by the header name, a hook and a dependency.
+    let ranges = |locations: Vec<Location>| -> Vec<(Url, Range)> {
+        locations
+            .into_iter()
+            .map(|location| (location.uri, location.range))
+            .collect()
+    };
+    let expected = ranges(references(
+        &mut client,
+        &root,
+        position_of(SOURCE, "build env", 0),
+    ));
+    assert_eq!(expected.len(), 3);
+    assert_eq!(expected[0].1.start, position_of(SOURCE, "build env", 0));
+    for at in [
+        position_of(SOURCE, "build echo", 0),
+        position_of(SOURCE, "build]\n    echo {{env", 0),
+    ] {
+        assert_eq!(ranges(references(&mut client, &root, at)), expected);
+    }
+
+    // `docker:build` finds the definition and the dependency inside the
+    // imported file, where it is written without the namespace.
+    let at = position_of(SOURCE, "docker:build", 8);
+    let locations = references(&mut client, &root, at);
+    assert_eq!(
+        locations,
+        [
+            Location::new(
+                docker.clone(),
+                Range::new(Position::new(0, 5), Position::new(0, 10))
+            ),
+            Location::new(
+                root.clone(),
+                Range::new(
+                    position_of(SOURCE, "build]\n    echo {{version", 0),
+                    position_of(SOURCE, "]\n    echo {{version", 0)
+                )
+            ),
+            Location::new(
+                docker.clone(),
+                Range::new(Position::new(3, 12), Position::new(3, 17))
+            ),
+        ]
+    );
+
+    // The alias is its own symbol.
+    let at = position_of(SOURCE, "[b", 1);
+    let locations = references(&mut client, &root, at);
+    assert_eq!(locations.len(), 2);
+    assert_eq!(locations[0].range.start, position_of(SOURCE, "b\ntask", 0));
+}
+
+#[test]
+fn test_rename() {
+    let mut client = TestClient::new();
+    let (root, docker) = open(&mut client);
+
+    let at = position_of(SOURCE, "docker:build", 8);
+    let prepared = client.request::<PrepareRenameRequest>(position_params(&root, at));
+    assert_eq!(
+        prepared,
+        Some(PrepareRenameResponse::Range(Range::new(
+            position_of(SOURCE, "build]\n    echo {{version", 0),
+            position_of(SOURCE, "]\n    echo {{version", 0)
+        )))
+    );
+
+    let edit = client
+        .request::<Rename>(rename_params(&root, at, "image"))
+        .unwrap();
+    let changes = edits(edit);
+    assert_eq!(
+        apply(DOCKER, &changes[&docker]),
+        "task image:\n    docker build .\n\ntask push: [image]\n    docker push\n"
+    );
+    assert!(apply(SOURCE, &changes[&root]).contains("[docker:image]"));
+
+    // A namespaced hook target renames the recipe in the imported file and
+    // keeps its namespace.
+    let at = position_of(SOURCE, "docker.push", 8);
+    let prepared = client.request::<PrepareRenameRequest>(position_params(&root, at));
+    assert_eq!(
+        prepared,
+        Some(PrepareRenameResponse::Range(Range::new(
+            position_of(SOURCE, "push echo", 0),
+            position_of(SOURCE, " echo \"pushed", 0)
+        )))
+    );
+    let edit = client
+        .request::<Rename>(rename_params(&root, at, "publish"))
+        .unwrap();
+    let changes = edits(edit);
+    assert!(apply(DOCKER, &changes[&docker]).contains("task publish: [build]"));
+    assert!(apply(SOURCE, &changes[&root]).contains("@after docker.publish echo"));
+
+    // Parameters are renamed within their recipe; `deploy` reads the
+    // variable `env`, which does not exist, and is left alone.
+    let at = position_of(SOURCE, "{{env", 2);
+    let edit = client
+        .request::<Rename>(rename_params(&root, at, "target"))
+        .unwrap();
+    let text = apply(SOURCE, &edits(edit)[&root]);
+    assert!(text.contains("task build target=\"dev\": [docker:build]"));
+    assert!(text.contains("echo {{version}} {{target}}\n"));
+    assert!(text.contains("echo {{env}} {{version}}\n"));
+
+    let at = position_of(SOURCE, "version =", 0);
+    let edit = client
+        .request::<Rename>(rename_params(&root, at, "release"))
+        .unwrap();
+    let text = apply(SOURCE, &edits(edit)[&root]);
+    assert_eq!(text.matches("release").count(), 3);
+    assert!(!text.contains("version"));
+}
+
+#[test]
+fn test_rename_refuses_collisions() {
+    let mut client = TestClient::new();
+    let (root, _) = open(&mut client);
+
+    let mut refused = |at: Position, new_name: &str| {
+        let params = serde_json::to_value(rename_params(&root, at, new_name)).unwrap();
+        let response = client.request_raw(Rename::METHOD, params);
+        let error = response.error.expect("rename should be refused");
+        (error.code, error.message)
+    };
+
+    let (code, message) = refused(position_of(SOURCE, "build env", 0), "deploy");
+    assert_eq!(code, ErrorCode::RequestFailed as i32);
+    assert_eq!(message, "a recipe named `deploy` already exists");
+
+    // Renaming `version` to `env` would make `build` read its parameter.
+    let (_, message) = refused(position_of(SOURCE, "version =", 0), "env");
+    assert_eq!(message, "`env` would be shadowed by a parameter of `build`");
+
+    let (_, message) = refused(position_of(SOURCE, "{{env", 2), "version");
+    assert_eq!(message, "`build` already uses a variable named `version`");
+
+    let (code, _) = refused(position_of(SOURCE, "build env", 0), "not valid");
+    assert_eq!(code, ErrorCode::InvalidParams as i32);
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use common::{uri, TestClient};
+use lsp_types::request::{SemanticTokensFullRequest, SemanticTokensRangeRequest};
+use lsp_types::{
+    Position, Range, SemanticTokensParams, SemanticTokensRangeParams, SemanticTokensRangeResult,
+    SemanticTokensResult, SemanticTokensServerCapabilities, TextDocumentIdentifier,
+};
+
+const SOURCE: &str = "\
+@import \"docker.jake\" as docker
+
+version := \"1.0\"
+
+task build env=\"dev\": [docker:image, lint, missing]
+    @cache src/*.rs
+    echo {{version}} {{env}} {{$1}} {{uppercase(env)}} {{nope}}
+
+lint:
+    cargo clippy
+
+file dist/app.js: src/*.js
+    bundle
+";
+
+const DOCKER: &str = "task image:\n    docker build .\n";
+
+/// A decoded token: its text, type and modifiers.
+type Decoded = (String, String, Vec<String>);
+
+fn decode(client: &TestClient, data: &[lsp_types::SemanticToken]) -> Vec<Decoded> {
+    let Some(SemanticTokensServerCapabilities::SemanticTokensOptions(options)) = &client
+        .initialize_result
+        .capabilities
+        .semantic_tokens_provider
+    else {
+        panic!("semantic tokens are not advertised");
+    };
+    let legend = &options.legend;
+    let lines: Vec<&str> = SOURCE.lines().collect();
+    let mut position = Position::default();
+    data.iter()
+        .map(|token| {
+            if token.delta_line == 0 {
+                position.character += token.delta_start;
+            } else {
+                position.line += token.delta_line;
+                position.character = token.delta_start;
+            }
+            let start = position.character as usize;
+            let text = &lines[position.line as usize][start..start + token.length as usize];
+            let modifiers = legend
+                .token_modifiers
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| token.token_modifiers_bitset & (1 << bit) != 0)
+                .map(|(_, modifier)| modifier.as_str().to_string())
+                .collect();
+            (
+                text.to_string(),
+                legend.token_types[token.token_type as usize]
+                    .as_str()
+                    .to_string(),
+                modifiers,
+            )
+        })
+        .collect()
+}
+
+fn token(text: &str, kind: &str, modifiers: &[&str]) -> Decoded {
+    (
+        text.to_string(),
+        kind.to_string(),
+        modifiers
+            .iter()
+            .map(|modifier| modifier.to_string())
+            .collect(),
+    )
+}
+
+#[test]
+fn test_semantic_tokens() {
+    let mut client = TestClient::new();
+    let root = uri("semantic/Jakefile");
+    client.open(&uri("semantic/docker.jake"), DOCKER);
+    client.open(&root, SOURCE);
+
+    let result = client.request::<SemanticTokensFullRequest>(SemanticTokensParams {
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        text_document: TextDocumentIdentifier::new(root.clone()),
+    });
+    let Some(SemanticTokensResult::Tokens(tokens)) = result else {
+        panic!("expected tokens, got {result:?}");
+    };
+    assert_eq!(
+        decode(&client, &tokens.data),
+        [
+            token("@import", "keyword", &[]),
+            token("as", "keyword", &[]),
+            token("docker", "namespace", &["declaration"]),
+            token("version", "variable", &["declaration", "deprecated"]),
+            token("task", "keyword", &[]),
+            token("build", "taskRecipe", &["declaration"]),
+            token("env", "parameter", &["declaration"]),
+            token("docker", "namespace", &[]),
+            token("image", "taskRecipe", &[]),
+            token("lint", "simpleRecipe", &[]),
+            token("missing", "unresolved", &[]),
+            token("@cache", "keyword", &[]),
+            token("src/*.rs", "glob", &[]),
+            token("version", "variable", &[]),
+            token("env", "parameter", &[]),
+            token("$1", "shellVariable", &[]),
+            token("uppercase", "function", &["defaultLibrary"]),
+            token("env", "parameter", &[]),
+            token("nope", "unresolved", &[]),
+            token("lint", "simpleRecipe", &["declaration"]),
+            token("file", "keyword", &[]),
+            token("dist/app.js", "fileRecipe", &["declaration"]),
+            token("src/*.js", "glob", &[]),
+        ]
+    );
+
+    // Only the tokens on the `lint:` line.
+    let result = client.request::<SemanticTokensRangeRequest>(SemanticTokensRangeParams {
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+        text_document: TextDocumentIdentifier::new(root),
+        range: Range::new(Position::new(8, 0), Position::new(9, 0)),
+    });
+    let Some(SemanticTokensRangeResult::Tokens(tokens)) = result else {
+        panic!("expected tokens, got {result:?}");
+    };
+    let decoded = decode(&client, &tokens.data);
+    assert_eq!(decoded, [token("lint", "simpleRecipe", &["declaration"])]);
+}
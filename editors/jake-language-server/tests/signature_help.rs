@@ -0,0 +1,89 @@
+mod common;
+
+use common::{position_of, position_params, uri, TestClient};
+use lsp_types::request::SignatureHelpRequest;
+use lsp_types::{ParameterLabel, Position, SignatureHelp, SignatureHelpParams};
+
+const SOURCE: &str = "\
+@before deploy echo \"starting\"
+
+@desc \"Ship it\"
+task deploy env=\"staging\" +regions:
+    @if eq(env, prod)
+        echo {{dirname(env)}} {{home()}}
+    @end
+
+task release: [deploy]
+    echo done
+";
+
+fn signature_help(client: &mut TestClient, position: Position) -> Option<SignatureHelp> {
+    client.request::<SignatureHelpRequest>(SignatureHelpParams {
+        context: None,
+        text_document_position_params: position_params(&uri("signature/Jakefile"), position),
+        work_done_progress_params: Default::default(),
+    })
+}
+
+/// The label and the text of each parameter.
+fn signature(help: &SignatureHelp) -> (&str, Vec<&str>) {
+    let signature = &help.signatures[0];
+    let parameters = signature
+        .parameters
+        .iter()
+        .flatten()
+        .map(|parameter| match parameter.label {
+            ParameterLabel::LabelOffsets([start, end]) => {
+                &signature.label[start as usize..end as usize]
+            }
+            ParameterLabel::Simple(ref label) => label.as_str(),
+        })
+        .collect();
+    (&signature.label, parameters)
+}
+
+#[test]
+fn test_builtin_signature_help() {
+    let mut client = TestClient::new();
+    client.open(&uri("signature/Jakefile"), SOURCE);
+
+    let help = signature_help(&mut client, position_of(SOURCE, "dirname(", 8)).unwrap();
+    assert_eq!(signature(&help), ("dirname(p)", vec!["p"]));
+    assert_eq!(help.active_parameter, Some(0));
+
+    // The second argument of a condition.
+    let help = signature_help(&mut client, position_of(SOURCE, "prod)", 0)).unwrap();
+    assert_eq!(signature(&help), ("eq(a, b)", vec!["a", "b"]));
+    assert_eq!(help.active_parameter, Some(1));
+    let help = signature_help(&mut client, position_of(SOURCE, "env, prod", 1)).unwrap();
+    assert_eq!(help.active_parameter, Some(0));
+
+    let help = signature_help(&mut client, position_of(SOURCE, "home()", 5)).unwrap();
+    assert_eq!(signature(&help), ("home()", vec![]));
+
+    // Outside the parentheses and in plain commands.
+    assert!(signature_help(&mut client, position_of(SOURCE, "dirname(", 2)).is_none());
+    assert!(signature_help(&mut client, position_of(SOURCE, "echo done", 2)).is_none());
+}
+
+#[test]
+fn test_recipe_signature_help() {
+    let mut client = TestClient::new();
+    client.open(&uri("signature/Jakefile"), SOURCE);
+
+    let expected = (
+        "task deploy env=\"staging\" +regions",
+        vec!["env=\"staging\"", "+regions"],
+    );
+    for at in [
+        position_of(SOURCE, "deploy]", 2),
+        position_of(SOURCE, "deploy echo", 0),
+    ] {
+        let help = signature_help(&mut client, at).unwrap();
+        assert_eq!(signature(&help), expected);
+        assert_eq!(help.active_parameter, None);
+    }
+
+    // The recipe's own header is a definition, not a use.
+    assert!(signature_help(&mut client, position_of(SOURCE, "deploy env", 1)).is_none());
+}
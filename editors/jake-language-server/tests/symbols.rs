@@ -0,0 +1,229 @@
+mod common;
+
+use common::{uri, TestClient};
+use lsp_types::request::{DocumentSymbolRequest, WorkspaceSymbolRequest};
+use lsp_types::{
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, InitializeParams, Location,
+    OneOf, Position, Range, SymbolKind, TextDocumentIdentifier, Url, WorkspaceFolder,
+    WorkspaceSymbolParams, WorkspaceSymbolResponse,
+};
+
+fn document_symbols(client: &mut TestClient, uri: &Url) -> Vec<DocumentSymbol> {
+    let response = client.request::<DocumentSymbolRequest>(DocumentSymbolParams {
+        text_document: TextDocumentIdentifier::new(uri.clone()),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    });
+    match response {
+        Some(DocumentSymbolResponse::Nested(symbols)) => symbols,
+        response => panic!("expected nested symbols, got {response:?}"),
+    }
+}
+
+/// Names, kinds and details of `symbols`.
+fn summary(symbols: &[DocumentSymbol]) -> Vec<(&str, SymbolKind, Option<&str>)> {
+    symbols
+        .iter()
+        .map(|symbol| (symbol.name.as_str(), symbol.kind, symbol.detail.as_deref()))
+        .collect()
+}
+
+/// Names of the workspace symbols matching `query`, with the file name of
+/// their location.
+fn workspace_symbols(client: &mut TestClient, query: &str) -> Vec<(String, SymbolKind, String)> {
+    let response = client.request::<WorkspaceSymbolRequest>(WorkspaceSymbolParams {
+        partial_result_params: Default::default(),
+        work_done_progress_params: Default::default(),
+        query: query.to_string(),
+    });
+    // The response is untagged, and a `WorkspaceSymbol` with a full location
+    // reads back as `SymbolInformation`.
+    let symbols: Vec<(String, SymbolKind, Location)> = match response {
+        Some(WorkspaceSymbolResponse::Flat(symbols)) => symbols
+            .into_iter()
+            .map(|symbol| (symbol.name, symbol.kind, symbol.location))
+            .collect(),
+        Some(WorkspaceSymbolResponse::Nested(symbols)) => symbols
+            .into_iter()
+            .map(|symbol| {
+                let OneOf::Left(location) = symbol.location else {
+                    panic!("expected a location for {}", symbol.name);
+                };
+                (symbol.name, symbol.kind, location)
+            })
+            .collect(),
+        None => panic!("expected workspace symbols"),
+    };
+    symbols
+        .into_iter()
+        .map(|(name, kind, location)| {
+            let path = location.uri.to_file_path().unwrap();
+            let file = path.file_name().unwrap().to_string_lossy().into_owned();
+            (name, kind, file)
+        })
+        .collect()
+}
+
+#[test]
+fn test_document_symbols() {
+    let mut client = TestClient::new();
+    let uri = uri("symbols/Jakefile");
+    client.open(
+        &uri,
+        "VERSION = \"1.0\"\n\n@desc \"Build the app\"\ntask build:\n    make\n\nfile dist/app.js: src/*.ts\n    tsc\n",
+    );
+
+    let symbols = document_symbols(&mut client, &uri);
+    assert_eq!(
+        summary(&symbols),
+        [
+            ("VERSION", SymbolKind::VARIABLE, Some("\"1.0\"")),
+            ("build", SymbolKind::FUNCTION, Some("task · Build the app")),
+            ("dist/app.js", SymbolKind::FILE, Some("file")),
+        ]
+    );
+
+    // The range covers the attributes and body; the selection is the name.
+    assert_eq!(symbols[1].range.start, Position::new(2, 0));
+    assert_eq!(
+        symbols[1].selection_range,
+        Range::new(Position::new(3, 5), Position::new(3, 10))
+    );
+}
+
+#[test]
+fn test_document_symbol_hierarchy() {
+    let mut client = TestClient::new();
+    let uri = uri("symbols-hierarchy/Jakefile");
+    client.open(
+        &uri,
+        "\
+@import \"docker.jake\" as docker
+
+@group ci
+task test filter=\"\" *flags:
+    cargo test
+
+task release:
+    make release
+
+@group ci
+lint:
+    cargo clippy
+",
+    );
+
+    let symbols = document_symbols(&mut client, &uri);
+    assert_eq!(
+        summary(&symbols),
+        [
+            ("docker.jake", SymbolKind::MODULE, Some("as docker")),
+            ("ci", SymbolKind::NAMESPACE, None),
+            ("release", SymbolKind::FUNCTION, Some("task")),
+        ]
+    );
+
+    // The group spans its recipes, even with another recipe in between.
+    let group = &symbols[1];
+    assert_eq!(group.range.start, Position::new(2, 0));
+    assert_eq!(group.range.end, Position::new(12, 0));
+    let recipes = group.children.as_deref().unwrap();
+    assert_eq!(
+        summary(recipes),
+        [
+            ("test", SymbolKind::FUNCTION, Some("task")),
+            ("lint", SymbolKind::FUNCTION, Some("simple")),
+        ]
+    );
+    assert_eq!(
+        summary(recipes[0].children.as_deref().unwrap()),
+        [
+            ("filter", SymbolKind::VARIABLE, Some("\"\"")),
+            ("flags", SymbolKind::VARIABLE, Some("variadic")),
+        ]
+    );
+    assert_eq!(recipes[1].children, None);
+}
+
+#[test]
+fn test_workspace_symbols_of_open_documents() {
+    let mut client = TestClient::new();
+    client.open(
+        &uri("workspace-symbols/docker.jake"),
+        "REGISTRY = \"ghcr.io\"\n\ntask build:\n    docker build .\n",
+    );
+    client.open(
+        &uri("workspace-symbols/Jakefile"),
+        "@import \"docker.jake\" as docker\n\ntask build: [docker:build]\n    make\n\nfile dist/app.js: src/*.ts\n    tsc\n",
+    );
+
+    // The imported `build` is listed once, under its namespaced name.
+    assert_eq!(
+        workspace_symbols(&mut client, "build"),
+        [
+            (
+                "build".to_string(),
+                SymbolKind::FUNCTION,
+                "Jakefile".to_string()
+            ),
+            (
+                "docker:build".to_string(),
+                SymbolKind::FUNCTION,
+                "docker.jake".to_string()
+            ),
+        ]
+    );
+    assert_eq!(
+        workspace_symbols(&mut client, "dstapp"),
+        [(
+            "dist/app.js".to_string(),
+            SymbolKind::FILE,
+            "Jakefile".to_string()
+        )]
+    );
+    assert_eq!(
+        workspace_symbols(&mut client, "registry"),
+        [(
+            "REGISTRY".to_string(),
+            SymbolKind::VARIABLE,
+            "docker.jake".to_string()
+        )]
+    );
+}
+
+#[test]
+fn test_workspace_symbols_of_workspace_folders() {
+    let root = std::env::temp_dir().join(format!(
+        "jake-language-server-workspace-symbols-{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(root.join("scripts")).unwrap();
+    std::fs::write(
+        root.join("Jakefile"),
+        "@import \"scripts/deploy.jake\" as deploy\n\ntask build:\n    make\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("scripts/deploy.jake"),
+        "task staging:\n    ./deploy.sh\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("tools.jake"), "task format:\n    cargo fmt\n").unwrap();
+
+    #[allow(deprecated)]
+    let mut client = TestClient::with_params(InitializeParams {
+        workspace_folders: Some(vec![WorkspaceFolder {
+            uri: Url::from_file_path(&root).unwrap(),
+            name: "project".to_string(),
+        }]),
+        ..InitializeParams::default()
+    });
+    let names: Vec<String> = workspace_symbols(&mut client, "")
+        .into_iter()
+        .map(|(name, _, _)| name)
+        .collect();
+    assert_eq!(names, ["build", "deploy:staging", "format"]);
+
+    client.shutdown();
+    std::fs::remove_dir_all(&root).unwrap();
+}
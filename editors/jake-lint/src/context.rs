@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use tree_sitter::Tree;
+use tree_sitter_jake::ast::{split_qualified_name, AstNode, Jakefile};
+use tree_sitter_jake::index::JakefileIndex;
+use tree_sitter_jake::workspace::{ModuleId, Workspace};
+
+/// Whether a name can be resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lookup {
+    Found,
+    Missing,
+    /// The name may come from an import the linter cannot see.
+    Unknown,
+}
+
+/// The file being linted and, in workspace mode, the files around it.
+pub struct LintContext<'a> {
+    pub source: &'a str,
+    pub tree: &'a Tree,
+    pub index: &'a JakefileIndex,
+    workspace: Option<(&'a Workspace, ModuleId)>,
+    /// Byte ranges of recipes whose results are already known, in source
+    /// order.
+    skipped: &'a [Range<usize>],
+    variable_uses: Option<&'a HashSet<&'a str>>,
+}
+
+impl<'a> LintContext<'a> {
+    pub fn new(
+        source: &'a str,
+        tree: &'a Tree,
+        index: &'a JakefileIndex,
+        workspace: Option<(&'a Workspace, ModuleId)>,
+    ) -> Self {
+        Self {
+            source,
+            tree,
+            index,
+            workspace,
+            skipped: &[],
+            variable_uses: None,
+        }
+    }
+
+    /// Leave the recipes at `ranges` out of [`Scope::Recipe`] rules.
+    ///
+    /// [`Scope::Recipe`]: crate::Scope::Recipe
+    pub(crate) fn skip_recipes(mut self, ranges: &'a [Range<usize>]) -> Self {
+        self.skipped = ranges;
+        self
+    }
+
+    /// Use `uses` as the variable names used in the workspace instead of
+    /// collecting them from every file.
+    pub(crate) fn with_variable_uses(mut self, uses: &'a HashSet<&'a str>) -> Self {
+        self.variable_uses = Some(uses);
+        self
+    }
+
+    /// The variable names used anywhere in the workspace, when the linter
+    /// kept them from an earlier run.
+    pub fn variable_uses(&self) -> Option<&'a HashSet<&'a str>> {
+        self.variable_uses
+    }
+
+    /// Whether the recipe at `range` is to be checked by rules that check
+    /// one recipe at a time. Recipes with syntax errors are not: what the
+    /// parser recovered of them says little about what was meant.
+    pub fn checks_recipe(&self, range: Range<usize>) -> bool {
+        let skipped = self
+            .skipped
+            .binary_search_by_key(&range.start, |skipped| skipped.start)
+            .is_ok_and(|position| self.skipped[position] == range);
+        let has_error = self
+            .tree
+            .root_node()
+            .descendant_for_byte_range(range.start, range.end)
+            .is_some_and(|node| node.byte_range() == range && node.has_error());
+        !skipped && !has_error
+    }
+
+    pub fn jakefile(&self) -> Option<Jakefile<'a>> {
+        Jakefile::cast(self.tree.root_node())
+    }
+
+    /// The workspace and the module being linted, if linting across imports.
+    pub fn workspace(&self) -> Option<(&'a Workspace, ModuleId)> {
+        self.workspace
+    }
+
+    /// Resolve a dependency or hook target written in this file.
+    pub fn lookup_recipe(&self, name: &str) -> Lookup {
+        if self.index.recipe(name).is_some() {
+            return Lookup::Found;
+        }
+        if let Some((workspace, module)) = self.workspace {
+            // Names inside an imported file are prefixed with its namespace
+            // when they point into that file.
+            let qualified = workspace
+                .module(module)
+                .namespace
+                .as_ref()
+                .map(|namespace| format!("{namespace}.{name}"));
+            if qualified.is_some_and(|name| workspace.recipe(&name).is_some())
+                || workspace.recipe(name).is_some()
+            {
+                return Lookup::Found;
+            }
+        }
+        if self.may_be_imported(name) {
+            Lookup::Unknown
+        } else {
+            Lookup::Missing
+        }
+    }
+
+    /// Resolve a `{{name}}` against the Jakefile variables.
+    pub fn lookup_variable(&self, name: &str) -> Lookup {
+        if self.index.variable(name).is_some() {
+            return Lookup::Found;
+        }
+        match self.workspace {
+            Some((workspace, _)) if workspace.variable(name).is_some() => Lookup::Found,
+            // Imported variables keep their names, so any import could define it.
+            _ if self.unresolved_imports().next().is_some() => Lookup::Unknown,
+            _ => Lookup::Missing,
+        }
+    }
+
+    /// Recipe names and aliases that can be suggested for a misspelt name.
+    /// Private recipes are left out together with their aliases, as in
+    /// `findSimilarRecipes`.
+    pub fn recipe_names(&self) -> Vec<&'a str> {
+        let index = self.index;
+        let mut names: Vec<&str> = index
+            .public_recipes()
+            .flat_map(|recipe| {
+                std::iter::once(recipe.name.as_str())
+                    .chain(recipe.aliases.iter().map(|alias| alias.name.as_str()))
+            })
+            .collect();
+        if let Some((workspace, _)) = self.workspace {
+            for recipe in workspace.recipes() {
+                let entry = workspace.entry(recipe);
+                if entry.is_private() {
+                    continue;
+                }
+                names.push(&recipe.name);
+                names.extend(entry.aliases.iter().map(|alias| alias.name.as_str()));
+            }
+        }
+        names
+    }
+
+    /// Variable names that can be suggested for a misspelt name.
+    pub fn variable_names(&self) -> Vec<&'a str> {
+        let mut names: Vec<&str> = self
+            .index
+            .variables()
+            .iter()
+            .map(|variable| variable.name.as_str())
+            .collect();
+        if let Some((workspace, _)) = self.workspace {
+            names.extend(
+                workspace
+                    .variables()
+                    .iter()
+                    .map(|variable| variable.name.as_str()),
+            );
+        }
+        names
+    }
+
+    /// Whether `name` could be defined by an import that was not loaded.
+    fn may_be_imported(&self, name: &str) -> bool {
+        let namespace = split_qualified_name(name)
+            .0
+            .and_then(|namespace| namespace.split(['.', ':']).next());
+        self.unresolved_imports()
+            .any(|import| import.is_none() || import == namespace)
+    }
+
+    /// Namespaces of the imports whose contents are not available: every
+    /// import in single-file mode, only failed ones in workspace mode.
+    fn unresolved_imports(&self) -> impl Iterator<Item = Option<&'a str>> + '_ {
+        let failed: Vec<Option<&'a str>> = match self.workspace {
+            Some((workspace, module)) => workspace
+                .imports()
+                .iter()
+                .filter(|import| import.from == module && import.to.is_none())
+                .map(|import| import.namespace.as_deref())
+                .collect(),
+            None => self
+                .index
+                .imports()
+                .iter()
+                .map(|import| import.namespace.as_deref())
+                .collect(),
+        };
+        failed.into_iter()
+    }
+}
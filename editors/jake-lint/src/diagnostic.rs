@@ -0,0 +1,229 @@
+use std::fmt;
+use std::ops::Range;
+
+/// How serious a diagnostic is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Hint => "hint",
+        }
+    }
+
+    /// Parse a severity name as accepted by `--severity`; `off` maps to `None`.
+    pub fn parse(text: &str) -> Option<Option<Severity>> {
+        let severity = match text {
+            "error" => Severity::Error,
+            "warning" | "warn" => Severity::Warning,
+            "info" => Severity::Info,
+            "hint" => Severity::Hint,
+            "off" => return Some(None),
+            _ => return None,
+        };
+        Some(Some(severity))
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A replacement of a byte range of the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl Edit {
+    pub fn replace(range: Range<usize>, replacement: impl Into<String>) -> Self {
+        Self {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    pub fn insert(offset: usize, text: impl Into<String>) -> Self {
+        Self::replace(offset..offset, text)
+    }
+
+    pub fn delete(range: Range<usize>) -> Self {
+        Self::replace(range, "")
+    }
+}
+
+/// A suggested change that resolves a diagnostic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fix {
+    /// Short description shown in editors, e.g. "Remove '@end'".
+    pub title: String,
+    pub edits: Vec<Edit>,
+}
+
+impl Fix {
+    pub fn new(title: impl Into<String>, edits: Vec<Edit>) -> Self {
+        Self {
+            title: title.into(),
+            edits,
+        }
+    }
+}
+
+/// A problem reported by a rule, before the linter attaches its code and severity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub range: Range<usize>,
+    pub message: String,
+    pub fix: Option<Fix>,
+}
+
+impl Violation {
+    pub fn new(range: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            range,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// A problem found by the linter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Stable code of the rule, e.g. `JK001`.
+    pub code: &'static str,
+    /// Name of the rule, e.g. `undefined-dependency`.
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Byte range in the linted source.
+    pub range: Range<usize>,
+    pub fix: Option<Fix>,
+}
+
+impl Diagnostic {
+    /// The same diagnostic after the text before it grew by `bytes` bytes.
+    pub(crate) fn moved(&self, bytes: isize) -> Self {
+        Self {
+            range: move_range(&self.range, bytes),
+            fix: self.fix.as_ref().map(|fix| Fix {
+                title: fix.title.clone(),
+                edits: fix
+                    .edits
+                    .iter()
+                    .map(|edit| {
+                        Edit::replace(move_range(&edit.range, bytes), edit.replacement.clone())
+                    })
+                    .collect(),
+            }),
+            ..self.clone()
+        }
+    }
+}
+
+/// `range` after the text before it grew by `bytes` bytes.
+pub(crate) fn move_range(range: &Range<usize>, bytes: isize) -> Range<usize> {
+    range.start.wrapping_add_signed(bytes)..range.end.wrapping_add_signed(bytes)
+}
+
+/// Apply the fixes of `diagnostics` to `source`.
+///
+/// Edits are applied in source order; a fix whose edits overlap an edit that
+/// was already accepted is skipped as a whole, so running the linter again
+/// may offer it once more.
+pub fn apply_fixes(source: &str, diagnostics: &[Diagnostic]) -> String {
+    let mut fixes: Vec<&Fix> = diagnostics.iter().filter_map(|d| d.fix.as_ref()).collect();
+    fixes.sort_by_key(|fix| fix.edits.iter().map(|edit| edit.range.start).min());
+
+    let mut accepted: Vec<&Edit> = Vec::new();
+    for fix in fixes {
+        let overlaps = fix.edits.iter().any(|edit| {
+            accepted
+                .iter()
+                .any(|other| ranges_overlap(&edit.range, &other.range))
+        });
+        if !overlaps {
+            accepted.extend(&fix.edits);
+        }
+    }
+    accepted.sort_by_key(|edit| (edit.range.start, edit.range.end));
+
+    let mut result = source.to_string();
+    for edit in accepted.iter().rev() {
+        result.replace_range(edit.range.clone(), &edit.replacement);
+    }
+    result
+}
+
+/// Overlap test that also treats two insertions at the same offset, or an
+/// insertion inside a replaced range, as conflicting.
+fn ranges_overlap(a: &Range<usize>, b: &Range<usize>) -> bool {
+    if a.is_empty() || b.is_empty() {
+        return (b.start..=b.end).contains(&a.start) || (a.start..=a.end).contains(&b.start);
+    }
+    a.start < b.end && b.start < a.end
+}
+
+/// Zero-based line and column (in bytes) of `offset` in `source`.
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count();
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1);
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(fix: Fix) -> Diagnostic {
+        Diagnostic {
+            code: "JK000",
+            rule: "test",
+            severity: Severity::Warning,
+            message: String::new(),
+            range: 0..0,
+            fix: Some(fix),
+        }
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let source = "task build: [clena]\n";
+        let diagnostics = [
+            diagnostic(Fix::new("Replace", vec![Edit::replace(13..18, "clean")])),
+            diagnostic(Fix::new("Conflict", vec![Edit::delete(14..19)])),
+            diagnostic(Fix::new("Rename", vec![Edit::replace(5..10, "compile")])),
+            diagnostic(Fix::new("Append", vec![Edit::insert(20, "    make\n")])),
+        ];
+        assert_eq!(
+            apply_fixes(source, &diagnostics),
+            "task compile: [clean]\n    make\n"
+        );
+    }
+
+    #[test]
+    fn test_line_col() {
+        let source = "a\nbc\n";
+        assert_eq!(line_col(source, 0), (0, 0));
+        assert_eq!(line_col(source, 3), (1, 1));
+        assert_eq!(line_col(source, 5), (2, 0));
+    }
+}
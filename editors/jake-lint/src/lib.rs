@@ -0,0 +1,520 @@
+//! Static checks for Jakefiles.
+//!
+//! A [`Linter`] runs a set of [`Rule`]s over a parsed Jakefile and returns
+//! [`Diagnostic`]s. Every diagnostic carries a stable code (`JK001`, ...), a
+//! byte range into the source and, where the rule knows how, a [`Fix`]. The
+//! same output drives the `jake-lint` binary and editor integrations.
+//!
+//! ```
+//! let diagnostics = jake_lint::Linter::new().lint_source("task build: [clena]\n    make\n");
+//! assert_eq!(diagnostics[0].code, "JK001");
+//! ```
+//!
+//! Linting a single file cannot see recipes and variables from imported
+//! files, so names that might come from an import are not reported. Use
+//! [`Linter::lint_module`] with a [`Workspace`] to check across imports, and
+//! [`Linter::lint_changes`] with a [`LintCache`] to re-check a file after an
+//! edit without checking the recipes that did not change.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+use tree_sitter::Tree;
+use tree_sitter_jake::index::JakefileIndex;
+use tree_sitter_jake::workspace::{ModuleId, Workspace};
+
+mod context;
+mod diagnostic;
+pub mod rules;
+pub mod suggest;
+
+pub use context::{LintContext, Lookup};
+use diagnostic::move_range;
+pub use diagnostic::{apply_fixes, line_col, Diagnostic, Edit, Fix, Severity, Violation};
+
+/// A single check.
+pub trait Rule {
+    /// Stable identifier such as `JK001`, used in output and configuration.
+    fn code(&self) -> &'static str;
+
+    /// Kebab-case name such as `undefined-dependency`.
+    fn name(&self) -> &'static str;
+
+    /// One-line summary for `jake-lint --list-rules`.
+    fn description(&self) -> &'static str;
+
+    fn default_severity(&self) -> Severity;
+
+    /// What the rule's violations depend on, which decides what has to be
+    /// checked again after an edit.
+    fn scope(&self) -> Scope {
+        Scope::File
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Violation>;
+}
+
+/// What a rule looks at to find a violation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    /// Anything in the file.
+    File,
+    /// For violations inside a recipe, only that recipe and the names the
+    /// file can see. Such rules skip recipes that
+    /// [`LintContext::checks_recipe`] rules out and still check everything
+    /// outside recipes.
+    Recipe,
+}
+
+/// Results of linting one workspace file, kept so that linting it again
+/// after an edit only checks the recipes that changed.
+///
+/// A cache is only valid for the file and the [`Linter`] it was filled by.
+#[derive(Clone, Debug, Default)]
+pub struct LintCache {
+    /// Length of the source the results are for.
+    len: usize,
+    /// Fingerprint of the names the file could see.
+    names: Option<u64>,
+    /// Each indexed recipe, in source order.
+    recipes: Vec<CachedRecipe>,
+    /// The variables the other files of the workspace use, with a
+    /// fingerprint of their sources.
+    imported_uses: Option<(u64, HashSet<String>)>,
+}
+
+/// What is kept of one recipe between edits.
+#[derive(Clone, Debug)]
+struct CachedRecipe {
+    range: Range<usize>,
+    /// Violations of [`Scope::Recipe`] rules inside the recipe.
+    diagnostics: Vec<Diagnostic>,
+    /// The variables the recipe uses.
+    uses: Vec<String>,
+}
+
+/// A set of rules and their configured severities.
+pub struct Linter {
+    rules: Vec<Box<dyn Rule>>,
+    severities: HashMap<&'static str, Option<Severity>>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Linter {
+    /// A linter with every built-in rule at its default severity.
+    pub fn new() -> Self {
+        let mut linter = Self::empty();
+        for rule in rules::all() {
+            linter.register(rule);
+        }
+        linter
+    }
+
+    /// A linter with no rules.
+    pub fn empty() -> Self {
+        Self {
+            rules: Vec::new(),
+            severities: HashMap::new(),
+        }
+    }
+
+    pub fn register(&mut self, rule: Box<dyn Rule>) {
+        self.rules.push(rule);
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules.iter().map(|rule| rule.as_ref())
+    }
+
+    /// Override the severity of the rule with the given code or name.
+    /// `None` disables the rule. Returns `false` if no rule matches.
+    pub fn set_severity(&mut self, rule: &str, severity: Option<Severity>) -> bool {
+        let Some(code) = self
+            .rules
+            .iter()
+            .find(|candidate| candidate.code() == rule || candidate.name() == rule)
+            .map(|rule| rule.code())
+        else {
+            return false;
+        };
+        self.severities.insert(code, severity);
+        true
+    }
+
+    /// The effective severity of a rule, `None` if it is disabled.
+    pub fn severity(&self, rule: &dyn Rule) -> Option<Severity> {
+        self.severities
+            .get(rule.code())
+            .copied()
+            .unwrap_or(Some(rule.default_severity()))
+    }
+
+    /// Parse and lint a standalone Jakefile.
+    pub fn lint_source(&self, source: &str) -> Vec<Diagnostic> {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_jake::language())
+            .expect("Error loading Jake language");
+        let tree = parser
+            .parse(source, None)
+            .expect("parser has a language and no timeout");
+        self.lint_tree(&tree, source)
+    }
+
+    /// Lint an already parsed standalone Jakefile.
+    pub fn lint_tree(&self, tree: &Tree, source: &str) -> Vec<Diagnostic> {
+        let index = JakefileIndex::build(tree, source);
+        self.run(&LintContext::new(source, tree, &index, None))
+    }
+
+    /// Lint one file of a workspace, resolving names across its imports.
+    pub fn lint_module(&self, workspace: &Workspace, module: ModuleId) -> Vec<Diagnostic> {
+        let file = workspace.module(module);
+        self.run(&LintContext::new(
+            &file.source,
+            &file.tree,
+            &file.index,
+            Some((workspace, module)),
+        ))
+    }
+
+    /// Lint one file of a workspace after an edit, with the same results as
+    /// [`Linter::lint_module`].
+    ///
+    /// `changed` holds the byte ranges of the file that were edited, or
+    /// whose syntax changed, since `cache` was filled. Recipes before the
+    /// first and after the last of them keep their results from `cache` and
+    /// are not checked again, as long as the recipe, alias and variable
+    /// names the file can see are the same as before. The variables the
+    /// other files use are collected again only when one of them changed.
+    /// `cache` is updated for the next edit.
+    pub fn lint_changes(
+        &self,
+        workspace: &Workspace,
+        module: ModuleId,
+        changed: &[Range<usize>],
+        cache: &mut LintCache,
+    ) -> Vec<Diagnostic> {
+        let file = workspace.module(module);
+        let names = Some(names_fingerprint(workspace, module));
+        let first = changed.iter().map(|range| range.start).min();
+        let last = changed.iter().map(|range| range.end).max();
+        let delta = file.source.len() as isize - cache.len as isize;
+
+        let mut reused = Vec::new();
+        let mut unchanged = Vec::new();
+        // The variables used by the unchanged recipes, by where they start.
+        let mut recipe_uses = HashMap::new();
+        if cache.names == names {
+            for entry in file.index.recipes() {
+                let range = entry.range.start_byte..entry.range.end_byte;
+                let bytes = if first.is_none_or(|first| range.end < first) {
+                    0
+                } else if last.is_some_and(|last| range.start > last) {
+                    delta
+                } else {
+                    continue;
+                };
+                let before = move_range(&range, -bytes);
+                let Ok(position) = cache
+                    .recipes
+                    .binary_search_by_key(&before.start, |cached| cached.range.start)
+                else {
+                    continue;
+                };
+                let cached = &mut cache.recipes[position];
+                if cached.range == before {
+                    reused.extend(cached.diagnostics.iter().map(|d| d.moved(bytes)));
+                    recipe_uses.insert(range.start, std::mem::take(&mut cached.uses));
+                    unchanged.push(range);
+                }
+            }
+        }
+
+        let imported = imported_fingerprint(workspace, module);
+        if cache
+            .imported_uses
+            .as_ref()
+            .is_none_or(|(fingerprint, _)| *fingerprint != imported)
+        {
+            let uses = workspace
+                .modules()
+                .filter(|(id, _)| *id != module)
+                .flat_map(|(_, other)| rules::variable_uses(other.tree.root_node(), &other.source))
+                .map(String::from)
+                .collect();
+            cache.imported_uses = Some((imported, uses));
+        }
+        let mut used: HashSet<&str> = cache
+            .imported_uses
+            .iter()
+            .flat_map(|(_, uses)| uses.iter().map(String::as_str))
+            .collect();
+        let root = file.tree.root_node();
+        for node in root.children(&mut root.walk()) {
+            let is_recipe = file
+                .index
+                .recipes()
+                .binary_search_by_key(&node.start_byte(), |entry| entry.range.start_byte)
+                .is_ok_and(|position| {
+                    file.index.recipes()[position].range.end_byte == node.end_byte()
+                });
+            if !is_recipe {
+                used.extend(rules::variable_uses(node, &file.source));
+                continue;
+            }
+            recipe_uses.entry(node.start_byte()).or_insert_with(|| {
+                rules::variable_uses(node, &file.source)
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            });
+        }
+        used.extend(recipe_uses.values().flatten().map(String::as_str));
+
+        let ctx = LintContext::new(
+            &file.source,
+            &file.tree,
+            &file.index,
+            Some((workspace, module)),
+        )
+        .skip_recipes(&unchanged)
+        .with_variable_uses(&used);
+        let mut diagnostics = self.run(&ctx);
+        diagnostics.extend(reused);
+        sort(&mut diagnostics);
+
+        let recipe_scoped: HashSet<&str> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.scope() == Scope::Recipe)
+            .map(|rule| rule.code())
+            .collect();
+        cache.len = file.source.len();
+        cache.names = names;
+        cache.recipes = file
+            .index
+            .recipes()
+            .iter()
+            .map(|entry| CachedRecipe {
+                range: entry.range.start_byte..entry.range.end_byte,
+                diagnostics: Vec::new(),
+                uses: recipe_uses
+                    .remove(&entry.range.start_byte)
+                    .unwrap_or_default(),
+            })
+            .collect();
+        for diagnostic in &diagnostics {
+            if !recipe_scoped.contains(diagnostic.code) {
+                continue;
+            }
+            let position = cache
+                .recipes
+                .partition_point(|cached| cached.range.start <= diagnostic.range.start);
+            if let Some(cached) = position
+                .checked_sub(1)
+                .map(|position| &mut cache.recipes[position])
+            {
+                if diagnostic.range.end <= cached.range.end {
+                    cached.diagnostics.push(diagnostic.clone());
+                }
+            }
+        }
+        diagnostics
+    }
+
+    fn run(&self, ctx: &LintContext<'_>) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for rule in &self.rules {
+            let Some(severity) = self.severity(rule.as_ref()) else {
+                continue;
+            };
+            diagnostics.extend(rule.check(ctx).into_iter().map(|violation| Diagnostic {
+                code: rule.code(),
+                rule: rule.name(),
+                severity,
+                message: violation.message,
+                range: violation.range,
+                fix: violation.fix,
+            }));
+        }
+        sort(&mut diagnostics);
+        diagnostics
+    }
+}
+
+fn sort(diagnostics: &mut [Diagnostic]) {
+    diagnostics.sort_by(|a, b| {
+        (a.range.start, a.range.end, a.code).cmp(&(b.range.start, b.range.end, b.code))
+    });
+}
+
+/// A hash of the names lookups from `module` can resolve to, in the order
+/// suggestions are drawn from them.
+fn names_fingerprint(workspace: &Workspace, module: ModuleId) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let file = workspace.module(module);
+    file.namespace.hash(&mut hasher);
+    for recipe in file.index.recipes() {
+        recipe.name.hash(&mut hasher);
+        for alias in &recipe.aliases {
+            alias.name.hash(&mut hasher);
+        }
+    }
+    for variable in file.index.variables() {
+        variable.name.hash(&mut hasher);
+    }
+    for recipe in workspace.recipes() {
+        recipe.name.hash(&mut hasher);
+        for alias in &workspace.entry(recipe).aliases {
+            alias.name.hash(&mut hasher);
+        }
+    }
+    for variable in workspace.variables() {
+        variable.name.hash(&mut hasher);
+    }
+    for import in workspace.imports() {
+        (import.from, import.to.is_some(), &import.namespace).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// A hash of the files of the workspace other than `module`.
+fn imported_fingerprint(workspace: &Workspace, module: ModuleId) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for (id, other) in workspace.modules() {
+        if id != module {
+            (&other.path, &other.source).hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_overrides() {
+        let source = "task build: [missing]\n    make\n";
+        let mut linter = Linter::new();
+        assert_eq!(linter.lint_source(source)[0].severity, Severity::Error);
+
+        assert!(linter.set_severity("undefined-dependency", Some(Severity::Warning)));
+        assert_eq!(linter.lint_source(source)[0].severity, Severity::Warning);
+
+        assert!(linter.set_severity("JK001", None));
+        assert!(linter.lint_source(source).is_empty());
+        assert!(!linter.set_severity("JK999", None));
+    }
+
+    #[test]
+    fn test_recipes_with_syntax_errors_are_skipped() {
+        let source = "task build: [clena]\n    echo {{VERSON}}\n    echo {{ }}\n\ntask test: [biuld]\n    make test\n";
+        let diagnostics = Linter::new().lint_source(source);
+        let names: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| &source[diagnostic.range.clone()])
+            .collect();
+        assert_eq!(names, ["biuld"]);
+    }
+
+    #[test]
+    fn test_rule_codes_are_unique() {
+        let linter = Linter::new();
+        let mut codes: Vec<_> = linter.rules().map(|rule| rule.code()).collect();
+        let count = codes.len();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), count);
+    }
+
+    #[test]
+    fn test_lint_changes_matches_lint_module() {
+        use tree_sitter_jake::workspace::MemoryFileSystem;
+
+        let linter = Linter::new();
+        let lint = |source: &str, changed: &[Range<usize>], cache: &mut LintCache| {
+            let workspace = Workspace::with_root_source(
+                &MemoryFileSystem::new(),
+                "/p/Jakefile",
+                source.to_string(),
+            );
+            let incremental = linter.lint_changes(&workspace, workspace.root(), changed, cache);
+            assert_eq!(
+                incremental,
+                linter.lint_module(&workspace, workspace.root())
+            );
+            incremental
+        };
+
+        let before = "VERSION = \"1.0\"\n\ntask build: [clena]\n    echo {{VERSION}}\n\ntask test:\n    make test\n\ntask release: [test]\n    @if env(CI)\n    echo {{VERSON}}\n";
+        let mut cache = LintCache::default();
+        assert_eq!(lint(before, &[], &mut cache).len(), 3);
+
+        // Typing in `test` moves the results for `release` along.
+        let offset = before.find("make test").unwrap();
+        let after = format!(
+            "{}{{{{upcase(VERSION)}}}} {}",
+            &before[..offset],
+            &before[offset..]
+        );
+        let changed = offset..offset + "{{upcase(VERSION)}} ".len();
+        let codes: Vec<_> = lint(&after, std::slice::from_ref(&changed), &mut cache)
+            .iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect();
+        assert_eq!(codes, ["JK001", "JK009", "JK006", "JK002"]);
+
+        // Renaming a recipe re-checks every recipe that depends on it.
+        let offset = after.find("build:").unwrap();
+        let renamed = after.replacen("task build:", "task clena:", 1);
+        let changed = offset..offset + "build".len();
+        assert!(lint(&renamed, std::slice::from_ref(&changed), &mut cache)
+            .iter()
+            .all(|diagnostic| diagnostic.code != "JK001"));
+    }
+
+    #[test]
+    fn test_lint_changes_keeps_variable_uses() {
+        use tree_sitter_jake::workspace::MemoryFileSystem;
+
+        let linter = Linter::new();
+        let mut cache = LintCache::default();
+        let mut lint = |source: &str, changed: &[Range<usize>]| {
+            let workspace = Workspace::with_root_source(
+                &MemoryFileSystem::new(),
+                "/p/Jakefile",
+                source.to_string(),
+            );
+            let incremental =
+                linter.lint_changes(&workspace, workspace.root(), changed, &mut cache);
+            assert_eq!(
+                incremental,
+                linter.lint_module(&workspace, workspace.root())
+            );
+            incremental
+        };
+
+        let before = "VERSION = \"1.0\"\nTAG = \"v\"\n\ntask build:\n    echo {{VERSION}}\n\ntask tag:\n    echo {{TAG}}\n";
+        assert!(lint(before, &[]).is_empty());
+
+        // `build` keeps its uses from the cache; `tag` no longer uses TAG.
+        let offset = before.find("{{TAG}}").unwrap();
+        let after = before.replace("{{TAG}}", "none");
+        let changed = offset..offset + "none".len();
+        let diagnostics = lint(&after, std::slice::from_ref(&changed));
+        let messages: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(messages, ["Variable 'TAG' is never used"]);
+    }
+}
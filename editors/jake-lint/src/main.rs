@@ -0,0 +1,160 @@
+//! `jake-lint [--fix] [--list-rules] [--severity RULE=LEVEL]... [FILE]...`
+//!
+//! Lints each Jakefile (default: `./Jakefile`) together with the files it
+//! imports and prints `path:line:col: severity[code]: message`, after any
+//! syntax errors and failed imports. Exits with status 1 if any error was
+//! reported.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use jake_lint::{apply_fixes, line_col, Linter, Severity};
+use tree_sitter::Node;
+use tree_sitter_jake::workspace::{OsFileSystem, Workspace};
+
+const USAGE: &str = "usage: jake-lint [--fix] [--list-rules] [--severity RULE=LEVEL]... [FILE]...";
+
+fn main() -> ExitCode {
+    let mut linter = Linter::new();
+    let mut fix = false;
+    let mut files = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fix" => fix = true,
+            "--list-rules" => {
+                for rule in linter.rules() {
+                    println!(
+                        "{} {:<22} {:<8} {}",
+                        rule.code(),
+                        rule.name(),
+                        rule.default_severity(),
+                        rule.description()
+                    );
+                }
+                return ExitCode::SUCCESS;
+            }
+            "--severity" => {
+                let Some(setting) = args.next() else {
+                    eprintln!("{USAGE}");
+                    return ExitCode::from(2);
+                };
+                let parsed = setting
+                    .split_once('=')
+                    .and_then(|(rule, level)| Some((rule, Severity::parse(level)?)));
+                let Some((rule, severity)) = parsed else {
+                    eprintln!("jake-lint: invalid --severity '{setting}', expected RULE=error|warning|info|hint|off");
+                    return ExitCode::from(2);
+                };
+                if !linter.set_severity(rule, severity) {
+                    eprintln!("jake-lint: unknown rule '{rule}'");
+                    return ExitCode::from(2);
+                }
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("jake-lint: unknown option '{arg}'\n{USAGE}");
+                return ExitCode::from(2);
+            }
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+    if files.is_empty() {
+        files.push(PathBuf::from("Jakefile"));
+    }
+
+    let mut has_errors = false;
+    for path in files {
+        match lint_file(&linter, &path, fix) {
+            Ok(errors) => has_errors |= errors,
+            Err(err) => {
+                eprintln!("{}: {err}", path.display());
+                has_errors = true;
+            }
+        }
+    }
+
+    if has_errors {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Lint `path`, applying fixes first if `fix` is set, and print what remains.
+/// Returns whether any error was reported.
+fn lint_file(linter: &Linter, path: &Path, fix: bool) -> io::Result<bool> {
+    let mut workspace = Workspace::load(&OsFileSystem, path)?;
+    if fix {
+        let diagnostics = linter.lint_module(&workspace, workspace.root());
+        if diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.fix.is_some())
+        {
+            let root = workspace.module(workspace.root());
+            std::fs::write(path, apply_fixes(&root.source, &diagnostics))?;
+            workspace = Workspace::load(&OsFileSystem, path)?;
+        }
+    }
+
+    let root = workspace.module(workspace.root());
+    let mut has_errors = false;
+    for error in workspace.errors() {
+        if error.importer == workspace.root() {
+            let point = error.range.start_point;
+            println!(
+                "{}:{}:{}: error: {error}",
+                path.display(),
+                point.row + 1,
+                point.column + 1
+            );
+            has_errors = true;
+        }
+    }
+    for node in syntax_errors(root.tree.root_node()) {
+        let point = node.start_position();
+        let message = if node.is_missing() {
+            format!("Syntax error: missing {}", node.kind())
+        } else {
+            "Syntax error".to_string()
+        };
+        println!(
+            "{}:{}:{}: error: {message}",
+            path.display(),
+            point.row + 1,
+            point.column + 1
+        );
+        has_errors = true;
+    }
+    for diagnostic in linter.lint_module(&workspace, workspace.root()) {
+        let (line, column) = line_col(&root.source, diagnostic.range.start);
+        println!(
+            "{}:{}:{}: {}[{}]: {}",
+            path.display(),
+            line + 1,
+            column + 1,
+            diagnostic.severity,
+            diagnostic.code,
+            diagnostic.message
+        );
+        has_errors |= diagnostic.severity == Severity::Error;
+    }
+    Ok(has_errors)
+}
+
+/// The `ERROR` and missing nodes under `node`, outermost first.
+fn syntax_errors(node: Node<'_>) -> Vec<Node<'_>> {
+    if !node.has_error() {
+        return Vec::new();
+    }
+    if node.is_error() || node.is_missing() {
+        return vec![node];
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor).flat_map(syntax_errors).collect()
+}
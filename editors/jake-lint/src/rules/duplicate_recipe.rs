@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use tree_sitter::Range;
+
+use crate::{LintContext, Rule, Severity, Violation};
+
+/// Two recipes, two aliases or a recipe and an alias sharing a name. Only
+/// the first definition can be run.
+pub struct DuplicateRecipe;
+
+impl Rule for DuplicateRecipe {
+    fn code(&self) -> &'static str {
+        "JK004"
+    }
+
+    fn name(&self) -> &'static str {
+        "duplicate-recipe"
+    }
+
+    fn description(&self) -> &'static str {
+        "Recipe or alias name is already defined"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Violation> {
+        let mut names: Vec<(&str, Range, bool)> = Vec::new();
+        for recipe in ctx.index.recipes() {
+            names.push((&recipe.name, recipe.name_range, false));
+            names.extend(
+                recipe
+                    .aliases
+                    .iter()
+                    .map(|alias| (alias.name.as_str(), alias.range, true)),
+            );
+        }
+        // Aliases are written above their recipe's header.
+        names.sort_by_key(|&(_, range, _)| range.start_byte);
+
+        let mut first: HashMap<&str, Range> = HashMap::new();
+        let mut violations = Vec::new();
+        for (name, range, is_alias) in names {
+            let Some(previous) = first.get(name) else {
+                first.insert(name, range);
+                continue;
+            };
+            let what = if is_alias { "Alias" } else { "Recipe" };
+            violations.push(Violation::new(
+                range.start_byte..range.end_byte,
+                format!(
+                    "{what} '{name}' is already defined on line {}",
+                    previous.start_point.row + 1
+                ),
+            ));
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::check;
+
+    #[test]
+    fn test_duplicate_recipe() {
+        let source = "@alias b\ntask build:\n    make\n\ntask build:\n    make all\n\n@alias b\ntask bundle:\n    make bundle\n";
+        let violations = check(&DuplicateRecipe, source);
+        let messages: Vec<_> = violations.iter().map(|v| v.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "Recipe 'build' is already defined on line 2",
+                "Alias 'b' is already defined on line 1",
+            ]
+        );
+    }
+}
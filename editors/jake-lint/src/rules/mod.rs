@@ -0,0 +1,112 @@
+//! Built-in rules.
+//!
+//! Codes are stable: a rule keeps its code when it is renamed, and codes of
+//! removed rules are not reused.
+
+use tree_sitter::Node;
+use tree_sitter_jake::ast::{
+    descendants_of_kind, fields, kinds, node_text, ExpressionKind, Identifier, Interpolation,
+    ValueKind,
+};
+
+use crate::Rule;
+
+mod duplicate_recipe;
+mod multiple_defaults;
+mod unbalanced_block;
+mod undefined_dependency;
+mod undefined_variable;
+mod unknown_function;
+mod unknown_hook_target;
+mod unreachable_else;
+mod unused_variable;
+mod walrus_assignment;
+
+pub use duplicate_recipe::DuplicateRecipe;
+pub use multiple_defaults::MultipleDefaults;
+pub use unbalanced_block::UnbalancedBlock;
+pub use undefined_dependency::UndefinedDependency;
+pub use undefined_variable::UndefinedVariable;
+pub use unknown_function::UnknownFunction;
+pub use unknown_hook_target::UnknownHookTarget;
+pub use unreachable_else::UnreachableElse;
+pub use unused_variable::UnusedVariable;
+pub use walrus_assignment::WalrusAssignment;
+
+/// Every built-in rule, in code order.
+pub fn all() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(UndefinedDependency),
+        Box::new(UndefinedVariable),
+        Box::new(UnusedVariable),
+        Box::new(DuplicateRecipe),
+        Box::new(MultipleDefaults),
+        Box::new(UnbalancedBlock),
+        Box::new(UnreachableElse),
+        Box::new(UnknownHookTarget),
+        Box::new(UnknownFunction),
+        Box::new(WalrusAssignment),
+    ]
+}
+
+/// The name in a plain `{{name}}` interpolation, which the runtime expands
+/// from variables. Function calls, `{{$1}}` and compound expressions are
+/// not names.
+pub(crate) fn interpolated_name(interpolation: Interpolation<'_>) -> Option<Identifier<'_>> {
+    match interpolation.expression()?.kind()? {
+        ExpressionKind::Value(value) => match value.kind()? {
+            ValueKind::Identifier(identifier) => Some(identifier),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Names of the variables `node` uses: interpolated, read in a condition or
+/// in the value of another variable, or exported.
+pub(crate) fn variable_uses<'src>(node: Node<'_>, source: &'src str) -> Vec<&'src str> {
+    let mut uses = Vec::new();
+    let mut stack = vec![node];
+    while let Some(current) = stack.pop() {
+        let scope = match current.kind() {
+            kinds::INTERPOLATION | kinds::CONDITION_EXPRESSION => Some(current),
+            kinds::ASSIGNMENT => current.child_by_field_name(fields::VALUE),
+            kinds::EXPORT_DIRECTIVE | kinds::BODY_EXPORT_DIRECTIVE => {
+                let name = current.child_by_field_name(fields::NAME);
+                uses.extend(name.map(|name| node_text(name, source)));
+                None
+            }
+            _ => None,
+        };
+        match scope {
+            Some(scope) => uses.extend(
+                descendants_of_kind(scope, kinds::IDENTIFIER)
+                    .into_iter()
+                    .map(|identifier| node_text(identifier, source)),
+            ),
+            None => stack.extend(current.children(&mut current.walk())),
+        }
+    }
+    uses
+}
+
+/// Byte range of the whole line containing `range`, including its newline.
+pub(crate) fn line_range(source: &str, range: std::ops::Range<usize>) -> std::ops::Range<usize> {
+    let start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    if range.end > range.start && source[..range.end].ends_with('\n') {
+        return start..range.end;
+    }
+    let end = source[range.end..]
+        .find('\n')
+        .map_or(source.len(), |i| range.end + i + 1);
+    start..end
+}
+
+#[cfg(test)]
+pub(crate) fn check(rule: &dyn Rule, source: &str) -> Vec<crate::Violation> {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_jake::language()).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let index = tree_sitter_jake::index::JakefileIndex::build(&tree, source);
+    rule.check(&crate::LintContext::new(source, &tree, &index, None))
+}
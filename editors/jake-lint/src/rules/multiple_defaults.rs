@@ -0,0 +1,65 @@
+use tree_sitter_jake::ast::GlobalDirectiveKind;
+
+use crate::rules::line_range;
+use crate::{Edit, Fix, LintContext, Rule, Severity, Violation};
+
+/// More than one `@default`. The runtime uses the first one and ignores the rest.
+pub struct MultipleDefaults;
+
+impl Rule for MultipleDefaults {
+    fn code(&self) -> &'static str {
+        "JK005"
+    }
+
+    fn name(&self) -> &'static str {
+        "multiple-defaults"
+    }
+
+    fn description(&self) -> &'static str {
+        "More than one recipe is marked @default"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Violation> {
+        let mut defaults = ctx.index.directives(GlobalDirectiveKind::Default);
+        let Some(first) = defaults.next() else {
+            return Vec::new();
+        };
+        let first_line = first.range.start_point.row + 1;
+        defaults
+            .map(|directive| {
+                let range =
+                    directive.range.start_byte..directive.range.start_byte + "@default".len();
+                let line = line_range(
+                    ctx.source,
+                    directive.range.start_byte..directive.range.end_byte,
+                );
+                Violation::new(
+                    range,
+                    format!("'@default' is already used on line {first_line}; this one is ignored"),
+                )
+                .with_fix(Fix::new("Remove '@default'", vec![Edit::delete(line)]))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::check;
+
+    #[test]
+    fn test_multiple_defaults() {
+        let source = "@default\ntask build:\n    make\n\n@default\ntask test:\n    make test\n";
+        let violations = check(&MultipleDefaults, source);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].range.start, source.rfind("@default").unwrap());
+
+        let edit = &violations[0].fix.as_ref().unwrap().edits[0];
+        assert_eq!(&source[edit.range.clone()], "@default\n");
+    }
+}
@@ -0,0 +1,125 @@
+use tree_sitter_jake::ast::{AstNode, BodyDirective, RecipeBody};
+use tree_sitter_jake::blocks::{BlockErrorKind, BlockTree};
+
+use crate::rules::line_range;
+use crate::{Edit, Fix, LintContext, Rule, Scope, Severity, Violation};
+
+/// An `@if` or `@each` without a matching `@end`, or an `@elif`, `@else` or
+/// `@end` with nothing to attach to.
+pub struct UnbalancedBlock;
+
+impl Rule for UnbalancedBlock {
+    fn code(&self) -> &'static str {
+        "JK006"
+    }
+
+    fn name(&self) -> &'static str {
+        "unbalanced-block"
+    }
+
+    fn description(&self) -> &'static str {
+        "@if/@each blocks must be closed with @end"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Recipe
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Violation> {
+        let Some(jakefile) = ctx.jakefile() else {
+            return Vec::new();
+        };
+        let mut violations = Vec::new();
+        for body in jakefile
+            .recipes()
+            .filter(|recipe| ctx.checks_recipe(recipe.byte_range()))
+            .filter_map(|recipe| recipe.body())
+        {
+            let blocks = BlockTree::build(body);
+            for error in blocks.errors() {
+                let directive = error.directive;
+                let range = directive.byte_range();
+                let keyword = directive.keyword().unwrap_or_default();
+                let violation = match error.kind {
+                    BlockErrorKind::Unclosed => {
+                        Violation::new(range, format!("'{keyword}' is never closed"))
+                            .with_fix(insert_end(ctx.source, body, directive))
+                    }
+                    BlockErrorKind::UnmatchedEnd => {
+                        let line = line_range(ctx.source, range.clone());
+                        Violation::new(range, "'@end' without a matching '@if' or '@each'")
+                            .with_fix(Fix::new("Remove '@end'", vec![Edit::delete(line)]))
+                    }
+                    BlockErrorKind::OrphanBranch => {
+                        Violation::new(range, format!("'{keyword}' without a matching '@if'"))
+                    }
+                    // Reported by `unreachable-else`.
+                    BlockErrorKind::UnreachableBranch => continue,
+                };
+                violations.push(violation);
+            }
+        }
+        violations
+    }
+}
+
+/// Insert an `@end` after the last line of `body`, indented like `opener`.
+fn insert_end(source: &str, body: RecipeBody<'_>, opener: BodyDirective<'_>) -> Fix {
+    let body = body.syntax();
+    let insert_at = body
+        .named_child_count()
+        .checked_sub(1)
+        .and_then(|last| body.named_child(last))
+        .map_or(body.end_byte(), |last| {
+            line_range(source, last.byte_range()).end
+        });
+    let newline = if source[..insert_at].ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    let start = opener.byte_range().start;
+    let indent = &source[line_range(source, start..start).start..start];
+    Fix::new(
+        "Insert '@end'",
+        vec![Edit::insert(insert_at, format!("{newline}{indent}@end\n"))],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::check;
+
+    #[test]
+    fn test_unclosed_block() {
+        let source = "task build:\n    @if env(CI)\n        make ci\n    @each a b\n        echo {{item}}\n    @end\n";
+        let violations = check(&UnbalancedBlock, source);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].message, "'@if' is never closed");
+
+        let edit = &violations[0].fix.as_ref().unwrap().edits[0];
+        assert_eq!(edit.range, source.len()..source.len());
+        assert_eq!(edit.replacement, "    @end\n");
+    }
+
+    #[test]
+    fn test_orphan_directives() {
+        let source = "task build:\n    @else\n        make\n    @end\n";
+        let violations = check(&UnbalancedBlock, source);
+        let messages: Vec<_> = violations.iter().map(|v| v.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "'@else' without a matching '@if'",
+                "'@end' without a matching '@if' or '@each'",
+            ]
+        );
+        let edit = &violations[1].fix.as_ref().unwrap().edits[0];
+        assert_eq!(&source[edit.range.clone()], "    @end\n");
+    }
+}
@@ -0,0 +1,98 @@
+use crate::context::Lookup;
+use crate::suggest::find_similar;
+use crate::{Edit, Fix, LintContext, Rule, Scope, Severity, Violation};
+
+/// `task build: [clena]` where no recipe or alias `clena` exists.
+pub struct UndefinedDependency;
+
+impl Rule for UndefinedDependency {
+    fn code(&self) -> &'static str {
+        "JK001"
+    }
+
+    fn name(&self) -> &'static str {
+        "undefined-dependency"
+    }
+
+    fn description(&self) -> &'static str {
+        "Dependency does not name a recipe or alias"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Recipe
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Violation> {
+        let candidates = ctx.recipe_names();
+        let mut violations = Vec::new();
+        for recipe in ctx.index.recipes() {
+            if !ctx.checks_recipe(recipe.range.start_byte..recipe.range.end_byte) {
+                continue;
+            }
+            for dependency in &recipe.dependencies {
+                // Paths may name files on disk rather than file recipes.
+                if dependency.name.contains('/')
+                    || ctx.lookup_recipe(&dependency.name) != Lookup::Missing
+                {
+                    continue;
+                }
+                let range = dependency.range.start_byte..dependency.range.end_byte;
+                let mut violation = Violation::new(
+                    range.clone(),
+                    format!(
+                        "Recipe '{}' depends on unknown recipe '{}'",
+                        recipe.name, dependency.name
+                    ),
+                );
+                if let Some(similar) =
+                    find_similar(&dependency.name, candidates.iter().copied()).first()
+                {
+                    violation = violation.with_fix(Fix::new(
+                        format!("Did you mean '{similar}'?"),
+                        vec![Edit::replace(range, *similar)],
+                    ));
+                }
+                violations.push(violation);
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::check;
+
+    #[test]
+    fn test_undefined_dependency() {
+        let source =
+            "task clean:\n    rm -rf dist\n\ntask build: [clena, clean, dist/app.js]\n    make\n";
+        let violations = check(&UndefinedDependency, source);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(&source[violations[0].range.clone()], "clena");
+        let fix = violations[0].fix.as_ref().unwrap();
+        assert_eq!(fix.edits[0].replacement, "clean");
+    }
+
+    #[test]
+    fn test_private_aliases_are_not_suggested() {
+        let source =
+            "@alias clean\ntask _wipe:\n    rm -rf dist\n\ntask build: [clena]\n    make\n";
+        let violations = check(&UndefinedDependency, source);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].fix.is_none());
+    }
+
+    #[test]
+    fn test_imported_names_are_not_reported() {
+        let source = "@import \"docker.jake\" as docker\n\ntask build: [docker:push, other.push]\n    make\n";
+        let violations = check(&UndefinedDependency, source);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(&source[violations[0].range.clone()], "other.push");
+    }
+}
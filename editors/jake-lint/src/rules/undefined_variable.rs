@@ -0,0 +1,134 @@
+use tree_sitter::Node;
+use tree_sitter_jake::ast::{
+    descendants_of_kind, kinds, AstNode, BodyDirectiveKind, Interpolation, Recipe,
+};
+
+use crate::context::Lookup;
+use crate::rules::interpolated_name;
+use crate::suggest::find_similar;
+use crate::{Edit, Fix, LintContext, Rule, Scope, Severity, Violation};
+
+/// `{{name}}` where `name` is neither a variable nor a parameter of the
+/// recipe. The runtime leaves such text unexpanded, so this is a warning.
+pub struct UndefinedVariable;
+
+impl Rule for UndefinedVariable {
+    fn code(&self) -> &'static str {
+        "JK002"
+    }
+
+    fn name(&self) -> &'static str {
+        "undefined-variable"
+    }
+
+    fn description(&self) -> &'static str {
+        "Interpolated name is not a variable or recipe parameter"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Recipe
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Violation> {
+        let Some(jakefile) = ctx.jakefile() else {
+            return Vec::new();
+        };
+        let candidates = ctx.variable_names();
+        let mut violations = Vec::new();
+
+        for recipe in jakefile.recipes() {
+            if !ctx.checks_recipe(recipe.byte_range()) {
+                continue;
+            }
+            let Some(body) = recipe.body() else {
+                continue;
+            };
+            let locals = recipe_locals(recipe, ctx.source);
+            check_interpolations(ctx, body.syntax(), &locals, &candidates, &mut violations);
+        }
+        for directive in jakefile.global_directives() {
+            if let Some(hook) = directive.hook() {
+                check_interpolations(ctx, hook.syntax(), &[], &candidates, &mut violations);
+            }
+        }
+        violations
+    }
+}
+
+/// Names that are in scope for interpolations inside a recipe body.
+fn recipe_locals<'src>(recipe: Recipe<'_>, source: &'src str) -> Vec<&'src str> {
+    let mut locals: Vec<&str> = recipe
+        .parameters()
+        .into_iter()
+        .filter_map(|parameter| parameter.name())
+        .map(|name| name.text(source))
+        .collect();
+    let has_each = recipe.body().is_some_and(|body| {
+        body.directives()
+            .any(|directive| directive.kind() == Some(BodyDirectiveKind::Each))
+    });
+    if has_each {
+        locals.push("item");
+    }
+    locals
+}
+
+fn check_interpolations(
+    ctx: &LintContext<'_>,
+    node: Node<'_>,
+    locals: &[&str],
+    candidates: &[&str],
+    violations: &mut Vec<Violation>,
+) {
+    for interpolation in descendants_of_kind(node, kinds::INTERPOLATION) {
+        let Some(identifier) = Interpolation::cast(interpolation).and_then(interpolated_name)
+        else {
+            continue;
+        };
+        let name = identifier.text(ctx.source);
+        if locals.contains(&name) || ctx.lookup_variable(name) != Lookup::Missing {
+            continue;
+        }
+        let range = identifier.byte_range();
+        let mut violation = Violation::new(range.clone(), format!("Undefined variable '{name}'"));
+        let similar = find_similar(name, candidates.iter().chain(locals).copied());
+        if let Some(similar) = similar.first() {
+            violation = violation.with_fix(Fix::new(
+                format!("Replace with '{similar}'"),
+                vec![Edit::replace(range, *similar)],
+            ));
+        }
+        violations.push(violation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::check;
+
+    #[test]
+    fn test_undefined_variable() {
+        let source = "VERSION = \"1.0\"\n\ntask release target:\n    echo {{VERSON}} {{target}} {{$1}}\n    echo {{uppercase(target)}}\n\ntask other:\n    echo {{target}}\n";
+        let violations = check(&UndefinedVariable, source);
+        let names: Vec<_> = violations
+            .iter()
+            .map(|violation| &source[violation.range.clone()])
+            .collect();
+        assert_eq!(names, ["VERSON", "target"]);
+        let fix = violations[0].fix.as_ref().unwrap();
+        assert_eq!(fix.edits[0].replacement, "VERSION");
+    }
+
+    #[test]
+    fn test_each_item() {
+        let source = "task lint:\n    @each src tests\n        ruff {{item}}\n    @end\n\ntask other:\n    echo {{item}}\n";
+        let violations = check(&UndefinedVariable, source);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].range.start > source.find("other").unwrap());
+    }
+}
@@ -0,0 +1,85 @@
+use tree_sitter_jake::ast::{descendants_of_kind, kinds, AstNode, FunctionCall, Recipe};
+use tree_sitter_jake::builtins::{self, FUNCTIONS};
+
+use crate::suggest::find_similar;
+use crate::{Edit, Fix, LintContext, Rule, Scope, Severity, Violation};
+
+/// `{{name(...)}}` where `name` is not a built-in function. The runtime
+/// leaves such text unexpanded, so this is a warning.
+pub struct UnknownFunction;
+
+impl Rule for UnknownFunction {
+    fn code(&self) -> &'static str {
+        "JK009"
+    }
+
+    fn name(&self) -> &'static str {
+        "unknown-function"
+    }
+
+    fn description(&self) -> &'static str {
+        "Interpolation calls a function that is not built in"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Recipe
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Violation> {
+        let candidates: Vec<&str> = FUNCTIONS.iter().map(|builtin| builtin.name).collect();
+        let mut violations = Vec::new();
+        let root = ctx.tree.root_node();
+        let mut cursor = root.walk();
+        let items: Vec<_> = root.children(&mut cursor).collect();
+        let calls = items
+            .into_iter()
+            .filter(|item| {
+                Recipe::cast(*item).is_none_or(|recipe| ctx.checks_recipe(recipe.byte_range()))
+            })
+            .flat_map(|item| descendants_of_kind(item, kinds::FUNCTION_CALL));
+        for node in calls {
+            let Some(name) = FunctionCall::cast(node).and_then(|call| call.name()) else {
+                continue;
+            };
+            let text = name.text(ctx.source);
+            if builtins::function(text).is_some() {
+                continue;
+            }
+            let range = name.byte_range();
+            let mut violation = Violation::new(range.clone(), format!("Unknown function '{text}'"));
+            if let Some(similar) = find_similar(text, candidates.iter().copied()).first() {
+                violation = violation.with_fix(Fix::new(
+                    format!("Replace with '{similar}'"),
+                    vec![Edit::replace(range, *similar)],
+                ));
+            }
+            violations.push(violation);
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::check;
+
+    #[test]
+    fn test_unknown_function() {
+        let source = "task build:\n    echo {{uppercase(name)}} {{upcase(name)}}\n    echo {{frobnicate(x)}}\n";
+        let violations = check(&UnknownFunction, source);
+        let names: Vec<_> = violations
+            .iter()
+            .map(|violation| &source[violation.range.clone()])
+            .collect();
+        assert_eq!(names, ["upcase", "frobnicate"]);
+        assert_eq!(violations[0].message, "Unknown function 'upcase'");
+        let fix = violations[0].fix.as_ref().unwrap();
+        assert_eq!(fix.edits[0].replacement, "uppercase");
+        assert!(violations[1].fix.is_none());
+    }
+}
@@ -0,0 +1,111 @@
+use tree_sitter_jake::ast::AstNode;
+
+use crate::context::Lookup;
+use crate::suggest::find_similar;
+use crate::{Edit, Fix, LintContext, Rule, Severity, Violation};
+
+/// `@before deploy ...` or `@after deploy ...` where no recipe `deploy` exists.
+/// The hook would never run.
+pub struct UnknownHookTarget;
+
+impl Rule for UnknownHookTarget {
+    fn code(&self) -> &'static str {
+        "JK008"
+    }
+
+    fn name(&self) -> &'static str {
+        "unknown-hook-target"
+    }
+
+    fn description(&self) -> &'static str {
+        "@before/@after hook targets a recipe that does not exist"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Violation> {
+        let Some(jakefile) = ctx.jakefile() else {
+            return Vec::new();
+        };
+        let candidates = ctx.recipe_names();
+        let mut violations = Vec::new();
+        for directive in jakefile.global_directives() {
+            let Some(target) = directive.hook().and_then(|hook| hook.target()) else {
+                continue;
+            };
+            let name = target.text(ctx.source);
+            if ctx.lookup_recipe(name) != Lookup::Missing {
+                continue;
+            }
+            let range = target.byte_range();
+            let mut violation = Violation::new(
+                range.clone(),
+                format!(
+                    "'{}' hook targets unknown recipe '{name}'",
+                    directive.keyword().unwrap_or_default()
+                ),
+            );
+            if let Some(similar) = find_similar(name, candidates.iter().copied()).first() {
+                violation = violation.with_fix(Fix::new(
+                    format!("Replace with '{similar}'"),
+                    vec![Edit::replace(range, *similar)],
+                ));
+            }
+            violations.push(violation);
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::check;
+
+    #[test]
+    fn test_unknown_hook_target() {
+        let source = "@before deploy echo starting\n@after biuld echo done\n@pre echo always\n\ntask build:\n    make\n";
+        let violations = check(&UnknownHookTarget, source);
+        let names: Vec<_> = violations
+            .iter()
+            .map(|violation| &source[violation.range.clone()])
+            .collect();
+        assert_eq!(names, ["deploy", "biuld"]);
+        assert_eq!(
+            violations[1].message,
+            "'@after' hook targets unknown recipe 'biuld'"
+        );
+        assert!(violations[0].fix.is_none());
+        assert_eq!(
+            violations[1].fix.as_ref().unwrap().edits[0].replacement,
+            "build"
+        );
+    }
+
+    #[test]
+    fn test_namespaced_hook_target() {
+        use tree_sitter_jake::workspace::{MemoryFileSystem, Workspace};
+
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("/p/docker.jake", "task push:\n    docker push\n");
+        let source = "@import \"docker.jake\" as docker\n@before docker.push echo pushing\n@after docker.pushh echo pushed\n";
+        let workspace = Workspace::with_root_source(&fs, "/p/Jakefile", source.to_string());
+        let module = workspace.module(workspace.root());
+        let ctx = LintContext::new(
+            source,
+            &module.tree,
+            &module.index,
+            Some((&workspace, workspace.root())),
+        );
+
+        let violations = UnknownHookTarget.check(&ctx);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(&source[violations[0].range.clone()], "docker.pushh");
+        assert_eq!(
+            violations[0].fix.as_ref().unwrap().edits[0].replacement,
+            "docker.push"
+        );
+    }
+}
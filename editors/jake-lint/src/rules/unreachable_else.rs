@@ -0,0 +1,75 @@
+use tree_sitter_jake::ast::AstNode;
+use tree_sitter_jake::blocks::{BlockErrorKind, BlockTree};
+
+use crate::{LintContext, Rule, Scope, Severity, Violation};
+
+/// An `@elif` or `@else` after the `@else` of the same `@if`. The executor
+/// never runs such a branch.
+pub struct UnreachableElse;
+
+impl Rule for UnreachableElse {
+    fn code(&self) -> &'static str {
+        "JK007"
+    }
+
+    fn name(&self) -> &'static str {
+        "unreachable-else"
+    }
+
+    fn description(&self) -> &'static str {
+        "Branch after @else can never run"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Recipe
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Violation> {
+        let Some(jakefile) = ctx.jakefile() else {
+            return Vec::new();
+        };
+        let mut violations = Vec::new();
+        for body in jakefile
+            .recipes()
+            .filter(|recipe| ctx.checks_recipe(recipe.byte_range()))
+            .filter_map(|recipe| recipe.body())
+        {
+            let blocks = BlockTree::build(body);
+            for error in blocks.errors() {
+                if error.kind != BlockErrorKind::UnreachableBranch {
+                    continue;
+                }
+                violations.push(Violation::new(
+                    error.directive.byte_range(),
+                    format!(
+                        "'{}' after '@else' is unreachable",
+                        error.directive.keyword().unwrap_or_default()
+                    ),
+                ));
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::check;
+
+    #[test]
+    fn test_unreachable_else() {
+        let source = "task build:\n    @if env(CI)\n        make ci\n    @else\n        make\n    @elif env(DEBUG)\n        make debug\n    @end\n    @if env(CI)\n        true\n    @elif env(DEBUG)\n        true\n    @else\n        true\n    @end\n";
+        let violations = check(&UnreachableElse, source);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].message,
+            "'@elif' after '@else' is unreachable"
+        );
+        assert_eq!(violations[0].range.start, source.find("@elif").unwrap());
+    }
+}
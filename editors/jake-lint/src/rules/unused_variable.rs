@@ -0,0 +1,86 @@
+use std::collections::HashSet;
+
+use crate::rules::{line_range, variable_uses};
+use crate::{Edit, Fix, LintContext, Rule, Severity, Violation};
+
+/// A variable that is never interpolated, referenced by another variable,
+/// used in a condition or exported.
+pub struct UnusedVariable;
+
+impl Rule for UnusedVariable {
+    fn code(&self) -> &'static str {
+        "JK003"
+    }
+
+    fn name(&self) -> &'static str {
+        "unused-variable"
+    }
+
+    fn description(&self) -> &'static str {
+        "Variable is assigned but never used"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Violation> {
+        let collected: HashSet<&str>;
+        let used = match ctx.variable_uses() {
+            Some(used) => used,
+            None => {
+                collected = match ctx.workspace() {
+                    // Variables are shared by every file of the workspace.
+                    Some((workspace, _)) => workspace
+                        .modules()
+                        .flat_map(|(_, module)| {
+                            variable_uses(module.tree.root_node(), &module.source)
+                        })
+                        .collect(),
+                    None => variable_uses(ctx.tree.root_node(), ctx.source)
+                        .into_iter()
+                        .collect(),
+                };
+                &collected
+            }
+        };
+
+        ctx.index
+            .variables()
+            .iter()
+            .filter(|variable| !used.contains(variable.name.as_str()))
+            .map(|variable| {
+                let name_range = variable.name_range.start_byte..variable.name_range.end_byte;
+                let line = line_range(
+                    ctx.source,
+                    variable.range.start_byte..variable.range.end_byte,
+                );
+                Violation::new(
+                    name_range,
+                    format!("Variable '{}' is never used", variable.name),
+                )
+                .with_fix(Fix::new(
+                    format!("Remove '{}'", variable.name),
+                    vec![Edit::delete(line)],
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::check;
+
+    #[test]
+    fn test_unused_variable() {
+        let source = "VERSION = \"1.0\"\nTAG = VERSION + \"-rc\"\nUNUSED = \"x\"\nPORT = \"80\"\n@export PORT\n\ntask release:\n    echo {{TAG}}\n";
+        let violations = check(&UnusedVariable, source);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(&source[violations[0].range.clone()], "UNUSED");
+
+        let edit = &violations[0].fix.as_ref().unwrap().edits[0];
+        assert_eq!(&source[edit.range.clone()], "UNUSED = \"x\"\n");
+    }
+}
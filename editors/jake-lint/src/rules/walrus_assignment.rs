@@ -0,0 +1,55 @@
+use crate::{Edit, Fix, LintContext, Rule, Severity, Violation};
+
+/// `NAME := value`. The grammar accepts it for compatibility with `just`, but
+/// the runtime reads the line as a recipe named `NAME` and never sets the
+/// variable.
+pub struct WalrusAssignment;
+
+impl Rule for WalrusAssignment {
+    fn code(&self) -> &'static str {
+        "JK010"
+    }
+
+    fn name(&self) -> &'static str {
+        "walrus-assignment"
+    }
+
+    fn description(&self) -> &'static str {
+        "Variables are assigned with '=', not ':='"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext<'_>) -> Vec<Violation> {
+        let Some(jakefile) = ctx.jakefile() else {
+            return Vec::new();
+        };
+        jakefile
+            .assignments()
+            .filter_map(|assignment| assignment.walrus())
+            .map(|walrus| {
+                let range = walrus.byte_range();
+                Violation::new(range.clone(), "Jake does not support ':='; use '='")
+                    .with_fix(Fix::new("Convert to '='", vec![Edit::replace(range, "=")]))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::check;
+
+    #[test]
+    fn test_walrus_assignment() {
+        let source = "VERSION := \"1.0\"\nNAME = \"app\"\n";
+        let violations = check(&WalrusAssignment, source);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(&source[violations[0].range.clone()], ":=");
+        let edit = &violations[0].fix.as_ref().unwrap().edits[0];
+        assert_eq!(edit.replacement, "=");
+    }
+}
@@ -0,0 +1,73 @@
+//! "Did you mean" suggestions, mirroring `src/suggest.zig`.
+
+/// Largest edit distance at which a name is still suggested.
+pub const MAX_DISTANCE: usize = 3;
+
+/// Levenshtein distance between two strings, counted in bytes like the Zig
+/// implementation.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+
+    let mut prev_row: Vec<usize> = (0..=short.len()).collect();
+    let mut curr_row = vec![0; short.len() + 1];
+    for (j, &c2) in long.iter().enumerate() {
+        curr_row[0] = j + 1;
+        for (i, &c1) in short.iter().enumerate() {
+            let cost = usize::from(c1 != c2);
+            curr_row[i + 1] = (curr_row[i] + 1)
+                .min(prev_row[i + 1] + 1)
+                .min(prev_row[i] + cost);
+        }
+        std::mem::swap(&mut prev_row, &mut curr_row);
+    }
+    prev_row[short.len()]
+}
+
+/// Candidates within [`MAX_DISTANCE`] of `target`, closest first.
+///
+/// Exact matches and names starting with `_` are skipped. Ties keep the
+/// order of `candidates`.
+pub fn find_similar<'a>(
+    target: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter(|name| !name.starts_with('_'))
+        .filter_map(|name| {
+            let distance = levenshtein(target, name);
+            (distance > 0 && distance <= MAX_DISTANCE).then_some((distance, name))
+        })
+        .collect();
+    matches.sort_by_key(|&(distance, _)| distance);
+
+    let mut names: Vec<&str> = Vec::with_capacity(matches.len());
+    for (_, name) in matches {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("build", "build"), 0);
+        assert_eq!(levenshtein("biuld", "build"), 2);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_find_similar() {
+        let candidates = ["build", "test", "_build", "bundle", "deploy", "build"];
+        assert_eq!(find_similar("buld", candidates), ["build", "bundle"]);
+        assert!(find_similar("build", ["build"]).is_empty());
+        assert!(find_similar("xyz", candidates).is_empty());
+    }
+}
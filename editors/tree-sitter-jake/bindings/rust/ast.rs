@@ -0,0 +1,1315 @@
+//! Typed wrappers over the Jake syntax tree.
+//!
+//! The wrappers mirror the shape of the `Jakefile` and `Recipe` structs in
+//! `src/parser.zig`, so tools can ask a [`Recipe`] for its dependencies or a
+//! [`Jakefile`] for its imports instead of matching on node kind strings.
+//! Every wrapper is a thin `Copy` handle around a [`tree_sitter::Node`] and
+//! borrows its text from the source it was parsed from.
+//!
+//! Node kinds and field names come from the [`kinds`] and [`fields`] modules,
+//! which are generated from `src/node-types.json` at build time. A grammar
+//! change that renames or drops a node the wrappers depend on fails the build.
+//!
+//! ```
+//! use tree_sitter_jake::ast::{AstNode, Jakefile};
+//!
+//! let source = "task build: [clean]\n    make\n";
+//! let mut parser = tree_sitter::Parser::new();
+//! parser.set_language(&tree_sitter_jake::language()).unwrap();
+//! let tree = parser.parse(source, None).unwrap();
+//!
+//! let jakefile = Jakefile::cast(tree.root_node()).unwrap();
+//! let recipe = jakefile.recipes().next().unwrap();
+//! assert_eq!(recipe.name(source), Some("build"));
+//! assert_eq!(recipe.dependencies()[0].name(source), "clean");
+//! ```
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use tree_sitter::{Node, Point};
+
+include!(concat!(env!("OUT_DIR"), "/node_types.rs"));
+
+/// A typed view of a syntax node.
+pub trait AstNode<'tree>: Copy + Sized {
+    /// Wrap `node` if it has the kind this type represents.
+    fn cast(node: Node<'tree>) -> Option<Self>;
+
+    /// The underlying syntax node.
+    fn syntax(&self) -> Node<'tree>;
+
+    /// Byte range of the node in the source.
+    fn byte_range(&self) -> Range<usize> {
+        self.syntax().byte_range()
+    }
+
+    /// Byte and point range of the node in the source.
+    fn range(&self) -> tree_sitter::Range {
+        self.syntax().range()
+    }
+
+    fn start_position(&self) -> Point {
+        self.syntax().start_position()
+    }
+
+    fn end_position(&self) -> Point {
+        self.syntax().end_position()
+    }
+
+    /// The source text covered by the node.
+    fn text<'src>(&self, source: &'src str) -> &'src str {
+        &source[self.byte_range()]
+    }
+}
+
+macro_rules! ast_node {
+    ($(#[$meta:meta])* $name:ident => $kind:expr) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub struct $name<'tree>(Node<'tree>);
+
+        impl<'tree> AstNode<'tree> for $name<'tree> {
+            fn cast(node: Node<'tree>) -> Option<Self> {
+                (node.kind() == $kind).then_some(Self(node))
+            }
+
+            fn syntax(&self) -> Node<'tree> {
+                self.0
+            }
+        }
+    };
+}
+
+/// The source text covered by `node`.
+pub fn node_text<'src>(node: Node<'_>, source: &'src str) -> &'src str {
+    &source[node.byte_range()]
+}
+
+fn children<'tree>(node: Node<'tree>) -> impl Iterator<Item = Node<'tree>> {
+    (0..node.child_count()).filter_map(move |i| node.child(i))
+}
+
+fn named_children<'tree>(node: Node<'tree>) -> impl Iterator<Item = Node<'tree>> {
+    (0..node.named_child_count()).filter_map(move |i| node.named_child(i))
+}
+
+fn field_children<'tree>(node: Node<'tree>, field: &str) -> Vec<Node<'tree>> {
+    let mut cursor = node.walk();
+    let nodes = node.children_by_field_name(field, &mut cursor).collect();
+    nodes
+}
+
+fn cast_children<'tree, N: AstNode<'tree> + 'tree>(
+    node: Node<'tree>,
+) -> impl Iterator<Item = N> + 'tree {
+    named_children(node).filter_map(N::cast)
+}
+
+/// The keyword token (`@group`, `@if`, ...) that starts a directive node.
+fn keyword<'tree>(node: Node<'tree>) -> Option<&'static str> {
+    children(node)
+        .find(|child| !child.is_named())
+        .map(|child| child.kind())
+}
+
+ast_node!(
+    /// The root `source_file` node; the tree-sitter counterpart of `parser.Jakefile`.
+    Jakefile => kinds::SOURCE_FILE
+);
+
+impl<'tree> Jakefile<'tree> {
+    /// Top-level items in source order.
+    pub fn items(&self) -> impl Iterator<Item = Item<'tree>> {
+        named_children(self.0).filter_map(Item::cast)
+    }
+
+    pub fn recipes(&self) -> impl Iterator<Item = Recipe<'tree>> {
+        cast_children(self.0)
+    }
+
+    pub fn assignments(&self) -> impl Iterator<Item = Assignment<'tree>> {
+        cast_children(self.0)
+    }
+
+    pub fn imports(&self) -> impl Iterator<Item = ImportStatement<'tree>> {
+        cast_children(self.0)
+    }
+
+    pub fn global_directives(&self) -> impl Iterator<Item = GlobalDirective<'tree>> {
+        cast_children(self.0)
+    }
+
+    /// Top-level comments, excluding those nested inside recipes.
+    pub fn comments(&self) -> impl Iterator<Item = Node<'tree>> {
+        named_children(self.0).filter(|node| node.kind() == kinds::COMMENT)
+    }
+
+    pub fn shebang(&self) -> Option<Node<'tree>> {
+        named_children(self.0).find(|node| node.kind() == kinds::SHEBANG)
+    }
+}
+
+/// A top-level item of a [`Jakefile`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Item<'tree> {
+    Recipe(Recipe<'tree>),
+    Assignment(Assignment<'tree>),
+    Import(ImportStatement<'tree>),
+    Directive(GlobalDirective<'tree>),
+}
+
+impl<'tree> Item<'tree> {
+    pub fn cast(node: Node<'tree>) -> Option<Self> {
+        Recipe::cast(node)
+            .map(Item::Recipe)
+            .or_else(|| Assignment::cast(node).map(Item::Assignment))
+            .or_else(|| ImportStatement::cast(node).map(Item::Import))
+            .or_else(|| GlobalDirective::cast(node).map(Item::Directive))
+    }
+
+    pub fn syntax(&self) -> Node<'tree> {
+        match self {
+            Item::Recipe(item) => item.syntax(),
+            Item::Assignment(item) => item.syntax(),
+            Item::Import(item) => item.syntax(),
+            Item::Directive(item) => item.syntax(),
+        }
+    }
+}
+
+ast_node!(
+    /// `NAME = expression` at the top level.
+    Assignment => kinds::ASSIGNMENT
+);
+
+impl<'tree> Assignment<'tree> {
+    pub fn name(&self) -> Option<Identifier<'tree>> {
+        self.0
+            .child_by_field_name(fields::NAME)
+            .and_then(Identifier::cast)
+    }
+
+    pub fn value(&self) -> Option<Expression<'tree>> {
+        self.0
+            .child_by_field_name(fields::VALUE)
+            .and_then(Expression::cast)
+    }
+
+    /// Whether the assignment uses the just-compatible `:=` operator.
+    pub fn uses_walrus(&self) -> bool {
+        self.walrus().is_some()
+    }
+
+    /// The `:=` token, if the assignment uses it.
+    pub fn walrus(&self) -> Option<Node<'tree>> {
+        children(self.0).find(|child| child.kind() == ":=")
+    }
+}
+
+ast_node!(
+    /// `@import "path" as namespace`.
+    ImportStatement => kinds::IMPORT_STATEMENT
+);
+
+impl<'tree> ImportStatement<'tree> {
+    pub fn path(&self) -> Option<StringLiteral<'tree>> {
+        self.0
+            .child_by_field_name(fields::PATH)
+            .and_then(StringLiteral::cast)
+    }
+
+    pub fn namespace(&self) -> Option<Identifier<'tree>> {
+        self.0
+            .child_by_field_name(fields::NAMESPACE)
+            .and_then(Identifier::cast)
+    }
+}
+
+/// Kind of a [`GlobalDirective`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GlobalDirectiveKind {
+    Dotenv,
+    Require,
+    Export,
+    Default,
+    Hook,
+}
+
+ast_node!(
+    /// A top-level directive such as `@dotenv`, `@export` or `@before`.
+    GlobalDirective => kinds::GLOBAL_DIRECTIVE
+);
+
+impl<'tree> GlobalDirective<'tree> {
+    /// The concrete directive node (`dotenv_directive`, `global_hook`, ...).
+    pub fn inner(&self) -> Option<Node<'tree>> {
+        self.0.named_child(0)
+    }
+
+    pub fn kind(&self) -> Option<GlobalDirectiveKind> {
+        let kind = match self.inner()?.kind() {
+            kinds::DOTENV_DIRECTIVE => GlobalDirectiveKind::Dotenv,
+            kinds::REQUIRE_DIRECTIVE => GlobalDirectiveKind::Require,
+            kinds::EXPORT_DIRECTIVE => GlobalDirectiveKind::Export,
+            kinds::DEFAULT_DIRECTIVE => GlobalDirectiveKind::Default,
+            kinds::GLOBAL_HOOK => GlobalDirectiveKind::Hook,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// The directive keyword, e.g. `@dotenv` or `@before`.
+    pub fn keyword(&self) -> Option<&'static str> {
+        keyword(self.inner()?)
+    }
+
+    pub fn hook(&self) -> Option<Hook<'tree>> {
+        self.inner().and_then(Hook::cast)
+    }
+}
+
+/// Kind of a [`Hook`], matching `hooks.Hook.Kind` in the Zig runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HookKind {
+    Pre,
+    Post,
+    OnError,
+}
+
+/// `@pre`/`@post`/`@on_error` and the targeted `@before`/`@after` hooks,
+/// both at the top level (`global_hook`) and inside a recipe (`body_hook`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Hook<'tree>(Node<'tree>);
+
+impl<'tree> AstNode<'tree> for Hook<'tree> {
+    fn cast(node: Node<'tree>) -> Option<Self> {
+        matches!(node.kind(), kinds::GLOBAL_HOOK | kinds::BODY_HOOK).then_some(Self(node))
+    }
+
+    fn syntax(&self) -> Node<'tree> {
+        self.0
+    }
+}
+
+impl<'tree> Hook<'tree> {
+    pub fn kind(&self) -> Option<HookKind> {
+        let kind = match keyword(self.0)? {
+            "@pre" | "@before" => HookKind::Pre,
+            "@post" | "@after" => HookKind::Post,
+            "@on_error" => HookKind::OnError,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// The recipe a `@before`/`@after` hook is attached to.
+    pub fn target(&self) -> Option<HookTarget<'tree>> {
+        self.0
+            .child_by_field_name(fields::TARGET)
+            .and_then(HookTarget::cast)
+    }
+
+    /// Byte range of the hook's command text.
+    pub fn command_range(&self) -> Option<Range<usize>> {
+        let parts = field_children(self.0, fields::COMMAND);
+        let first = parts.first()?;
+        let last = parts.last()?;
+        Some(first.start_byte()..last.end_byte())
+    }
+
+    /// Interpolations inside the hook command.
+    pub fn interpolations(&self) -> Vec<Interpolation<'tree>> {
+        descendants_of_kind(self.0, kinds::INTERPOLATION)
+            .into_iter()
+            .filter_map(Interpolation::cast)
+            .collect()
+    }
+}
+
+ast_node!(
+    /// The recipe name after `@before`/`@after`, such as `docker.push`.
+    HookTarget => kinds::HOOK_TARGET
+);
+
+/// Kind of a recipe, matching `parser.Recipe.Kind`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RecipeKind {
+    /// `task name:`, always runs.
+    Task,
+    /// `file output: inputs`, only runs when the output is stale.
+    File,
+    /// `name:`, a plain make-style target.
+    Simple,
+}
+
+impl RecipeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecipeKind::Task => "task",
+            RecipeKind::File => "file",
+            RecipeKind::Simple => "simple",
+        }
+    }
+}
+
+ast_node!(
+    /// A recipe with its metadata directives, header and body.
+    Recipe => kinds::RECIPE
+);
+
+impl<'tree> Recipe<'tree> {
+    pub fn header(&self) -> Option<RecipeHeader<'tree>> {
+        cast_children(self.0).next()
+    }
+
+    pub fn body(&self) -> Option<RecipeBody<'tree>> {
+        cast_children(self.0).next()
+    }
+
+    /// Metadata directives (`@group`, `@desc`, `@alias`, ...) above the header.
+    pub fn attributes(&self) -> impl Iterator<Item = RecipeAttribute<'tree>> {
+        cast_children(self.0)
+    }
+
+    pub fn kind(&self) -> RecipeKind {
+        self.header()
+            .map_or(RecipeKind::Simple, |header| header.kind())
+    }
+
+    /// The recipe name, or the output path for `file` recipes.
+    pub fn name<'src>(&self, source: &'src str) -> Option<&'src str> {
+        self.header()?
+            .name_node()
+            .map(|node| node_text(node, source))
+    }
+
+    pub fn parameters(&self) -> Vec<Parameter<'tree>> {
+        self.header()
+            .map(|header| header.parameters())
+            .unwrap_or_default()
+    }
+
+    pub fn dependencies(&self) -> Vec<Dependency<'tree>> {
+        self.header()
+            .map(|header| header.dependencies())
+            .unwrap_or_default()
+    }
+
+    /// Whether the recipe is preceded by `@default`, ignoring comments in between.
+    pub fn is_default(&self) -> bool {
+        let mut sibling = self.0.prev_named_sibling();
+        while let Some(node) = sibling {
+            match node.kind() {
+                kinds::COMMENT => sibling = node.prev_named_sibling(),
+                kinds::GLOBAL_DIRECTIVE => {
+                    return GlobalDirective(node).kind() == Some(GlobalDirectiveKind::Default)
+                }
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    /// Names given by `@alias`.
+    pub fn aliases(&self) -> Vec<Identifier<'tree>> {
+        self.attributes_of_kind(AttributeKind::Alias)
+            .flat_map(|attribute| attribute.names())
+            .filter_map(Identifier::cast)
+            .collect()
+    }
+
+    /// The `@group` name node (an identifier or a string).
+    pub fn group(&self) -> Option<Node<'tree>> {
+        self.attributes_of_kind(AttributeKind::Group)
+            .last()
+            .and_then(|attribute| attribute.names().into_iter().next())
+    }
+
+    /// The `@desc`/`@description` string.
+    pub fn description(&self) -> Option<StringLiteral<'tree>> {
+        self.attributes_of_kind(AttributeKind::Description)
+            .last()
+            .and_then(|attribute| attribute.text())
+    }
+
+    /// Platforms from `@only`, `@only-os` and `@platform`.
+    pub fn platforms(&self) -> Vec<Identifier<'tree>> {
+        self.attributes_of_kind(AttributeKind::Platform)
+            .flat_map(|attribute| attribute.platforms())
+            .collect()
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.attributes_of_kind(AttributeKind::Quiet)
+            .next()
+            .is_some()
+    }
+
+    /// The `@timeout` value in seconds, if present and well-formed.
+    pub fn timeout_seconds(&self, source: &str) -> Option<u64> {
+        self.attributes_of_kind(AttributeKind::Timeout)
+            .last()
+            .and_then(|attribute| attribute.duration())
+            .and_then(|duration| parse_duration(node_text(duration, source)))
+    }
+
+    /// Recipe-level `@needs` requirements.
+    pub fn needs(&self) -> Vec<NeedsRequirement<'tree>> {
+        self.attributes_of_kind(AttributeKind::Needs)
+            .flat_map(|attribute| needs_requirements(attribute.0))
+            .collect()
+    }
+
+    fn attributes_of_kind(
+        &self,
+        kind: AttributeKind,
+    ) -> impl Iterator<Item = RecipeAttribute<'tree>> {
+        self.attributes()
+            .filter(move |attribute| attribute.kind() == Some(kind))
+    }
+}
+
+/// Kind of a [`RecipeAttribute`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttributeKind {
+    Group,
+    Description,
+    Alias,
+    Quiet,
+    Platform,
+    Needs,
+    Timeout,
+}
+
+ast_node!(
+    /// A metadata directive that applies to the following recipe.
+    RecipeAttribute => kinds::RECIPE_ATTRIBUTE
+);
+
+impl<'tree> RecipeAttribute<'tree> {
+    pub fn kind(&self) -> Option<AttributeKind> {
+        let kind = match self.keyword()? {
+            "@group" => AttributeKind::Group,
+            "@desc" | "@description" => AttributeKind::Description,
+            "@alias" => AttributeKind::Alias,
+            "@quiet" => AttributeKind::Quiet,
+            "@only" | "@only-os" | "@platform" => AttributeKind::Platform,
+            "@needs" => AttributeKind::Needs,
+            "@timeout" => AttributeKind::Timeout,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// The directive keyword, e.g. `@group` or `@description`.
+    pub fn keyword(&self) -> Option<&'static str> {
+        keyword(self.0)
+    }
+
+    /// `name` field nodes: the group name or the alias names.
+    pub fn names(&self) -> Vec<Node<'tree>> {
+        field_children(self.0, fields::NAME)
+    }
+
+    /// `text` field of `@desc`.
+    pub fn text(&self) -> Option<StringLiteral<'tree>> {
+        self.0
+            .child_by_field_name(fields::TEXT)
+            .and_then(StringLiteral::cast)
+    }
+
+    pub fn platforms(&self) -> Vec<Identifier<'tree>> {
+        field_children(self.0, fields::PLATFORM)
+            .into_iter()
+            .filter_map(Identifier::cast)
+            .collect()
+    }
+
+    /// The `30s`/`5m`/`2h` value of `@timeout`.
+    pub fn duration(&self) -> Option<Node<'tree>> {
+        self.0.child_by_field_name(fields::DURATION)
+    }
+}
+
+/// Convert a `@timeout` value to seconds, mirroring `Parser.parseTimeoutValue`.
+///
+/// Returns `None` for a missing or unknown unit and for zero.
+pub fn parse_duration(text: &str) -> Option<u64> {
+    let unit = text.chars().last()?;
+    let value: u64 = text[..text.len() - unit.len_utf8()].parse().ok()?;
+    if value == 0 {
+        return None;
+    }
+    match unit {
+        's' => Some(value),
+        'm' => value.checked_mul(60),
+        'h' => value.checked_mul(3600),
+        _ => None,
+    }
+}
+
+/// One entry of a `@needs` directive, matching `parser.NeedsRequirement`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NeedsRequirement<'tree> {
+    pub command: Identifier<'tree>,
+    pub hint: Option<StringLiteral<'tree>>,
+    /// The recipe named after `->`.
+    pub install_task: Option<Identifier<'tree>>,
+}
+
+fn needs_requirements<'tree>(node: Node<'tree>) -> Vec<NeedsRequirement<'tree>> {
+    let mut requirements: Vec<NeedsRequirement<'tree>> = Vec::new();
+    let mut after_arrow = false;
+    for child in children(node) {
+        match child.kind() {
+            "->" => after_arrow = true,
+            kinds::IDENTIFIER if after_arrow => {
+                if let Some(last) = requirements.last_mut() {
+                    last.install_task = Some(Identifier(child));
+                }
+                after_arrow = false;
+            }
+            kinds::IDENTIFIER => requirements.push(NeedsRequirement {
+                command: Identifier(child),
+                hint: None,
+                install_task: None,
+            }),
+            kinds::STRING => {
+                if let Some(last) = requirements.last_mut() {
+                    last.hint = Some(StringLiteral(child));
+                }
+            }
+            _ => {}
+        }
+    }
+    requirements
+}
+
+ast_node!(
+    /// `task name params: [deps]`, `file output: inputs` or `name: [deps]`.
+    RecipeHeader => kinds::RECIPE_HEADER
+);
+
+impl<'tree> RecipeHeader<'tree> {
+    pub fn kind(&self) -> RecipeKind {
+        match self
+            .0
+            .child_by_field_name(fields::TYPE)
+            .map(|node| node.kind())
+        {
+            Some("task") => RecipeKind::Task,
+            Some("file") => RecipeKind::File,
+            _ => RecipeKind::Simple,
+        }
+    }
+
+    /// The `task`/`file` keyword, if present.
+    pub fn kind_keyword(&self) -> Option<Node<'tree>> {
+        self.0.child_by_field_name(fields::TYPE)
+    }
+
+    pub fn name(&self) -> Option<Identifier<'tree>> {
+        self.0
+            .child_by_field_name(fields::NAME)
+            .and_then(Identifier::cast)
+    }
+
+    /// The output path of a `file` recipe, e.g. `dist/app.min.js`.
+    pub fn output(&self) -> Option<Node<'tree>> {
+        self.0.child_by_field_name(fields::OUTPUT)
+    }
+
+    /// The node naming the recipe: its identifier, or the output of a `file` recipe.
+    pub fn name_node(&self) -> Option<Node<'tree>> {
+        self.name()
+            .map(|name| name.syntax())
+            .or_else(|| self.output())
+    }
+
+    pub fn parameters(&self) -> Vec<Parameter<'tree>> {
+        let Some(parameters) = named_children(self.0).find(|node| node.kind() == kinds::PARAMETERS)
+        else {
+            return Vec::new();
+        };
+        named_children(parameters)
+            .filter_map(|node| match node.kind() {
+                kinds::PARAMETER => Some(Parameter(node)),
+                kinds::VARIADIC_PARAMETER => named_children(node).find_map(Parameter::cast),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The bracketed `[dep, ...]` list.
+    pub fn dependency_list(&self) -> Option<Node<'tree>> {
+        named_children(self.0).find(|node| node.kind() == kinds::DEPENDENCIES)
+    }
+
+    pub fn dependencies(&self) -> Vec<Dependency<'tree>> {
+        self.dependency_list()
+            .map(|list| cast_children(list).collect())
+            .unwrap_or_default()
+    }
+
+    /// The input paths and globs of a `file` recipe, bracketed or not.
+    pub fn file_dependencies(&self) -> Vec<Node<'tree>> {
+        named_children(self.0)
+            .find(|node| node.kind() == kinds::FILE_DEPENDENCIES)
+            .map(|list| {
+                named_children(list)
+                    .filter(|node| node.kind() == kinds::FILE_DEPENDENCY)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+ast_node!(
+    /// A recipe parameter, optionally with a default value.
+    Parameter => kinds::PARAMETER
+);
+
+impl<'tree> Parameter<'tree> {
+    pub fn name(&self) -> Option<Identifier<'tree>> {
+        self.0
+            .child_by_field_name(fields::NAME)
+            .and_then(Identifier::cast)
+    }
+
+    pub fn default_value(&self) -> Option<Value<'tree>> {
+        self.0
+            .child_by_field_name(fields::DEFAULT)
+            .and_then(Value::cast)
+    }
+
+    /// The `*` or `+` of a variadic parameter.
+    pub fn kleene(&self) -> Option<&'static str> {
+        let parent = self
+            .0
+            .parent()
+            .filter(|node| node.kind() == kinds::VARIADIC_PARAMETER)?;
+        parent
+            .child_by_field_name(fields::KLEENE)
+            .map(|node| node.kind())
+    }
+
+    pub fn is_variadic(&self) -> bool {
+        self.kleene().is_some()
+    }
+}
+
+ast_node!(
+    /// An entry of a recipe's `[...]` dependency list.
+    Dependency => kinds::DEPENDENCY
+);
+
+impl<'tree> Dependency<'tree> {
+    pub fn name_node(&self) -> Option<Node<'tree>> {
+        self.0.child_by_field_name(fields::NAME)
+    }
+
+    /// The full dependency name, including any `ns:`/`ns.` prefix.
+    pub fn name<'src>(&self, source: &'src str) -> &'src str {
+        self.name_node().map_or("", |node| node_text(node, source))
+    }
+
+    /// Split the name into its namespace and recipe parts.
+    pub fn split_name<'src>(&self, source: &'src str) -> (Option<&'src str>, &'src str) {
+        split_qualified_name(self.name(source))
+    }
+
+    /// Whether the dependency names a file path (the output of a `file` recipe).
+    pub fn is_path(&self, source: &str) -> bool {
+        self.name(source).contains('/')
+    }
+}
+
+/// Split `ns:recipe` or `ns.recipe` at the last separator.
+///
+/// Paths such as `dist/app.min.js` are never split.
+pub fn split_qualified_name(name: &str) -> (Option<&str>, &str) {
+    if name.contains('/') {
+        return (None, name);
+    }
+    match name.rfind([':', '.']) {
+        Some(index) => (Some(&name[..index]), &name[index + 1..]),
+        None => (None, name),
+    }
+}
+
+ast_node!(
+    /// The indented command block of a recipe.
+    RecipeBody => kinds::RECIPE_BODY
+);
+
+impl<'tree> RecipeBody<'tree> {
+    pub fn shebang(&self) -> Option<Node<'tree>> {
+        self.0.child_by_field_name(fields::SHEBANG)
+    }
+
+    /// Directives and command lines in source order.
+    pub fn lines(&self) -> impl Iterator<Item = BodyLine<'tree>> {
+        named_children(self.0).filter_map(BodyLine::cast)
+    }
+
+    pub fn directives(&self) -> impl Iterator<Item = BodyDirective<'tree>> {
+        cast_children(self.0)
+    }
+
+    pub fn command_lines(&self) -> impl Iterator<Item = CommandLine<'tree>> {
+        cast_children(self.0)
+    }
+}
+
+/// A line of a [`RecipeBody`], matching `parser.Recipe.Command`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BodyLine<'tree> {
+    Directive(BodyDirective<'tree>),
+    Command(CommandLine<'tree>),
+}
+
+impl<'tree> BodyLine<'tree> {
+    pub fn cast(node: Node<'tree>) -> Option<Self> {
+        BodyDirective::cast(node)
+            .map(BodyLine::Directive)
+            .or_else(|| CommandLine::cast(node).map(BodyLine::Command))
+    }
+
+    pub fn syntax(&self) -> Node<'tree> {
+        match self {
+            BodyLine::Directive(line) => line.syntax(),
+            BodyLine::Command(line) => line.syntax(),
+        }
+    }
+}
+
+/// Kind of a [`BodyDirective`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BodyDirectiveKind {
+    If,
+    Elif,
+    Else,
+    End,
+    Each,
+    Cd,
+    Cache,
+    Watch,
+    Confirm,
+    Ignore,
+    Shell,
+    Needs,
+    Require,
+    Export,
+    Hook,
+    Launch,
+}
+
+ast_node!(
+    /// A directive line inside a recipe body.
+    BodyDirective => kinds::BODY_DIRECTIVE
+);
+
+impl<'tree> BodyDirective<'tree> {
+    /// The concrete directive node (`if_directive`, `cache_directive`, ...).
+    pub fn inner(&self) -> Option<Node<'tree>> {
+        self.0.named_child(0)
+    }
+
+    pub fn kind(&self) -> Option<BodyDirectiveKind> {
+        let kind = match self.inner()?.kind() {
+            kinds::IF_DIRECTIVE => BodyDirectiveKind::If,
+            kinds::ELIF_DIRECTIVE => BodyDirectiveKind::Elif,
+            kinds::ELSE_DIRECTIVE => BodyDirectiveKind::Else,
+            kinds::END_DIRECTIVE => BodyDirectiveKind::End,
+            kinds::EACH_DIRECTIVE => BodyDirectiveKind::Each,
+            kinds::CD_DIRECTIVE => BodyDirectiveKind::Cd,
+            kinds::CACHE_DIRECTIVE => BodyDirectiveKind::Cache,
+            kinds::WATCH_DIRECTIVE => BodyDirectiveKind::Watch,
+            kinds::CONFIRM_DIRECTIVE => BodyDirectiveKind::Confirm,
+            kinds::IGNORE_DIRECTIVE => BodyDirectiveKind::Ignore,
+            kinds::SHELL_DIRECTIVE => BodyDirectiveKind::Shell,
+            kinds::BODY_NEEDS_DIRECTIVE => BodyDirectiveKind::Needs,
+            kinds::BODY_REQUIRE_DIRECTIVE => BodyDirectiveKind::Require,
+            kinds::BODY_EXPORT_DIRECTIVE => BodyDirectiveKind::Export,
+            kinds::BODY_HOOK => BodyDirectiveKind::Hook,
+            kinds::LAUNCH_DIRECTIVE => BodyDirectiveKind::Launch,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// The directive keyword, e.g. `@if` or `@cache`.
+    pub fn keyword(&self) -> Option<&'static str> {
+        let inner = self.inner()?;
+        // `@else`, `@end` and `@ignore` are single-token rules.
+        if inner.child_count() == 0 {
+            return match inner.kind() {
+                kinds::ELSE_DIRECTIVE => Some("@else"),
+                kinds::END_DIRECTIVE => Some("@end"),
+                kinds::IGNORE_DIRECTIVE => Some("@ignore"),
+                _ => None,
+            };
+        }
+        keyword(inner)
+    }
+
+    /// The condition of `@if`/`@elif`.
+    pub fn condition(&self) -> Option<Node<'tree>> {
+        self.inner()?.child_by_field_name(fields::CONDITION)
+    }
+
+    /// Path arguments of `@cd` and `@cache`, or patterns of `@watch`.
+    pub fn paths(&self) -> Vec<Node<'tree>> {
+        let Some(inner) = self.inner() else {
+            return Vec::new();
+        };
+        match inner.kind() {
+            kinds::WATCH_DIRECTIVE => field_children(inner, fields::PATTERN),
+            _ => field_children(inner, fields::PATH),
+        }
+    }
+
+    /// Requirements of a body-level `@needs`.
+    pub fn needs(&self) -> Vec<NeedsRequirement<'tree>> {
+        match self.inner() {
+            Some(inner) if inner.kind() == kinds::BODY_NEEDS_DIRECTIVE => needs_requirements(inner),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn hook(&self) -> Option<Hook<'tree>> {
+        self.inner().and_then(Hook::cast)
+    }
+
+    /// Byte range of the file or URL opened by `@launch`.
+    pub fn launch_target_range(&self) -> Option<Range<usize>> {
+        let inner = self
+            .inner()
+            .filter(|node| node.kind() == kinds::LAUNCH_DIRECTIVE)?;
+        let parts = field_children(inner, fields::TARGET);
+        let first = parts.first()?;
+        let last = parts.last()?;
+        Some(first.start_byte()..last.end_byte())
+    }
+}
+
+ast_node!(
+    /// A shell command line in a recipe body.
+    CommandLine => kinds::COMMAND_LINE
+);
+
+impl<'tree> CommandLine<'tree> {
+    /// The `@`, `-`, `@-` or `-@` prefix.
+    pub fn prefix(&self) -> Option<Node<'tree>> {
+        named_children(self.0).find(|node| node.kind() == kinds::COMMAND_PREFIX)
+    }
+
+    pub fn interpolations(&self) -> impl Iterator<Item = Interpolation<'tree>> {
+        cast_children(self.0)
+    }
+}
+
+ast_node!(
+    /// `{{ expression }}`.
+    Interpolation => kinds::INTERPOLATION
+);
+
+impl<'tree> Interpolation<'tree> {
+    pub fn expression(&self) -> Option<Expression<'tree>> {
+        cast_children(self.0).next()
+    }
+}
+
+/// An expression, including the anonymous `expression` nodes that the grammar
+/// produces for the operands of `+` and `/`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Expression<'tree>(Node<'tree>);
+
+impl<'tree> AstNode<'tree> for Expression<'tree> {
+    fn cast(node: Node<'tree>) -> Option<Self> {
+        (node.kind() == kinds::EXPRESSION).then_some(Self(node))
+    }
+
+    fn syntax(&self) -> Node<'tree> {
+        self.0
+    }
+}
+
+/// The shape of an [`Expression`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExpressionKind<'tree> {
+    Value(Value<'tree>),
+    /// `lhs + rhs`
+    Concat(Expression<'tree>, Expression<'tree>),
+    /// `lhs / rhs`
+    Join(Expression<'tree>, Expression<'tree>),
+    If(IfExpression<'tree>),
+}
+
+impl<'tree> Expression<'tree> {
+    /// The shape of the expression, ignoring a leading `/` (see [`Self::is_absolute`]).
+    pub fn kind(&self) -> Option<ExpressionKind<'tree>> {
+        let nodes: Vec<Node<'tree>> = children(self.0)
+            .filter(|node| !node.is_extra())
+            .skip_while(|node| node.kind() == "/")
+            .collect();
+        match nodes.as_slice() {
+            [lhs, op, rhs]
+                if lhs.kind() == kinds::EXPRESSION && rhs.kind() == kinds::EXPRESSION =>
+            {
+                let (lhs, rhs) = (Expression(*lhs), Expression(*rhs));
+                match op.kind() {
+                    "+" => Some(ExpressionKind::Concat(lhs, rhs)),
+                    "/" => Some(ExpressionKind::Join(lhs, rhs)),
+                    _ => None,
+                }
+            }
+            [single] => match single.kind() {
+                kinds::VALUE => Some(ExpressionKind::Value(Value(*single))),
+                kinds::IF_EXPRESSION => Some(ExpressionKind::If(IfExpression(*single))),
+                kinds::EXPRESSION => Expression(*single).kind(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether the expression starts with `/`, rooting the path it builds.
+    pub fn is_absolute(&self) -> bool {
+        self.0.child(0).is_some_and(|node| node.kind() == "/")
+    }
+
+    /// Every identifier referenced by the expression as a variable.
+    pub fn variable_references(&self) -> Vec<Identifier<'tree>> {
+        descendants_of_kind(self.0, kinds::VALUE)
+            .into_iter()
+            .filter_map(|node| match Value(node).kind()? {
+                ValueKind::Identifier(identifier) => Some(identifier),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every function call in the expression.
+    pub fn function_calls(&self) -> Vec<FunctionCall<'tree>> {
+        descendants_of_kind(self.0, kinds::FUNCTION_CALL)
+            .into_iter()
+            .map(FunctionCall)
+            .collect()
+    }
+}
+
+ast_node!(
+    /// `if cond { a } else { b }`.
+    IfExpression => kinds::IF_EXPRESSION
+);
+
+impl<'tree> IfExpression<'tree> {
+    pub fn condition(&self) -> Option<Node<'tree>> {
+        named_children(self.0).find(|node| node.kind() == kinds::CONDITION)
+    }
+
+    pub fn consequence(&self) -> Option<Expression<'tree>> {
+        self.0
+            .child_by_field_name(fields::BODY)
+            .and_then(Expression::cast)
+    }
+
+    /// `else if` and `else` clauses in order.
+    pub fn alternatives(&self) -> Vec<Node<'tree>> {
+        field_children(self.0, fields::ALTERNATIVE)
+    }
+}
+
+ast_node!(
+    /// An operand: a call, backtick, identifier, path, string, shell variable
+    /// or parenthesized expression.
+    Value => kinds::VALUE
+);
+
+/// The shape of a [`Value`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValueKind<'tree> {
+    FunctionCall(FunctionCall<'tree>),
+    ExternalCommand(Node<'tree>),
+    Identifier(Identifier<'tree>),
+    /// A bare dotted name such as `Cargo.toml`, taken as written.
+    Path(Node<'tree>),
+    String(StringLiteral<'tree>),
+    ShellVariable(Node<'tree>),
+    Number(Node<'tree>),
+    Parenthesized(Expression<'tree>),
+}
+
+impl<'tree> Value<'tree> {
+    pub fn kind(&self) -> Option<ValueKind<'tree>> {
+        let node = self.0.named_child(0)?;
+        let kind = match node.kind() {
+            kinds::FUNCTION_CALL => ValueKind::FunctionCall(FunctionCall(node)),
+            kinds::EXTERNAL_COMMAND => ValueKind::ExternalCommand(node),
+            kinds::IDENTIFIER => ValueKind::Identifier(Identifier(node)),
+            kinds::PATH => ValueKind::Path(node),
+            kinds::STRING => ValueKind::String(StringLiteral(node)),
+            kinds::SHELL_VARIABLE => ValueKind::ShellVariable(node),
+            kinds::NUMERIC_ERROR => ValueKind::Number(node),
+            kinds::EXPRESSION => ValueKind::Parenthesized(Expression(node)),
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+ast_node!(
+    /// `name(arg, ...)`.
+    FunctionCall => kinds::FUNCTION_CALL
+);
+
+impl<'tree> FunctionCall<'tree> {
+    pub fn name(&self) -> Option<Identifier<'tree>> {
+        self.0
+            .child_by_field_name(fields::NAME)
+            .and_then(Identifier::cast)
+    }
+
+    /// The `sequence` node holding the arguments.
+    pub fn argument_list(&self) -> Option<Node<'tree>> {
+        self.0.child_by_field_name(fields::ARGUMENTS)
+    }
+
+    pub fn arguments(&self) -> Vec<Expression<'tree>> {
+        self.argument_list()
+            .map(|sequence| cast_children(sequence).collect())
+            .unwrap_or_default()
+    }
+}
+
+ast_node!(
+    /// A recipe, variable, parameter or namespace name.
+    Identifier => kinds::IDENTIFIER
+);
+
+ast_node!(
+    /// A quoted string in any of the four quoting styles.
+    StringLiteral => kinds::STRING
+);
+
+impl<'tree> StringLiteral<'tree> {
+    /// The string contents with quotes removed and escapes of double-quoted
+    /// strings processed, like `stripQuotes` in the Zig parser.
+    pub fn value<'src>(&self, source: &'src str) -> Cow<'src, str> {
+        unquote(self.text(source))
+    }
+}
+
+/// Strip quotes from a string literal and process escape sequences.
+pub fn unquote(text: &str) -> Cow<'_, str> {
+    for quote in ["\"\"\"", "'''", "\"", "'"] {
+        if text.len() >= 2 * quote.len() && text.starts_with(quote) && text.ends_with(quote) {
+            let inner = &text[quote.len()..text.len() - quote.len()];
+            if quote.starts_with('\'') || !inner.contains('\\') {
+                return Cow::Borrowed(inner);
+            }
+            return Cow::Owned(unescape(inner));
+        }
+    }
+    Cow::Borrowed(text)
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('\n') => {}
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// All descendants of `node` with the given kind, in document order.
+pub fn descendants_of_kind<'tree>(node: Node<'tree>, kind: &str) -> Vec<Node<'tree>> {
+    let mut result = Vec::new();
+    let mut stack: Vec<Node<'tree>> = children(node).collect();
+    stack.reverse();
+    while let Some(current) = stack.pop() {
+        if current.kind() == kind {
+            result.push(current);
+        }
+        let start = stack.len();
+        stack.extend(children(current));
+        stack[start..].reverse();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> tree_sitter::Tree {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&crate::language()).unwrap();
+        parser.parse(source, None).unwrap()
+    }
+
+    #[test]
+    fn test_kinds_exist_in_language() {
+        let language = crate::language();
+        let missing: Vec<&str> = kinds::ALL
+            .iter()
+            .copied()
+            .filter(|kind| language.id_for_node_kind(kind, true) == 0)
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "kinds missing from parser.c: {missing:?}"
+        );
+    }
+
+    #[test]
+    fn test_recipe_accessors() {
+        let source = "@default\n@group build\n@desc \"Build it\"\n@alias b\ntask build env=\"dev\" +flags: [clean, docker:push]\n    echo {{env}}\n";
+        let tree = parse(source);
+        let jakefile = Jakefile::cast(tree.root_node()).unwrap();
+        let recipe = jakefile.recipes().next().unwrap();
+
+        assert_eq!(recipe.kind(), RecipeKind::Task);
+        assert_eq!(recipe.name(source), Some("build"));
+        assert!(recipe.is_default());
+        assert_eq!(
+            recipe.group().map(|node| node_text(node, source)),
+            Some("build")
+        );
+        assert_eq!(recipe.description().unwrap().value(source), "Build it");
+        assert_eq!(recipe.aliases()[0].text(source), "b");
+
+        let params = recipe.parameters();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].name().unwrap().text(source), "env");
+        assert_eq!(params[0].default_value().unwrap().text(source), "\"dev\"");
+        assert_eq!(params[1].kleene(), Some("+"));
+
+        let deps = recipe.dependencies();
+        assert_eq!(deps[0].name(source), "clean");
+        assert_eq!(deps[1].split_name(source), (Some("docker"), "push"));
+    }
+
+    #[test]
+    fn test_top_level_items() {
+        let source = "@import \"jake/docker.jake\" as docker\nVERSION = \"1.0\"\n@dotenv\nbuild:\n    make\n";
+        let tree = parse(source);
+        let jakefile = Jakefile::cast(tree.root_node()).unwrap();
+
+        let import = jakefile.imports().next().unwrap();
+        assert_eq!(import.path().unwrap().value(source), "jake/docker.jake");
+        assert_eq!(import.namespace().unwrap().text(source), "docker");
+
+        let assignment = jakefile.assignments().next().unwrap();
+        assert_eq!(assignment.name().unwrap().text(source), "VERSION");
+        assert!(matches!(
+            assignment.value().unwrap().kind(),
+            Some(ExpressionKind::Value(_))
+        ));
+
+        let directive = jakefile.global_directives().next().unwrap();
+        assert_eq!(directive.kind(), Some(GlobalDirectiveKind::Dotenv));
+        assert_eq!(jakefile.items().count(), 4);
+    }
+
+    #[test]
+    fn test_body_lines() {
+        let source = "task build:\n    @if env(CI)\n        echo {{uppercase(name)}}\n    @end\n";
+        let tree = parse(source);
+        let jakefile = Jakefile::cast(tree.root_node()).unwrap();
+        let body = jakefile.recipes().next().unwrap().body().unwrap();
+
+        let kinds: Vec<_> = body
+            .lines()
+            .map(|line| match line {
+                BodyLine::Directive(directive) => directive.keyword().unwrap(),
+                BodyLine::Command(_) => "command",
+            })
+            .collect();
+        assert_eq!(kinds, ["@if", "command", "@end"]);
+
+        let command = body.command_lines().next().unwrap();
+        let expression = command
+            .interpolations()
+            .next()
+            .unwrap()
+            .expression()
+            .unwrap();
+        let call = expression.function_calls()[0];
+        assert_eq!(call.name().unwrap().text(source), "uppercase");
+        assert_eq!(call.arguments().len(), 1);
+    }
+
+    #[test]
+    fn test_file_recipe() {
+        let source = "file dist/app.min.js: src/*.js, src/lib/**/*.js\n    esbuild src/index.js\n\ntask deploy: [dist/app.min.js, docker.push]\n    echo done\n";
+        let tree = parse(source);
+        assert!(!tree.root_node().has_error());
+        let jakefile = Jakefile::cast(tree.root_node()).unwrap();
+        let mut recipes = jakefile.recipes();
+
+        let file = recipes.next().unwrap();
+        let header = file.header().unwrap();
+        assert_eq!(file.kind(), RecipeKind::File);
+        assert_eq!(file.name(source), Some("dist/app.min.js"));
+        assert!(header.name().is_none());
+        let inputs: Vec<_> = header
+            .file_dependencies()
+            .into_iter()
+            .map(|node| node_text(node, source))
+            .collect();
+        assert_eq!(inputs, ["src/*.js", "src/lib/**/*.js"]);
+
+        let deps = recipes.next().unwrap().dependencies();
+        assert!(deps[0].is_path(source));
+        assert_eq!(deps[0].split_name(source), (None, "dist/app.min.js"));
+        assert_eq!(deps[1].split_name(source), (Some("docker"), "push"));
+    }
+
+    #[test]
+    fn test_timeout_and_launch() {
+        let source = "@timeout 5m\ntask docs:\n    @launch https://example.com\n";
+        let tree = parse(source);
+        assert!(!tree.root_node().has_error());
+        let jakefile = Jakefile::cast(tree.root_node()).unwrap();
+        let recipe = jakefile.recipes().next().unwrap();
+
+        assert_eq!(recipe.timeout_seconds(source), Some(300));
+        let directive = recipe.body().unwrap().directives().next().unwrap();
+        assert_eq!(directive.kind(), Some(BodyDirectiveKind::Launch));
+        assert_eq!(directive.keyword(), Some("@launch"));
+        let target = directive.launch_target_range().unwrap();
+        assert_eq!(source[target].trim(), "https://example.com");
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Some(30));
+        assert_eq!(parse_duration("2h"), Some(7200));
+        assert_eq!(parse_duration("0m"), None);
+        assert_eq!(parse_duration("10d"), None);
+        assert_eq!(parse_duration("s"), None);
+    }
+
+    #[test]
+    fn test_unquote() {
+        assert_eq!(unquote("\"a\\tb\""), "a\tb");
+        assert_eq!(unquote("'raw\\n'"), "raw\\n");
+        assert_eq!(unquote("\"\"\"block\"\"\""), "block");
+        assert_eq!(unquote("bare"), "bare");
+    }
+}
@@ -0,0 +1,356 @@
+//! Nesting of `@if`/`@elif`/`@else`/`@each`/`@end` in a recipe body.
+//!
+//! The grammar parses these directives as flat siblings of the other body
+//! lines, like the Zig parser does, and the executor pairs them up while it
+//! runs. [`BlockTree`] rebuilds the same pairing statically: `@end` closes the
+//! innermost `@if` or `@each`, and a branch after `@else` never runs. Lines
+//! that do not fit are reported as [`BlockError`]s and kept in the tree as
+//! plain lines, so the structure is always usable for folding and outlines.
+//!
+//! ```
+//! use tree_sitter_jake::ast::{AstNode, Jakefile};
+//! use tree_sitter_jake::blocks::{BlockKind, BlockTree};
+//!
+//! let source = "task build:\n    @if env(CI)\n        make ci\n    @else\n        make\n    @end\n";
+//! let mut parser = tree_sitter::Parser::new();
+//! parser.set_language(&tree_sitter_jake::language()).unwrap();
+//! let tree = parser.parse(source, None).unwrap();
+//!
+//! let recipe = Jakefile::cast(tree.root_node()).unwrap().recipes().next().unwrap();
+//! let blocks = BlockTree::build(recipe.body().unwrap());
+//! assert!(blocks.errors().is_empty());
+//! let block = blocks.blocks()[0];
+//! assert_eq!(block.kind, BlockKind::If);
+//! assert_eq!(block.branches.len(), 2);
+//! ```
+
+use tree_sitter::Range;
+
+use crate::ast::{AstNode, BodyDirective, BodyDirectiveKind, BodyLine, RecipeBody};
+
+/// Kind of a [`Block`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BlockKind {
+    If,
+    Each,
+}
+
+/// An `@if` or `@each` and everything up to its `@end`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block<'tree> {
+    pub kind: BlockKind,
+    /// The `@if`/`@elif`/`@else` branches in order. An `@each` block has a
+    /// single branch.
+    pub branches: Vec<Branch<'tree>>,
+    /// The closing `@end`, `None` if the block is never closed.
+    pub end: Option<BodyDirective<'tree>>,
+}
+
+impl<'tree> Block<'tree> {
+    /// The `@if` or `@each` that opens the block.
+    pub fn opener(&self) -> BodyDirective<'tree> {
+        self.branches[0].directive
+    }
+
+    /// From the opener to the `@end`, or to the last line of an unclosed block.
+    pub fn range(&self) -> Range {
+        let start = self.opener().range();
+        let end = match self.end {
+            Some(end) => end.range(),
+            None => self.branches.last().map_or(start, Branch::range),
+        };
+        span(start, end)
+    }
+}
+
+/// One branch of a [`Block`]: the directive that starts it and the lines up
+/// to the next branch or the `@end`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Branch<'tree> {
+    /// `@if`, `@elif`, `@else` or `@each`.
+    pub directive: BodyDirective<'tree>,
+    pub children: Vec<BlockChild<'tree>>,
+    /// `false` for an `@elif` or `@else` that follows an `@else`.
+    pub is_reachable: bool,
+}
+
+impl<'tree> Branch<'tree> {
+    pub fn kind(&self) -> Option<BodyDirectiveKind> {
+        self.directive.kind()
+    }
+
+    /// From the directive to the end of its last line.
+    pub fn range(&self) -> Range {
+        let start = self.directive.range();
+        let end = self.children.last().map_or(start, BlockChild::range);
+        span(start, end)
+    }
+}
+
+/// A line of a recipe body or branch, or a nested block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockChild<'tree> {
+    Line(BodyLine<'tree>),
+    Block(Block<'tree>),
+}
+
+impl BlockChild<'_> {
+    pub fn range(&self) -> Range {
+        match self {
+            BlockChild::Line(line) => line.syntax().range(),
+            BlockChild::Block(block) => block.range(),
+        }
+    }
+}
+
+/// What is wrong with a [`BlockError::directive`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BlockErrorKind {
+    /// An `@if` or `@each` without an `@end`.
+    Unclosed,
+    /// An `@end` with no open block.
+    UnmatchedEnd,
+    /// An `@elif` or `@else` outside an `@if` block.
+    OrphanBranch,
+    /// An `@elif` or `@else` after the `@else` of the same block.
+    UnreachableBranch,
+}
+
+/// A directive that does not nest correctly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockError<'tree> {
+    pub kind: BlockErrorKind,
+    pub directive: BodyDirective<'tree>,
+}
+
+/// The block structure of one recipe body.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockTree<'tree> {
+    children: Vec<BlockChild<'tree>>,
+    errors: Vec<BlockError<'tree>>,
+}
+
+impl<'tree> BlockTree<'tree> {
+    pub fn build(body: RecipeBody<'tree>) -> Self {
+        let mut builder = Builder::default();
+        for line in body.lines() {
+            builder.line(line);
+        }
+        builder.finish()
+    }
+
+    /// Top-level lines and blocks of the body, in source order.
+    pub fn children(&self) -> &[BlockChild<'tree>] {
+        &self.children
+    }
+
+    /// Every block, outer blocks before the blocks nested in them.
+    pub fn blocks(&self) -> Vec<&Block<'tree>> {
+        let mut blocks = Vec::new();
+        collect_blocks(&self.children, &mut blocks);
+        blocks
+    }
+
+    /// Nesting errors in source order.
+    pub fn errors(&self) -> &[BlockError<'tree>] {
+        &self.errors
+    }
+}
+
+fn collect_blocks<'a, 'tree>(
+    children: &'a [BlockChild<'tree>],
+    blocks: &mut Vec<&'a Block<'tree>>,
+) {
+    for child in children {
+        if let BlockChild::Block(block) = child {
+            blocks.push(block);
+            for branch in &block.branches {
+                collect_blocks(&branch.children, blocks);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Builder<'tree> {
+    root: Vec<BlockChild<'tree>>,
+    /// Blocks that are still waiting for their `@end`, innermost last.
+    open: Vec<Block<'tree>>,
+    errors: Vec<BlockError<'tree>>,
+}
+
+impl<'tree> Builder<'tree> {
+    fn line(&mut self, line: BodyLine<'tree>) {
+        let BodyLine::Directive(directive) = line else {
+            self.push(BlockChild::Line(line));
+            return;
+        };
+        match directive.kind() {
+            Some(kind @ (BodyDirectiveKind::If | BodyDirectiveKind::Each)) => {
+                self.open.push(Block {
+                    kind: if kind == BodyDirectiveKind::If {
+                        BlockKind::If
+                    } else {
+                        BlockKind::Each
+                    },
+                    branches: vec![Branch {
+                        directive,
+                        children: Vec::new(),
+                        is_reachable: true,
+                    }],
+                    end: None,
+                });
+            }
+            Some(BodyDirectiveKind::Elif | BodyDirectiveKind::Else) => {
+                let Some(block) = self
+                    .open
+                    .last_mut()
+                    .filter(|block| block.kind == BlockKind::If)
+                else {
+                    self.error(BlockErrorKind::OrphanBranch, directive);
+                    self.push(BlockChild::Line(line));
+                    return;
+                };
+                let after_else = block
+                    .branches
+                    .iter()
+                    .any(|branch| branch.kind() == Some(BodyDirectiveKind::Else));
+                block.branches.push(Branch {
+                    directive,
+                    children: Vec::new(),
+                    is_reachable: !after_else,
+                });
+                if after_else {
+                    self.error(BlockErrorKind::UnreachableBranch, directive);
+                }
+            }
+            Some(BodyDirectiveKind::End) => match self.open.pop() {
+                Some(mut block) => {
+                    block.end = Some(directive);
+                    self.push(BlockChild::Block(block));
+                }
+                None => {
+                    self.error(BlockErrorKind::UnmatchedEnd, directive);
+                    self.push(BlockChild::Line(line));
+                }
+            },
+            _ => self.push(BlockChild::Line(line)),
+        }
+    }
+
+    fn push(&mut self, child: BlockChild<'tree>) {
+        match self.open.last_mut() {
+            Some(block) => block
+                .branches
+                .last_mut()
+                .expect("blocks start with a branch")
+                .children
+                .push(child),
+            None => self.root.push(child),
+        }
+    }
+
+    fn error(&mut self, kind: BlockErrorKind, directive: BodyDirective<'tree>) {
+        self.errors.push(BlockError { kind, directive });
+    }
+
+    fn finish(mut self) -> BlockTree<'tree> {
+        while let Some(block) = self.open.pop() {
+            self.error(BlockErrorKind::Unclosed, block.opener());
+            self.push(BlockChild::Block(block));
+        }
+        self.errors
+            .sort_by_key(|error| error.directive.byte_range().start);
+        BlockTree {
+            children: self.root,
+            errors: self.errors,
+        }
+    }
+}
+
+/// The range from the start of `start` to the end of `end`.
+fn span(start: Range, end: Range) -> Range {
+    Range {
+        start_byte: start.start_byte,
+        end_byte: end.end_byte,
+        start_point: start.start_point,
+        end_point: end.end_point,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Jakefile;
+
+    fn parse(source: &str) -> tree_sitter::Tree {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&crate::language()).unwrap();
+        parser.parse(source, None).unwrap()
+    }
+
+    fn build<'tree>(tree: &'tree tree_sitter::Tree) -> BlockTree<'tree> {
+        let jakefile = Jakefile::cast(tree.root_node()).unwrap();
+        BlockTree::build(jakefile.recipes().next().unwrap().body().unwrap())
+    }
+
+    #[test]
+    fn test_nested_blocks() {
+        let source = "task build:\n    echo start\n    @if env(CI)\n        @each a b\n            echo {{item}}\n        @end\n    @elif env(DEBUG)\n        make debug\n    @else\n        make\n    @end\n    echo done\n";
+        let tree = parse(source);
+        let blocks = build(&tree);
+
+        assert!(blocks.errors().is_empty());
+        assert_eq!(blocks.children().len(), 3);
+        let BlockChild::Block(outer) = &blocks.children()[1] else {
+            panic!("expected a block");
+        };
+        assert_eq!(outer.kind, BlockKind::If);
+        let kinds: Vec<_> = outer.branches.iter().map(Branch::kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                Some(BodyDirectiveKind::If),
+                Some(BodyDirectiveKind::Elif),
+                Some(BodyDirectiveKind::Else),
+            ]
+        );
+        let range = outer.range();
+        assert_eq!(range.start_point.row, 2);
+        assert_eq!(range.end_point.row, 10);
+
+        let all = blocks.blocks();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].kind, BlockKind::Each);
+        assert_eq!(all[1].range().end_point.row, 5);
+    }
+
+    #[test]
+    fn test_nesting_errors() {
+        let source = "task build:\n    @else\n    @end\n    @if env(CI)\n        true\n    @else\n        true\n    @elif env(DEBUG)\n        true\n    @each a b\n        echo {{item}}\n";
+        let tree = parse(source);
+        let blocks = build(&tree);
+
+        let errors: Vec<_> = blocks
+            .errors()
+            .iter()
+            .map(|error| (error.kind, error.directive.start_position().row))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (BlockErrorKind::OrphanBranch, 1),
+                (BlockErrorKind::UnmatchedEnd, 2),
+                (BlockErrorKind::Unclosed, 3),
+                (BlockErrorKind::UnreachableBranch, 7),
+                (BlockErrorKind::Unclosed, 9),
+            ]
+        );
+
+        let block = blocks.blocks()[0];
+        assert!(block.end.is_none());
+        assert!(!block.branches[2].is_reachable);
+        // The unclosed `@each` ends up inside the unreachable branch.
+        assert_eq!(block.range().end_point.row, 10);
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt::Write as _;
+
 fn main() {
     let src_dir = std::path::Path::new("src");
 
@@ -20,4 +22,92 @@ fn main() {
     c_config.file(&scanner_path);
 
     c_config.compile("parser");
+
+    generate_node_types(&src_dir.join("node-types.json"));
+}
+
+/// Generate `kinds` and `fields` constants from `node-types.json`.
+///
+/// The typed AST only refers to node kinds and field names through these
+/// constants, so renaming or removing a rule in `grammar.js` without updating
+/// the wrappers fails the build instead of silently returning `None`.
+fn generate_node_types(node_types_path: &std::path::Path) {
+    println!(
+        "cargo:rerun-if-changed={}",
+        node_types_path.to_str().unwrap()
+    );
+
+    let contents =
+        std::fs::read_to_string(node_types_path).expect("failed to read node-types.json");
+    let node_types: serde_json::Value =
+        serde_json::from_str(&contents).expect("node-types.json is not valid JSON");
+
+    let mut kinds = std::collections::BTreeSet::new();
+    let mut fields = std::collections::BTreeSet::new();
+    for node_type in node_types
+        .as_array()
+        .expect("node-types.json must be an array")
+    {
+        if node_type["named"].as_bool() != Some(true) {
+            continue;
+        }
+        if let Some(kind) = node_type["type"].as_str() {
+            kinds.insert(kind.to_string());
+        }
+        if let Some(node_fields) = node_type["fields"].as_object() {
+            fields.extend(node_fields.keys().cloned());
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str("/// Named node kinds declared in `node-types.json`.\n");
+    out.push_str("pub mod kinds {\n");
+    for kind in &kinds {
+        writeln!(
+            out,
+            "    pub const {}: &str = {:?};",
+            constant_name(kind),
+            kind
+        )
+        .unwrap();
+    }
+    assert!(
+        !kinds.iter().any(|kind| constant_name(kind) == "ALL"),
+        "a node kind collides with `kinds::ALL`"
+    );
+    out.push_str("\n    /// Every kind above, in sorted order.\n");
+    out.push_str("    pub const ALL: &[&str] = &[\n");
+    for kind in &kinds {
+        writeln!(out, "        {},", constant_name(kind)).unwrap();
+    }
+    out.push_str("    ];\n");
+    out.push_str("}\n\n");
+    out.push_str("/// Field names declared in `node-types.json`.\n");
+    out.push_str("pub mod fields {\n");
+    for field in &fields {
+        writeln!(
+            out,
+            "    pub const {}: &str = {:?};",
+            constant_name(field),
+            field
+        )
+        .unwrap();
+    }
+    out.push_str("}\n");
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is not set");
+    std::fs::write(std::path::Path::new(&out_dir).join("node_types.rs"), out)
+        .expect("failed to write node_types.rs");
+}
+
+fn constant_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
 }
@@ -0,0 +1,135 @@
+//! Built-in functions and `@if` conditions, mirroring the dispatch in
+//! `src/functions.zig` and `src/conditions.zig`.
+//!
+//! The tables are shared by the linter, which reports calls the runtime
+//! does not know, and the language server, which offers them in completion
+//! and signature help.
+
+/// A built-in function usable in `{{ }}` or a condition usable in `@if`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub parameters: &'static [&'static str],
+    /// One-line description, as in the guide's reference tables.
+    pub summary: &'static str,
+}
+
+impl Builtin {
+    /// `name(param, ...)`, as the guide writes it.
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, self.parameters.join(", "))
+    }
+}
+
+const fn builtin(
+    name: &'static str,
+    parameters: &'static [&'static str],
+    summary: &'static str,
+) -> Builtin {
+    Builtin {
+        name,
+        parameters,
+        summary,
+    }
+}
+
+/// Functions callable inside `{{ }}`, from `functions.evaluate`.
+pub const FUNCTIONS: &[Builtin] = &[
+    builtin("uppercase", &["s"], "Convert to uppercase"),
+    builtin("lowercase", &["s"], "Convert to lowercase"),
+    builtin("trim", &["s"], "Remove whitespace"),
+    builtin("dirname", &["p"], "Get directory part"),
+    builtin("basename", &["p"], "Get filename part"),
+    builtin("extension", &["p"], "Get file extension"),
+    builtin("without_extension", &["p"], "Remove last extension"),
+    builtin("without_extensions", &["p"], "Remove all extensions"),
+    builtin("absolute_path", &["p"], "Get absolute path"),
+    builtin("home", &[], "Get user home directory"),
+    builtin("local_bin", &["name"], "Get path to binary in ~/.local/bin"),
+    builtin("shell_config", &[], "Get current shell's config file"),
+    builtin(
+        "launch",
+        &["target"],
+        "The platform's command for opening a file or URL: `open` on macOS, \
+         `xdg-open` on Linux and `cmd /c start` on Windows",
+    ),
+];
+
+/// Conditions usable in `@if` and `@elif`, from `conditions.evaluate`.
+pub const CONDITIONS: &[Builtin] = &[
+    builtin(
+        "env",
+        &["VAR"],
+        "True if environment variable is set and non-empty",
+    ),
+    builtin("exists", &["path"], "True if file or directory exists"),
+    builtin("command", &["name"], "True if command exists in PATH"),
+    builtin("eq", &["a", "b"], "True if strings are equal"),
+    builtin("neq", &["a", "b"], "True if strings are not equal"),
+    builtin("is_watching", &[], "True if running in watch mode (`-w`)"),
+    builtin("is_dry_run", &[], "True if running in dry-run mode (`-n`)"),
+    builtin("is_verbose", &[], "True if running in verbose mode (`-v`)"),
+    builtin("is_macos", &[], "True if running on macOS"),
+    builtin("is_linux", &[], "True if running on Linux"),
+    builtin("is_windows", &[], "True if running on Windows"),
+    builtin("is_unix", &[], "True if running on Unix-like OS"),
+    builtin(
+        "is_platform",
+        &["name"],
+        "True if running on the specified platform",
+    ),
+];
+
+/// The function called `name`.
+pub fn function(name: &str) -> Option<&'static Builtin> {
+    FUNCTIONS.iter().find(|builtin| builtin.name == name)
+}
+
+/// The condition called `name`.
+pub fn condition(name: &str) -> Option<&'static Builtin> {
+    CONDITIONS.iter().find(|builtin| builtin.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Names the runtime dispatches on in a Zig source file.
+    fn dispatched_names(file: &str) -> Option<Vec<String>> {
+        let manifest_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        // The runtime is only available in a checkout of the main repository.
+        let source = std::fs::read_to_string(manifest_dir.join("../../src").join(file)).ok()?;
+        let mut names: Vec<String> = source
+            .split("std.mem.eql(u8, func_name, \"")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .map(str::to_string)
+            .collect();
+        names.sort();
+        Some(names)
+    }
+
+    fn sorted_names(table: &[Builtin]) -> Vec<String> {
+        let mut names: Vec<String> = table.iter().map(|builtin| builtin.name.into()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_tables_match_runtime() {
+        if let Some(names) = dispatched_names("functions.zig") {
+            assert_eq!(names, sorted_names(FUNCTIONS));
+        }
+        if let Some(names) = dispatched_names("conditions.zig") {
+            assert_eq!(names, sorted_names(CONDITIONS));
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(function("dirname").unwrap().signature(), "dirname(p)");
+        assert_eq!(condition("eq").unwrap().signature(), "eq(a, b)");
+        assert_eq!(function("eq"), None);
+        assert_eq!(condition("dirname"), None);
+    }
+}
@@ -0,0 +1,478 @@
+//! Recipe dependency graph, mirroring the scheduler in `src/parallel.zig`.
+//!
+//! [`DependencyGraph::topological_order`] reproduces the order the runtime
+//! runs a target in: the subgraph is built depth first from the target with
+//! dependencies in the order they are written, then drained with Kahn's
+//! algorithm from a FIFO queue seeded in node order.
+//!
+//! ```
+//! use tree_sitter_jake::graph::DependencyGraph;
+//! use tree_sitter_jake::index::JakefileIndex;
+//!
+//! let source = "task deploy: [test, build]\n    echo\ntask test: [build]\n    echo\ntask build:\n    echo\n";
+//! let mut parser = tree_sitter::Parser::new();
+//! parser.set_language(&tree_sitter_jake::language()).unwrap();
+//! let tree = parser.parse(source, None).unwrap();
+//!
+//! let graph = DependencyGraph::from_index(&JakefileIndex::build(&tree, source));
+//! assert_eq!(graph.topological_order("deploy").unwrap(), ["build", "test", "deploy"]);
+//! ```
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{self, Write as _};
+
+use tree_sitter::Range;
+
+use crate::index::JakefileIndex;
+use crate::workspace::{ModuleId, Workspace};
+
+/// A recipe in a [`DependencyGraph`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphNode {
+    /// Qualified recipe name, e.g. `docker.build`.
+    pub name: String,
+    /// The defining module, when built from a [`Workspace`].
+    pub module: Option<ModuleId>,
+    /// Range of the recipe name in its file.
+    pub name_range: Range,
+    /// Nodes this recipe depends on, in the order they are written.
+    pub dependencies: Vec<usize>,
+    /// Nodes that depend on this recipe.
+    pub dependents: Vec<usize>,
+}
+
+/// A dependency that names no known recipe.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnresolvedDependency {
+    /// The node whose dependency list contains the name.
+    pub recipe: usize,
+    pub name: String,
+    pub range: Range,
+}
+
+/// Why a target cannot be scheduled, mirroring `ExecuteError`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphError {
+    RecipeNotFound(String),
+    /// A cycle reachable from the target, starting and ending with the same recipe.
+    CyclicDependency(Vec<String>),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::RecipeNotFound(name) => write!(f, "recipe not found: {name}"),
+            GraphError::CyclicDependency(cycle) => {
+                write!(f, "cyclic dependency: {}", cycle.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// Recipes and the dependency edges between them.
+#[derive(Clone, Debug, Default)]
+pub struct DependencyGraph {
+    nodes: Vec<GraphNode>,
+    names: HashMap<String, usize>,
+    unresolved: Vec<UnresolvedDependency>,
+}
+
+impl DependencyGraph {
+    /// Build the graph of a single file. Dependencies on imported recipes are
+    /// reported as unresolved.
+    pub fn from_index(index: &JakefileIndex) -> Self {
+        let mut graph = Self::default();
+        for recipe in index.recipes() {
+            graph.add_node(&recipe.name, None, recipe.name_range);
+        }
+        for recipe in index.recipes() {
+            let Some(&from) = graph.names.get(&recipe.name) else {
+                continue;
+            };
+            if graph.nodes[from].name_range != recipe.name_range {
+                // A later duplicate; the runtime only sees the first definition.
+                continue;
+            }
+            for dependency in &recipe.dependencies {
+                let target = index
+                    .recipe(&dependency.name)
+                    .and_then(|entry| graph.names.get(&entry.name).copied());
+                graph.add_edge(from, target, &dependency.name, dependency.range);
+            }
+        }
+        graph
+    }
+
+    /// Build the graph of a root Jakefile and its imports, using qualified names.
+    pub fn from_workspace(workspace: &Workspace) -> Self {
+        let mut graph = Self::default();
+        let mut first_definitions = Vec::new();
+        for recipe in workspace.recipes() {
+            let before = graph.nodes.len();
+            graph.add_node(
+                &recipe.name,
+                Some(recipe.module),
+                workspace.entry(recipe).name_range,
+            );
+            if graph.nodes.len() > before {
+                first_definitions.push(recipe);
+            }
+        }
+        for recipe in first_definitions {
+            let from = graph.names[&recipe.name];
+            let written = &workspace.entry(recipe).dependencies;
+            for (name, symbol) in recipe.dependencies.iter().zip(written) {
+                let target = workspace
+                    .recipe(name)
+                    .and_then(|entry| graph.names.get(&entry.name).copied());
+                graph.add_edge(from, target, name, symbol.range);
+            }
+        }
+        graph
+    }
+
+    fn add_node(&mut self, name: &str, module: Option<ModuleId>, name_range: Range) {
+        if self.names.contains_key(name) {
+            return; // Preserve first definition behavior
+        }
+        self.names.insert(name.to_string(), self.nodes.len());
+        self.nodes.push(GraphNode {
+            name: name.to_string(),
+            module,
+            name_range,
+            dependencies: Vec::new(),
+            dependents: Vec::new(),
+        });
+    }
+
+    fn add_edge(&mut self, from: usize, to: Option<usize>, name: &str, range: Range) {
+        match to {
+            Some(to) => {
+                self.nodes[from].dependencies.push(to);
+                self.nodes[to].dependents.push(from);
+            }
+            None => self.unresolved.push(UnresolvedDependency {
+                recipe: from,
+                name: name.to_string(),
+                range,
+            }),
+        }
+    }
+
+    pub fn nodes(&self) -> &[GraphNode] {
+        &self.nodes
+    }
+
+    pub fn node(&self, name: &str) -> Option<&GraphNode> {
+        self.names.get(name).map(|&index| &self.nodes[index])
+    }
+
+    /// Dependencies that name no recipe in the graph.
+    pub fn unresolved(&self) -> &[UnresolvedDependency] {
+        &self.unresolved
+    }
+
+    /// Recipes that list `recipe` as a direct dependency.
+    pub fn dependents_of(&self, recipe: &str) -> Vec<&str> {
+        let Some(node) = self.node(recipe) else {
+            return Vec::new();
+        };
+        let mut seen = HashSet::new();
+        node.dependents
+            .iter()
+            .filter(|&&index| seen.insert(index))
+            .map(|&index| self.nodes[index].name.as_str())
+            .collect()
+    }
+
+    /// Direct dependencies of `recipe`, in the order they are written.
+    pub fn dependencies_of(&self, recipe: &str) -> Vec<&str> {
+        self.node(recipe)
+            .map(|node| {
+                node.dependencies
+                    .iter()
+                    .map(|&index| self.nodes[index].name.as_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The part of the graph `jake target` would run, with nodes in the order
+    /// `ParallelExecutor.buildGraph` adds them.
+    pub fn subgraph(&self, target: &str) -> Result<DependencyGraph, GraphError> {
+        let mut subgraph = DependencyGraph::default();
+        self.add_to_subgraph(&mut subgraph, target, None)?;
+        Ok(subgraph)
+    }
+
+    fn add_to_subgraph(
+        &self,
+        subgraph: &mut DependencyGraph,
+        name: &str,
+        dependent: Option<usize>,
+    ) -> Result<usize, GraphError> {
+        if let Some(&existing) = subgraph.names.get(name) {
+            if let Some(dependent) = dependent {
+                subgraph.nodes[existing].dependents.push(dependent);
+            }
+            return Ok(existing);
+        }
+
+        let Some(&source) = self.names.get(name) else {
+            return Err(GraphError::RecipeNotFound(name.to_string()));
+        };
+        if let Some(unresolved) = self
+            .unresolved
+            .iter()
+            .find(|unresolved| unresolved.recipe == source)
+        {
+            return Err(GraphError::RecipeNotFound(unresolved.name.clone()));
+        }
+
+        let node = &self.nodes[source];
+        let index = subgraph.nodes.len();
+        subgraph.add_node(&node.name, node.module, node.name_range);
+        if let Some(dependent) = dependent {
+            subgraph.nodes[index].dependents.push(dependent);
+        }
+        for &dependency in &node.dependencies {
+            let dependency =
+                self.add_to_subgraph(subgraph, &self.nodes[dependency].name, Some(index))?;
+            subgraph.nodes[index].dependencies.push(dependency);
+        }
+        Ok(index)
+    }
+
+    /// The order `jake target` runs recipes in, dependencies first.
+    pub fn topological_order(&self, target: &str) -> Result<Vec<&str>, GraphError> {
+        let subgraph = self.subgraph(target)?;
+        let order = subgraph.kahn_order();
+        if order.len() != subgraph.nodes.len() {
+            let cycle = subgraph.cycles().into_iter().next().unwrap_or_default();
+            return Err(GraphError::CyclicDependency(cycle));
+        }
+        Ok(order
+            .into_iter()
+            .map(|index| {
+                self.nodes[self.names[&subgraph.nodes[index].name]]
+                    .name
+                    .as_str()
+            })
+            .collect())
+    }
+
+    /// Kahn's algorithm as in `ParallelExecutor.executeSequential`. Nodes on a
+    /// cycle are left out.
+    fn kahn_order(&self) -> Vec<usize> {
+        let mut in_degrees: Vec<usize> = self
+            .nodes
+            .iter()
+            .map(|node| node.dependencies.len())
+            .collect();
+        let mut queue: VecDeque<usize> = (0..self.nodes.len())
+            .filter(|&index| in_degrees[index] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(index) = queue.pop_front() {
+            order.push(index);
+            for &dependent in &self.nodes[index].dependents {
+                in_degrees[dependent] -= 1;
+                if in_degrees[dependent] == 0 {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+        order
+    }
+
+    /// Dependency cycles, each starting and ending with the same recipe.
+    ///
+    /// Every cycle closed by a back edge of a depth-first search in node order
+    /// is reported once.
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Color {
+            White,
+            Gray,
+            Black,
+        }
+
+        let mut colors = vec![Color::White; self.nodes.len()];
+        let mut cycles = Vec::new();
+        let mut seen = HashSet::new();
+
+        for start in 0..self.nodes.len() {
+            if colors[start] != Color::White {
+                continue;
+            }
+            // Iterative DFS: (node, next dependency to visit).
+            let mut stack = vec![(start, 0)];
+            colors[start] = Color::Gray;
+            while let Some(&mut (index, ref mut next)) = stack.last_mut() {
+                let Some(&dependency) = self.nodes[index].dependencies.get(*next) else {
+                    colors[index] = Color::Black;
+                    stack.pop();
+                    continue;
+                };
+                *next += 1;
+                match colors[dependency] {
+                    Color::White => {
+                        colors[dependency] = Color::Gray;
+                        stack.push((dependency, 0));
+                    }
+                    Color::Gray => {
+                        let position = stack
+                            .iter()
+                            .position(|&(node, _)| node == dependency)
+                            .expect("gray nodes are on the stack");
+                        let mut cycle: Vec<usize> =
+                            stack[position..].iter().map(|&(node, _)| node).collect();
+                        // Rotate so the same cycle found from another node dedupes.
+                        let min = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+                        cycle.rotate_left(min);
+                        if seen.insert(cycle.clone()) {
+                            cycle.push(cycle[0]);
+                            cycles.push(
+                                cycle
+                                    .into_iter()
+                                    .map(|node| self.nodes[node].name.clone())
+                                    .collect(),
+                            );
+                        }
+                    }
+                    Color::Black => {}
+                }
+            }
+        }
+        cycles
+    }
+
+    /// Render the graph in Graphviz DOT, with an edge from each recipe to its
+    /// dependencies.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph jake {\n");
+        for node in &self.nodes {
+            writeln!(out, "    {};", dot_id(&node.name)).unwrap();
+        }
+        for node in &self.nodes {
+            for &dependency in &node.dependencies {
+                writeln!(
+                    out,
+                    "    {} -> {};",
+                    dot_id(&node.name),
+                    dot_id(&self.nodes[dependency].name)
+                )
+                .unwrap();
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render the graph as a Mermaid flowchart, with an edge from each recipe
+    /// to its dependencies.
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("graph TD\n");
+        for (index, node) in self.nodes.iter().enumerate() {
+            writeln!(
+                out,
+                "    n{index}[\"{}\"]",
+                node.name.replace('"', "#quot;")
+            )
+            .unwrap();
+        }
+        for (index, node) in self.nodes.iter().enumerate() {
+            for &dependency in &node.dependencies {
+                writeln!(out, "    n{index} --> n{dependency}").unwrap();
+            }
+        }
+        out
+    }
+}
+
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::MemoryFileSystem;
+
+    fn graph(source: &str) -> DependencyGraph {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&crate::language()).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        DependencyGraph::from_index(&JakefileIndex::build(&tree, source))
+    }
+
+    #[test]
+    fn test_topological_order_matches_scheduler() {
+        let graph = graph(
+            "task all: [lint, test, docs]\n    echo\ntask lint: [deps]\n    echo\ntask test: [deps, build]\n    echo\ntask docs:\n    echo\ntask build: [deps]\n    echo\ntask deps:\n    echo\n",
+        );
+        // Subgraph order: all, lint, deps, test, build, docs. Seeds in node
+        // order are deps and docs; dependents follow in edge order.
+        assert_eq!(
+            graph.topological_order("all").unwrap(),
+            ["deps", "docs", "lint", "build", "test", "all"]
+        );
+        assert_eq!(graph.topological_order("build").unwrap(), ["deps", "build"]);
+        assert_eq!(graph.dependents_of("deps"), ["lint", "test", "build"]);
+        assert_eq!(
+            graph.topological_order("missing"),
+            Err(GraphError::RecipeNotFound("missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_cycles() {
+        let graph = graph(
+            "task a: [b]\n    echo\ntask b: [c]\n    echo\ntask c: [a]\n    echo\ntask d: [d, nope]\n    echo\n",
+        );
+        assert_eq!(graph.cycles(), [vec!["a", "b", "c", "a"], vec!["d", "d"]]);
+        assert_eq!(
+            graph.topological_order("b"),
+            Err(GraphError::CyclicDependency(vec![
+                "b".to_string(),
+                "c".to_string(),
+                "a".to_string(),
+                "b".to_string()
+            ]))
+        );
+        assert_eq!(graph.unresolved()[0].name, "nope");
+    }
+
+    #[test]
+    fn test_workspace_graph_and_export() {
+        let mut fs = MemoryFileSystem::new();
+        fs.insert(
+            "/p/Jakefile",
+            "@import \"docker.jake\" as docker\ntask deploy: [docker.push]\n    echo\n",
+        );
+        fs.insert(
+            "/p/docker.jake",
+            "task build:\n    echo\ntask push: [build]\n    echo\n",
+        );
+        let workspace = Workspace::load(&fs, "/p/Jakefile").unwrap();
+        let graph = DependencyGraph::from_workspace(&workspace);
+
+        assert_eq!(
+            graph.topological_order("deploy").unwrap(),
+            ["docker.build", "docker.push", "deploy"]
+        );
+        assert_eq!(graph.node("docker.push").unwrap().module, Some(ModuleId(1)));
+
+        let subgraph = graph.subgraph("docker.push").unwrap();
+        assert_eq!(
+            subgraph.to_dot(),
+            "digraph jake {\n    \"docker.push\";\n    \"docker.build\";\n    \"docker.push\" -> \"docker.build\";\n}\n"
+        );
+        assert_eq!(
+            subgraph.to_mermaid(),
+            "graph TD\n    n0[\"docker.push\"]\n    n1[\"docker.build\"]\n    n0 --> n1\n"
+        );
+    }
+}
@@ -0,0 +1,493 @@
+//! Name lookup over a parsed Jakefile, mirroring `src/jakefile_index.zig`.
+//!
+//! [`JakefileIndex`] owns its data, so it can outlive the tree it was built
+//! from and be cached between edits. Every entry records the source range it
+//! was found at.
+//!
+//! ```
+//! use tree_sitter_jake::index::JakefileIndex;
+//!
+//! let source = "@alias b\ntask build:\n    make\n";
+//! let mut parser = tree_sitter::Parser::new();
+//! parser.set_language(&tree_sitter_jake::language()).unwrap();
+//! let tree = parser.parse(source, None).unwrap();
+//!
+//! let index = JakefileIndex::build(&tree, source);
+//! assert_eq!(index.recipe("b").unwrap().name, "build");
+//! assert_eq!(index.default_recipe().unwrap().name, "build");
+//! ```
+
+use std::collections::HashMap;
+
+use tree_sitter::{Point, Range, Tree};
+
+use crate::ast::{
+    node_text, unquote, AstNode, GlobalDirective, GlobalDirectiveKind, Jakefile, Recipe, RecipeKind,
+};
+
+/// A recipe and the names that refer to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecipeEntry {
+    /// The recipe name, or the output path of a `file` recipe.
+    pub name: String,
+    pub kind: RecipeKind,
+    pub aliases: Vec<Symbol>,
+    pub parameters: Vec<ParameterEntry>,
+    pub dependencies: Vec<Symbol>,
+    pub group: Option<String>,
+    pub description: Option<String>,
+    /// Whether the recipe is marked with `@default`.
+    pub is_default: bool,
+    /// The whole recipe, including its attributes and body.
+    pub range: Range,
+    /// The recipe name in the header.
+    pub name_range: Range,
+}
+
+impl RecipeEntry {
+    /// Recipes starting with `_` are hidden from `jake --list`.
+    pub fn is_private(&self) -> bool {
+        self.name.starts_with('_')
+    }
+
+    /// The entry of the same recipe after text above it grew by `bytes`
+    /// bytes and `rows` lines.
+    fn moved(&self, bytes: isize, rows: isize) -> Self {
+        let symbol = |symbol: &Symbol| Symbol {
+            name: symbol.name.clone(),
+            range: move_range(symbol.range, bytes, rows),
+        };
+        Self {
+            aliases: self.aliases.iter().map(symbol).collect(),
+            parameters: self
+                .parameters
+                .iter()
+                .map(|parameter| ParameterEntry {
+                    range: move_range(parameter.range, bytes, rows),
+                    ..parameter.clone()
+                })
+                .collect(),
+            dependencies: self.dependencies.iter().map(symbol).collect(),
+            range: move_range(self.range, bytes, rows),
+            name_range: move_range(self.name_range, bytes, rows),
+            ..self.clone()
+        }
+    }
+}
+
+/// A name and where it was written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub range: Range,
+}
+
+/// A recipe parameter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParameterEntry {
+    pub name: String,
+    /// Source text of the default value, if any.
+    pub default: Option<String>,
+    pub is_variadic: bool,
+    pub range: Range,
+}
+
+/// A top-level `NAME = value` assignment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariableEntry {
+    pub name: String,
+    /// Source text of the value expression.
+    pub value: String,
+    pub range: Range,
+    pub name_range: Range,
+}
+
+/// A top-level directive such as `@dotenv` or `@require`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirectiveEntry {
+    pub kind: GlobalDirectiveKind,
+    /// The directive keyword, e.g. `@dotenv` or `@before`.
+    pub keyword: String,
+    /// Arguments after the keyword, with string quotes removed.
+    pub args: Vec<String>,
+    pub range: Range,
+}
+
+/// An `@import "path" as namespace` statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportEntry {
+    pub path: String,
+    pub namespace: Option<String>,
+    pub range: Range,
+    pub path_range: Range,
+}
+
+/// Lookup tables for recipes, aliases, variables and directives of one file.
+#[derive(Clone, Debug, Default)]
+pub struct JakefileIndex {
+    recipes: Vec<RecipeEntry>,
+    recipe_names: HashMap<String, usize>,
+    variables: Vec<VariableEntry>,
+    variable_names: HashMap<String, usize>,
+    directives: Vec<DirectiveEntry>,
+    imports: Vec<ImportEntry>,
+    default_recipe: Option<usize>,
+    /// Length of the indexed source, to tell how far an edit moved the
+    /// recipes after it.
+    len: usize,
+}
+
+impl JakefileIndex {
+    /// Index the `source_file` at the root of `tree`.
+    pub fn build(tree: &Tree, source: &str) -> Self {
+        let mut index = Self {
+            len: source.len(),
+            ..Self::default()
+        };
+        if let Some(jakefile) = Jakefile::cast(tree.root_node()) {
+            index.populate(jakefile, source, |_| None);
+        }
+        index
+    }
+
+    /// Index `tree` after an edit to the source this index was built from.
+    ///
+    /// `changed` holds the byte ranges of the new `source` that were edited
+    /// or whose syntax changed, e.g. from [`Tree::changed_ranges`]. Recipes
+    /// before the first and after the last changed range are carried over
+    /// from this index and only moved; the recipes in between are indexed
+    /// again. Top-level assignments and directives are always re-read, as
+    /// they are short.
+    pub fn update(&self, tree: &Tree, source: &str, changed: &[std::ops::Range<usize>]) -> Self {
+        let first = changed.iter().map(|range| range.start).min();
+        let last = changed.iter().map(|range| range.end).max();
+        let delta = source.len() as isize - self.len as isize;
+
+        let mut index = Self {
+            len: source.len(),
+            ..Self::default()
+        };
+        if let Some(jakefile) = Jakefile::cast(tree.root_node()) {
+            index.populate(jakefile, source, |recipe| {
+                let range = recipe.byte_range();
+                if first.is_none_or(|first| range.end < first) {
+                    self.moved_recipe(recipe.range(), 0)
+                } else if last.is_some_and(|last| range.start > last) {
+                    self.moved_recipe(recipe.range(), delta)
+                } else {
+                    None
+                }
+            });
+        }
+        index
+    }
+
+    /// The entry of the recipe now at `range`, if it was indexed `bytes`
+    /// bytes earlier with the same extent.
+    fn moved_recipe(&self, range: Range, bytes: isize) -> Option<RecipeEntry> {
+        let start = range.start_byte.checked_add_signed(-bytes)?;
+        let position = self
+            .recipes
+            .binary_search_by_key(&start, |entry| entry.range.start_byte)
+            .ok()?;
+        let entry = &self.recipes[position];
+        let unchanged = entry.range.end_byte - entry.range.start_byte
+            == range.end_byte - range.start_byte
+            && entry.range.start_point.column == range.start_point.column;
+        let rows = range.start_point.row as isize - entry.range.start_point.row as isize;
+        unchanged.then(|| entry.moved(bytes, rows))
+    }
+
+    fn populate<'tree>(
+        &mut self,
+        jakefile: Jakefile<'tree>,
+        source: &str,
+        mut reuse: impl FnMut(Recipe<'tree>) -> Option<RecipeEntry>,
+    ) {
+        for recipe in jakefile.recipes() {
+            if let Some(entry) = reuse(recipe).or_else(|| recipe_entry(recipe, source)) {
+                self.insert_recipe(entry);
+            }
+        }
+        if self.default_recipe.is_none() && !self.recipes.is_empty() {
+            self.default_recipe = Some(0);
+        }
+
+        for assignment in jakefile.assignments() {
+            let Some(name) = assignment.name() else {
+                continue;
+            };
+            let entry = VariableEntry {
+                name: name.text(source).to_string(),
+                value: assignment
+                    .value()
+                    .map_or_else(String::new, |value| value.text(source).to_string()),
+                range: assignment.range(),
+                name_range: name.range(),
+            };
+            // Preserve first definition behavior
+            self.variable_names
+                .entry(entry.name.clone())
+                .or_insert(self.variables.len());
+            self.variables.push(entry);
+        }
+
+        self.directives = jakefile
+            .global_directives()
+            .filter_map(|directive| directive_entry(directive, source))
+            .collect();
+
+        self.imports = jakefile
+            .imports()
+            .filter_map(|import| {
+                let path = import.path()?;
+                Some(ImportEntry {
+                    path: path.value(source).into_owned(),
+                    namespace: import
+                        .namespace()
+                        .map(|namespace| namespace.text(source).to_string()),
+                    range: import.range(),
+                    path_range: path.range(),
+                })
+            })
+            .collect();
+    }
+
+    fn insert_recipe(&mut self, entry: RecipeEntry) {
+        let position = self.recipes.len();
+        // Preserve first definition behavior
+        for name in std::iter::once(&entry.name).chain(entry.aliases.iter().map(|a| &a.name)) {
+            self.recipe_names.entry(name.clone()).or_insert(position);
+        }
+        if entry.is_default && self.default_recipe.is_none() {
+            self.default_recipe = Some(position);
+        }
+        self.recipes.push(entry);
+    }
+
+    /// Look up a recipe by name or alias. The first definition wins.
+    pub fn recipe(&self, name: &str) -> Option<&RecipeEntry> {
+        self.recipe_names
+            .get(name)
+            .map(|&position| &self.recipes[position])
+    }
+
+    /// The recipe an `@alias` refers to, or `None` if `name` is not an alias.
+    pub fn resolve_alias(&self, name: &str) -> Option<&RecipeEntry> {
+        self.recipe(name).filter(|recipe| recipe.name != name)
+    }
+
+    /// All recipes in source order, including later duplicates.
+    pub fn recipes(&self) -> &[RecipeEntry] {
+        &self.recipes
+    }
+
+    /// Recipes shown by `jake --list`, in source order.
+    pub fn public_recipes(&self) -> impl Iterator<Item = &RecipeEntry> {
+        self.recipes.iter().filter(|recipe| !recipe.is_private())
+    }
+
+    /// The `@default` recipe, or the first recipe when none is marked.
+    pub fn default_recipe(&self) -> Option<&RecipeEntry> {
+        self.default_recipe.map(|position| &self.recipes[position])
+    }
+
+    /// Look up a variable. The first definition wins.
+    pub fn variable(&self, name: &str) -> Option<&VariableEntry> {
+        self.variable_names
+            .get(name)
+            .map(|&position| &self.variables[position])
+    }
+
+    /// All variable assignments in source order, including redefinitions.
+    pub fn variables(&self) -> &[VariableEntry] {
+        &self.variables
+    }
+
+    /// Top-level directives of the given kind, in source order.
+    pub fn directives(&self, kind: GlobalDirectiveKind) -> impl Iterator<Item = &DirectiveEntry> {
+        self.directives
+            .iter()
+            .filter(move |directive| directive.kind == kind)
+    }
+
+    pub fn imports(&self) -> &[ImportEntry] {
+        &self.imports
+    }
+}
+
+fn recipe_entry(recipe: Recipe<'_>, source: &str) -> Option<RecipeEntry> {
+    let header = recipe.header()?;
+    let name_node = header.name_node()?;
+    Some(RecipeEntry {
+        name: node_text(name_node, source).to_string(),
+        kind: recipe.kind(),
+        aliases: recipe
+            .aliases()
+            .into_iter()
+            .map(|alias| Symbol {
+                name: alias.text(source).to_string(),
+                range: alias.range(),
+            })
+            .collect(),
+        parameters: recipe
+            .parameters()
+            .into_iter()
+            .filter_map(|parameter| {
+                Some(ParameterEntry {
+                    name: parameter.name()?.text(source).to_string(),
+                    default: parameter
+                        .default_value()
+                        .map(|value| value.text(source).to_string()),
+                    is_variadic: parameter.is_variadic(),
+                    range: parameter.range(),
+                })
+            })
+            .collect(),
+        dependencies: recipe
+            .dependencies()
+            .into_iter()
+            .map(|dependency| Symbol {
+                name: dependency.name(source).to_string(),
+                range: dependency.range(),
+            })
+            .collect(),
+        group: recipe
+            .group()
+            .map(|group| unquote(node_text(group, source)).into_owned()),
+        description: recipe
+            .description()
+            .map(|description| description.value(source).into_owned()),
+        is_default: recipe.is_default(),
+        range: recipe.range(),
+        name_range: name_node.range(),
+    })
+}
+
+fn move_range(range: Range, bytes: isize, rows: isize) -> Range {
+    let point = |point: Point| Point::new(point.row.wrapping_add_signed(rows), point.column);
+    Range {
+        start_byte: range.start_byte.wrapping_add_signed(bytes),
+        end_byte: range.end_byte.wrapping_add_signed(bytes),
+        start_point: point(range.start_point),
+        end_point: point(range.end_point),
+    }
+}
+
+fn directive_entry(directive: GlobalDirective<'_>, source: &str) -> Option<DirectiveEntry> {
+    let inner = directive.inner()?;
+    let args = (0..inner.named_child_count())
+        .filter_map(|i| inner.named_child(i))
+        .filter(|node| node.kind() != crate::ast::kinds::COMMENT)
+        .map(|node| unquote(node_text(node, source).trim()).into_owned())
+        .collect();
+    Some(DirectiveEntry {
+        kind: directive.kind()?,
+        keyword: directive.keyword().unwrap_or_default().to_string(),
+        args,
+        range: directive.range(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(source: &str) -> JakefileIndex {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&crate::language()).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        JakefileIndex::build(&tree, source)
+    }
+
+    #[test]
+    fn test_recipes_and_aliases() {
+        let source = "task build:\n    make\n\n@default\n@alias t check\ntask test:\n    make test\n\ntask build:\n    echo duplicate\n\ntask _setup:\n    true\n";
+        let index = index(source);
+
+        assert_eq!(index.recipes().len(), 4);
+        assert_eq!(index.recipe("build").unwrap().range.start_point.row, 0);
+        assert_eq!(index.recipe("t").unwrap().name, "test");
+        assert_eq!(index.resolve_alias("check").unwrap().name, "test");
+        assert!(index.resolve_alias("test").is_none());
+        assert_eq!(index.default_recipe().unwrap().name, "test");
+
+        let name_range = index.recipe("test").unwrap().name_range;
+        assert_eq!(&source[name_range.start_byte..name_range.end_byte], "test");
+
+        assert!(index.recipe("_setup").unwrap().is_private());
+        let public: Vec<_> = index.public_recipes().map(|recipe| &recipe.name).collect();
+        assert_eq!(public, ["build", "test", "build"]);
+    }
+
+    #[test]
+    fn test_default_falls_back_to_first_recipe() {
+        let index = index("lint:\n    eslint .\n\ntask build:\n    make\n");
+        assert_eq!(index.default_recipe().unwrap().name, "lint");
+        assert!(JakefileIndex::default().default_recipe().is_none());
+    }
+
+    #[test]
+    fn test_variables_directives_and_imports() {
+        let source = "VERSION = \"1.0\"\nVERSION = \"2.0\"\n@dotenv \".env.local\"\n@require API_KEY TOKEN\n@import \"jake/docker.jake\" as docker\n";
+        let index = index(source);
+
+        assert_eq!(index.variable("VERSION").unwrap().value, "\"1.0\"");
+        assert_eq!(index.variables().len(), 2);
+
+        let dotenv: Vec<_> = index.directives(GlobalDirectiveKind::Dotenv).collect();
+        assert_eq!(dotenv[0].args, [".env.local"]);
+        let require = index
+            .directives(GlobalDirectiveKind::Require)
+            .next()
+            .unwrap();
+        assert_eq!(require.keyword, "@require");
+        assert_eq!(require.args, ["API_KEY", "TOKEN"]);
+
+        let import = &index.imports()[0];
+        assert_eq!(import.path, "jake/docker.jake");
+        assert_eq!(import.namespace.as_deref(), Some("docker"));
+    }
+
+    #[test]
+    fn test_update_matches_build() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&crate::language()).unwrap();
+        let before = "task build:\n    make\n\n@alias t\ntask test: [build]\n    make test\n\ntask deploy name=\"prod\": [test]\n    echo {{name}}\n";
+        let mut tree = parser.parse(before, None).unwrap();
+        let index = JakefileIndex::build(&tree, before);
+
+        // Add a dependency and a line to `test`.
+        let start = before.find("[build]").unwrap() + "[build".len();
+        let inserted = ", lint]\n    make lint\n    #";
+        let after = format!("{}{inserted}{}", &before[..start], &before[start + 1..]);
+        let old_end = start + 1;
+        let new_end = start + inserted.len();
+        tree.edit(&tree_sitter::InputEdit {
+            start_byte: start,
+            old_end_byte: old_end,
+            new_end_byte: new_end,
+            start_position: Point::new(4, start - before.find("task test").unwrap()),
+            old_end_position: Point::new(4, old_end - before.find("task test").unwrap()),
+            new_end_position: Point::new(6, 5),
+        });
+        let new_tree = parser.parse(&after, Some(&tree)).unwrap();
+        let changed: Vec<_> = std::iter::once(start..new_end)
+            .chain(
+                tree.changed_ranges(&new_tree)
+                    .map(|range| range.start_byte..range.end_byte),
+            )
+            .collect();
+
+        let updated = index.update(&new_tree, &after, &changed);
+        let fresh = JakefileIndex::build(&new_tree, &after);
+        assert_eq!(updated.recipes(), fresh.recipes());
+        assert_eq!(updated.recipe("t").unwrap().dependencies.len(), 2);
+        let deploy = updated.recipe("deploy").unwrap();
+        assert_eq!(deploy.range.start_point.row, 9);
+        assert_eq!(
+            &after[deploy.name_range.start_byte..deploy.name_range.end_byte],
+            "deploy"
+        );
+    }
+}
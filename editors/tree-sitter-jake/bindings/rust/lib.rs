@@ -17,6 +17,13 @@
 
 use tree_sitter::Language;
 
+pub mod ast;
+pub mod blocks;
+pub mod builtins;
+pub mod graph;
+pub mod index;
+pub mod workspace;
+
 extern "C" {
     fn tree_sitter_jake() -> Language;
 }
@@ -39,6 +46,8 @@ pub const LOCALS_QUERY: &str = include_str!("../../queries-flavored/helix/locals
 
 #[cfg(test)]
 mod tests {
+    use crate::ast::kinds;
+
     #[test]
     fn test_can_load_grammar() {
         let mut parser = tree_sitter::Parser::new();
@@ -46,4 +55,72 @@ mod tests {
             .set_language(&super::language())
             .expect("Error loading Jake language");
     }
+
+    /// Keywords the Zig lexer accepts without a leading `@`.
+    const BARE_KEYWORDS: &[&str] = &["task", "file", "as"];
+
+    #[test]
+    fn test_directives_match_lexer_keywords() {
+        let manifest_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        // The lexer is only available in a checkout of the main repository.
+        let Ok(lexer) = std::fs::read_to_string(manifest_dir.join("../../src/lexer.zig")) else {
+            return;
+        };
+        let language = super::language();
+
+        let keywords: Vec<&str> = lexer
+            .split("std.mem.eql(u8, text, \"")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .collect();
+        assert!(!keywords.is_empty(), "no keywords found in lexer.zig");
+
+        let missing: Vec<&str> = keywords
+            .into_iter()
+            .filter(|keyword| {
+                let literal = if BARE_KEYWORDS.contains(keyword) {
+                    keyword.to_string()
+                } else {
+                    format!("@{keyword}")
+                };
+                !is_token(&language, &literal)
+            })
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "lexer.zig keywords the compiled parser does not recognize: {missing:?}"
+        );
+    }
+
+    /// Whether the compiled parser lexes `literal` as a token of its own.
+    ///
+    /// Most keywords are anonymous nodes. A directive that is only its
+    /// keyword, like `@end`, is a named token instead, so those are parsed
+    /// at the top level, as a recipe attribute and inside a recipe body.
+    fn is_token(language: &tree_sitter::Language, literal: &str) -> bool {
+        if language.id_for_node_kind(literal, false) != 0 {
+            return true;
+        }
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(language).unwrap();
+        let body = "task build:\n    ";
+        [
+            (format!("{literal}\n"), 0),
+            (format!("{literal}\ntask build:\n    echo\n"), 0),
+            (format!("{body}{literal}\n"), body.len()),
+        ]
+        .iter()
+        .any(|(source, start)| {
+            let tree = parser.parse(source, None).unwrap();
+            let end = start + literal.len();
+            let node = tree.root_node().descendant_for_byte_range(*start, end);
+            node.is_some_and(|node| {
+                node.byte_range() == (*start..end)
+                    && node.child_count() == 0
+                    && node.is_named()
+                    && !node.is_error()
+                    && node.kind() != kinds::TEXT
+            })
+        })
+    }
 }
@@ -0,0 +1,819 @@
+//! Cross-file view of a Jakefile and everything it imports.
+//!
+//! [`Workspace::load`] follows `@import "path" as ns` the way `ImportResolver`
+//! in `src/import.zig` does: paths resolve relative to the importing file,
+//! recipes from a namespaced import are renamed to `ns.recipe`, dependencies
+//! that point into the imported file are renamed with them, variables keep
+//! their names, and `@default` only counts in the root file. A file that was
+//! already imported is not merged a second time.
+//!
+//! Unlike the runtime, errors do not stop resolution. Missing files and
+//! import cycles are collected as [`ImportError`]s and the rest of the module
+//! graph is still built, so editors can keep working on a broken workspace.
+//!
+//! Files are read through the [`FileSystem`] trait, which lets language
+//! servers serve unsaved buffers and tests run against a
+//! [`MemoryFileSystem`]. A [`ModuleCache`] carries parsed files from one load
+//! to the next, so files that did not change are not parsed again.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use tree_sitter::{Parser, Range, Tree};
+
+use crate::index::{JakefileIndex, RecipeEntry, VariableEntry};
+
+/// Source of Jakefile contents.
+pub trait FileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    /// Resolve `path` to the canonical form used to detect repeated imports.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+}
+
+/// The real file system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OsFileSystem;
+
+impl FileSystem for OsFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        std::fs::canonicalize(path)
+    }
+}
+
+/// An in-memory file system keyed by normalized absolute paths.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryFileSystem {
+    files: HashMap<PathBuf, String>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, path: impl AsRef<Path>, contents: impl Into<String>) {
+        self.files
+            .insert(normalize_path(path.as_ref()), contents.into());
+    }
+
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> Option<String> {
+        self.files.remove(&normalize_path(path.as_ref()))
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.files
+            .get(&normalize_path(path))
+            .cloned()
+            .ok_or_else(|| not_found(path))
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path = normalize_path(path);
+        if self.files.contains_key(&path) {
+            Ok(path)
+        } else {
+            Err(not_found(&path))
+        }
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{}: file not found", path.display()),
+    )
+}
+
+/// Resolve `.` and `..` components without touching the file system.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Rewrite `ns:recipe` to the `ns.recipe` form the runtime uses.
+///
+/// File paths are returned unchanged.
+pub fn normalize_qualified_name(name: &str) -> Cow<'_, str> {
+    if name.contains(':') && !name.contains('/') {
+        Cow::Owned(name.replace(':', "."))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// Parsed and indexed files kept between workspace loads.
+///
+/// Entries are keyed by path and only used while the file still has the
+/// contents they were parsed from.
+#[derive(Clone, Debug, Default)]
+pub struct ModuleCache {
+    files: HashMap<PathBuf, (String, Tree, JakefileIndex)>,
+}
+
+impl ModuleCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cache a file parsed elsewhere, such as an editor buffer that is
+    /// reparsed incrementally.
+    pub fn insert(
+        &mut self,
+        path: impl AsRef<Path>,
+        source: String,
+        tree: Tree,
+        index: JakefileIndex,
+    ) {
+        self.files
+            .insert(normalize_path(path.as_ref()), (source, tree, index));
+    }
+
+    pub fn remove(&mut self, path: impl AsRef<Path>) {
+        self.files.remove(&normalize_path(path.as_ref()));
+    }
+
+    /// The tree and index of `path`, if it was cached with `source`.
+    fn get(&self, path: &Path, source: &str) -> Option<(Tree, JakefileIndex)> {
+        self.files
+            .get(&normalize_path(path))
+            .filter(|(cached, _, _)| cached == source)
+            .map(|(_, tree, index)| (tree.clone(), index.clone()))
+    }
+}
+
+/// Identifies a [`Module`] within a [`Workspace`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ModuleId(pub usize);
+
+/// One parsed Jakefile.
+#[derive(Clone, Debug)]
+pub struct Module {
+    /// Canonical path of the file.
+    pub path: PathBuf,
+    pub source: String,
+    pub tree: Tree,
+    pub index: JakefileIndex,
+    /// Prefix applied to this module's recipes, e.g. `ci.docker` for a
+    /// `docker` import inside a `ci` import. `None` for the root file and
+    /// for imports without `as`.
+    pub namespace: Option<String>,
+    /// The module whose `@import` first reached this one.
+    pub importer: Option<ModuleId>,
+}
+
+/// An `@import` statement and the module it resolved to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportEdge {
+    pub from: ModuleId,
+    /// `None` when the import could not be loaded; see [`Workspace::errors`].
+    pub to: Option<ModuleId>,
+    pub namespace: Option<String>,
+    /// The import path as written.
+    pub path: String,
+    /// Range of the path string in the importing file.
+    pub path_range: Range,
+    /// Whether the target was already imported elsewhere, in which case the
+    /// runtime skips it and none of its recipes are added under `namespace`.
+    pub is_repeat: bool,
+}
+
+/// Where an imported recipe was defined, mirroring `parser.RecipeOrigin`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecipeOrigin {
+    /// The name as written in the defining file.
+    pub original_name: String,
+    /// The full namespace prefix, `None` for unprefixed recipes.
+    pub import_prefix: Option<String>,
+    /// The defining file, `None` for the root file.
+    pub source_file: Option<PathBuf>,
+}
+
+/// A recipe under the name Jake would run it by.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceRecipe {
+    /// Qualified name, e.g. `docker.build`.
+    pub name: String,
+    pub module: ModuleId,
+    /// Position of the recipe in the module's [`JakefileIndex::recipes`].
+    pub entry: usize,
+    pub origin: RecipeOrigin,
+    /// Dependency names, qualified the same way as recipe names.
+    pub dependencies: Vec<String>,
+    pub is_default: bool,
+}
+
+/// A variable and the module that defines it. Variables are never prefixed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceVariable {
+    pub name: String,
+    pub module: ModuleId,
+    /// Position of the variable in the module's [`JakefileIndex::variables`].
+    pub entry: usize,
+}
+
+/// Why an `@import` could not be followed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportErrorKind {
+    /// The imported file does not exist.
+    FileNotFound(PathBuf),
+    /// The imported file is already being imported; the paths form the cycle,
+    /// starting and ending with the same file.
+    CircularImport(Vec<PathBuf>),
+    /// The imported file exists but could not be read.
+    Io {
+        path: PathBuf,
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+/// A failed `@import`, located at the path string of the import statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportError {
+    pub kind: ImportErrorKind,
+    /// The module containing the `@import`.
+    pub importer: ModuleId,
+    /// The import path as written.
+    pub path: String,
+    pub range: Range,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ImportErrorKind::FileNotFound(path) => {
+                write!(f, "imported file not found: {}", path.display())
+            }
+            ImportErrorKind::CircularImport(cycle) => {
+                write!(f, "circular import: ")?;
+                for (i, path) in cycle.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " -> ")?;
+                    }
+                    write!(f, "{}", path.display())?;
+                }
+                Ok(())
+            }
+            ImportErrorKind::Io { path, message, .. } => {
+                write!(f, "failed to read {}: {message}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// A root Jakefile, the files it imports and the merged recipe namespace.
+#[derive(Clone, Debug)]
+pub struct Workspace {
+    modules: Vec<Module>,
+    imports: Vec<ImportEdge>,
+    recipes: Vec<WorkspaceRecipe>,
+    recipe_names: HashMap<String, usize>,
+    variables: Vec<WorkspaceVariable>,
+    variable_names: HashMap<String, usize>,
+    errors: Vec<ImportError>,
+}
+
+impl Workspace {
+    /// Load `root` and everything it imports.
+    ///
+    /// Only a failure to read `root` itself is returned as an error.
+    pub fn load(fs: &dyn FileSystem, root: impl AsRef<Path>) -> io::Result<Self> {
+        let root = root.as_ref();
+        let source = fs.read_to_string(root)?;
+        Ok(Self::with_root_source(fs, root, source))
+    }
+
+    /// Build a workspace from root contents that may differ from what `fs`
+    /// holds, such as an unsaved editor buffer.
+    pub fn with_root_source(fs: &dyn FileSystem, root: impl AsRef<Path>, source: String) -> Self {
+        Self::with_cache(fs, root, source, &mut ModuleCache::new())
+    }
+
+    /// Like [`Workspace::with_root_source`], reusing the files in `cache`
+    /// that still have the contents they were parsed from and caching the
+    /// rest.
+    pub fn with_cache(
+        fs: &dyn FileSystem,
+        root: impl AsRef<Path>,
+        source: String,
+        cache: &mut ModuleCache,
+    ) -> Self {
+        let root = root.as_ref();
+        let path = fs
+            .canonicalize(root)
+            .unwrap_or_else(|_| normalize_path(root));
+
+        let mut loader = Loader {
+            fs,
+            cache,
+            parser: None,
+            modules: Vec::new(),
+            imports: Vec::new(),
+            errors: Vec::new(),
+            stack: vec![path.clone()],
+            resolved: HashSet::new(),
+        };
+        let (_, recipes, variables) = loader.load_module(path, source, None, None);
+
+        let mut workspace = Self {
+            modules: loader.modules,
+            imports: loader.imports,
+            recipes: Vec::new(),
+            recipe_names: HashMap::new(),
+            variables,
+            variable_names: HashMap::new(),
+            errors: loader.errors,
+        };
+        for recipe in recipes {
+            workspace.insert_recipe(recipe);
+        }
+        for (position, variable) in workspace.variables.iter().enumerate() {
+            // Preserve first definition behavior
+            workspace
+                .variable_names
+                .entry(variable.name.clone())
+                .or_insert(position);
+        }
+        workspace
+    }
+
+    fn insert_recipe(&mut self, recipe: WorkspaceRecipe) {
+        let position = self.recipes.len();
+        // Aliases are registered unprefixed, as in the runtime's index.
+        let aliases = self
+            .entry(&recipe)
+            .aliases
+            .iter()
+            .map(|alias| alias.name.clone())
+            .collect::<Vec<_>>();
+        // Preserve first definition behavior
+        for name in std::iter::once(recipe.name.clone()).chain(aliases) {
+            self.recipe_names.entry(name).or_insert(position);
+        }
+        self.recipes.push(recipe);
+    }
+
+    pub fn root(&self) -> ModuleId {
+        ModuleId(0)
+    }
+
+    pub fn module(&self, id: ModuleId) -> &Module {
+        &self.modules[id.0]
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = (ModuleId, &Module)> {
+        self.modules
+            .iter()
+            .enumerate()
+            .map(|(i, module)| (ModuleId(i), module))
+    }
+
+    /// The module loaded from `path`, compared after normalization.
+    pub fn module_for_path(&self, path: &Path) -> Option<ModuleId> {
+        let path = normalize_path(path);
+        self.modules
+            .iter()
+            .position(|module| module.path == path)
+            .map(ModuleId)
+    }
+
+    /// Every `@import` statement in load order.
+    pub fn imports(&self) -> &[ImportEdge] {
+        &self.imports
+    }
+
+    /// Imports that could not be followed.
+    pub fn errors(&self) -> &[ImportError] {
+        &self.errors
+    }
+
+    /// All recipes in the order the runtime merges them.
+    pub fn recipes(&self) -> &[WorkspaceRecipe] {
+        &self.recipes
+    }
+
+    /// Look up a recipe by qualified name (`ns.recipe` or `ns:recipe`) or alias.
+    pub fn recipe(&self, name: &str) -> Option<&WorkspaceRecipe> {
+        self.recipe_names
+            .get(normalize_qualified_name(name).as_ref())
+            .map(|&position| &self.recipes[position])
+    }
+
+    /// The root file's `@default` recipe, or the first recipe.
+    pub fn default_recipe(&self) -> Option<&WorkspaceRecipe> {
+        self.recipes
+            .iter()
+            .find(|recipe| recipe.is_default)
+            .or_else(|| self.recipes.first())
+    }
+
+    /// The index entry a workspace recipe was built from.
+    pub fn entry(&self, recipe: &WorkspaceRecipe) -> &RecipeEntry {
+        &self.module(recipe.module).index.recipes()[recipe.entry]
+    }
+
+    /// All variables in merge order, including redefinitions.
+    pub fn variables(&self) -> &[WorkspaceVariable] {
+        &self.variables
+    }
+
+    /// Look up a variable. The first definition in merge order wins.
+    pub fn variable(&self, name: &str) -> Option<&WorkspaceVariable> {
+        self.variable_names
+            .get(name)
+            .map(|&position| &self.variables[position])
+    }
+
+    /// The index entry a workspace variable was built from.
+    pub fn variable_entry(&self, variable: &WorkspaceVariable) -> &VariableEntry {
+        &self.module(variable.module).index.variables()[variable.entry]
+    }
+}
+
+fn new_parser() -> Parser {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&crate::language())
+        .expect("Error loading Jake language");
+    parser
+}
+
+struct Loader<'a> {
+    fs: &'a dyn FileSystem,
+    cache: &'a mut ModuleCache,
+    /// Created on the first file that is not cached.
+    parser: Option<Parser>,
+    modules: Vec<Module>,
+    imports: Vec<ImportEdge>,
+    errors: Vec<ImportError>,
+    /// Files currently being imported, for cycle detection.
+    stack: Vec<PathBuf>,
+    /// Files that have been fully imported.
+    resolved: HashSet<PathBuf>,
+}
+
+impl Loader<'_> {
+    /// Parse one file, load its imports depth first and return its recipes
+    /// and variables merged with those of its imports.
+    fn load_module(
+        &mut self,
+        path: PathBuf,
+        source: String,
+        namespace: Option<String>,
+        importer: Option<ModuleId>,
+    ) -> (ModuleId, Vec<WorkspaceRecipe>, Vec<WorkspaceVariable>) {
+        let (tree, index) = self.parse(&path, &source);
+        let id = ModuleId(self.modules.len());
+        let source_file = importer.map(|_| path.clone());
+
+        let mut recipes: Vec<WorkspaceRecipe> = index
+            .recipes()
+            .iter()
+            .enumerate()
+            .map(|(position, entry)| WorkspaceRecipe {
+                name: entry.name.clone(),
+                module: id,
+                entry: position,
+                origin: RecipeOrigin {
+                    original_name: entry.name.clone(),
+                    import_prefix: namespace.clone(),
+                    source_file: source_file.clone(),
+                },
+                dependencies: entry
+                    .dependencies
+                    .iter()
+                    .map(|dependency| normalize_qualified_name(&dependency.name).into_owned())
+                    .collect(),
+                // Don't carry over is_default from imported files
+                is_default: importer.is_none() && entry.is_default,
+            })
+            .collect();
+        let mut variables: Vec<WorkspaceVariable> = index
+            .variables()
+            .iter()
+            .enumerate()
+            .map(|(position, entry)| WorkspaceVariable {
+                name: entry.name.clone(),
+                module: id,
+                entry: position,
+            })
+            .collect();
+
+        let imports = index.imports().to_vec();
+        let base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        self.modules.push(Module {
+            path,
+            source,
+            tree,
+            index,
+            namespace: namespace.clone(),
+            importer,
+        });
+
+        for import in imports {
+            let mut edge = ImportEdge {
+                from: id,
+                to: None,
+                namespace: import.namespace.clone(),
+                path: import.path.clone(),
+                path_range: import.path_range,
+                is_repeat: false,
+            };
+            let error = |kind| ImportError {
+                kind,
+                importer: id,
+                path: import.path.clone(),
+                range: import.path_range,
+            };
+
+            let joined = base_dir.join(&import.path);
+            let resolved = match self.fs.canonicalize(&joined) {
+                Ok(resolved) => resolved,
+                Err(err) => {
+                    self.errors.push(error(io_error_kind(joined, err)));
+                    self.imports.push(edge);
+                    continue;
+                }
+            };
+
+            if let Some(start) = self.stack.iter().position(|path| *path == resolved) {
+                let mut cycle = self.stack[start..].to_vec();
+                cycle.push(resolved);
+                self.errors
+                    .push(error(ImportErrorKind::CircularImport(cycle)));
+                self.imports.push(edge);
+                continue;
+            }
+
+            if self.resolved.contains(&resolved) {
+                // Already imported, skip
+                edge.to = self
+                    .modules
+                    .iter()
+                    .position(|module| module.path == resolved)
+                    .map(ModuleId);
+                edge.is_repeat = true;
+                self.imports.push(edge);
+                continue;
+            }
+
+            let child_source = match self.fs.read_to_string(&resolved) {
+                Ok(source) => source,
+                Err(err) => {
+                    self.errors.push(error(io_error_kind(resolved, err)));
+                    self.imports.push(edge);
+                    continue;
+                }
+            };
+
+            let child_namespace = match (&namespace, &import.namespace) {
+                (Some(outer), Some(inner)) => Some(format!("{outer}.{inner}")),
+                (outer, inner) => inner.clone().or_else(|| outer.clone()),
+            };
+            self.stack.push(resolved.clone());
+            let (child, child_recipes, child_variables) =
+                self.load_module(resolved.clone(), child_source, child_namespace, Some(id));
+            self.stack.pop();
+            self.resolved.insert(resolved);
+
+            edge.to = Some(child);
+            self.imports.push(edge);
+            recipes.extend(prefix_recipes(child_recipes, import.namespace.as_deref()));
+            variables.extend(child_variables);
+        }
+
+        (id, recipes, variables)
+    }
+
+    fn parse(&mut self, path: &Path, source: &str) -> (Tree, JakefileIndex) {
+        if let Some(cached) = self.cache.get(path, source) {
+            return cached;
+        }
+        let tree = self
+            .parser
+            .get_or_insert_with(new_parser)
+            .parse(source, None)
+            .expect("parser has a language and no timeout");
+        let index = JakefileIndex::build(&tree, source);
+        self.cache
+            .insert(path, source.to_string(), tree.clone(), index.clone());
+        (tree, index)
+    }
+}
+
+/// Rename imported recipes to `prefix.name`, along with dependencies that
+/// point at other recipes from the same import.
+fn prefix_recipes(recipes: Vec<WorkspaceRecipe>, prefix: Option<&str>) -> Vec<WorkspaceRecipe> {
+    let Some(prefix) = prefix else {
+        return recipes;
+    };
+    let imported: HashSet<String> = recipes.iter().map(|recipe| recipe.name.clone()).collect();
+    recipes
+        .into_iter()
+        .map(|mut recipe| {
+            recipe.name = format!("{prefix}.{}", recipe.name);
+            for dependency in &mut recipe.dependencies {
+                if imported.contains(dependency.as_str()) {
+                    *dependency = format!("{prefix}.{dependency}");
+                }
+            }
+            recipe
+        })
+        .collect()
+}
+
+fn io_error_kind(path: PathBuf, err: io::Error) -> ImportErrorKind {
+    match err.kind() {
+        io::ErrorKind::NotFound => ImportErrorKind::FileNotFound(path),
+        kind => ImportErrorKind::Io {
+            path,
+            kind,
+            message: err.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(files: &[(&str, &str)]) -> Workspace {
+        let mut fs = MemoryFileSystem::new();
+        for (path, contents) in files {
+            fs.insert(path, *contents);
+        }
+        Workspace::load(&fs, files[0].0).unwrap()
+    }
+
+    #[test]
+    fn test_namespaced_import() {
+        let workspace = workspace(&[
+            (
+                "/project/Jakefile",
+                "@import \"jake/docker.jake\" as docker\n\ntask deploy: [docker.push, docker:build]\n    echo deploy\n",
+            ),
+            (
+                "/project/jake/docker.jake",
+                "REGISTRY = \"ghcr.io\"\n\n@default\ntask build:\n    docker build .\n\ntask push: [build, lint]\n    docker push\n",
+            ),
+        ]);
+
+        assert!(workspace.errors().is_empty());
+        let names: Vec<_> = workspace
+            .recipes()
+            .iter()
+            .map(|recipe| recipe.name.as_str())
+            .collect();
+        assert_eq!(names, ["deploy", "docker.build", "docker.push"]);
+
+        let push = workspace.recipe("docker:push").unwrap();
+        assert_eq!(push.dependencies, ["docker.build", "lint"]);
+        assert_eq!(push.origin.original_name, "push");
+        assert_eq!(push.origin.import_prefix.as_deref(), Some("docker"));
+        assert_eq!(
+            push.origin.source_file.as_deref(),
+            Some(Path::new("/project/jake/docker.jake"))
+        );
+        let module = workspace.module(push.module);
+        let name_range = workspace.entry(push).name_range;
+        assert_eq!(
+            &module.source[name_range.start_byte..name_range.end_byte],
+            "push"
+        );
+
+        assert_eq!(
+            workspace.recipe("deploy").unwrap().dependencies,
+            ["docker.push", "docker.build"]
+        );
+        // `@default` in an imported file is ignored.
+        assert_eq!(workspace.default_recipe().unwrap().name, "deploy");
+        assert_eq!(workspace.variable("REGISTRY").unwrap().module, ModuleId(1));
+    }
+
+    #[test]
+    fn test_nested_and_repeated_imports() {
+        let workspace = workspace(&[
+            (
+                "/p/Jakefile",
+                "@import \"ci.jake\" as ci\n@import \"shared.jake\" as shared\n",
+            ),
+            (
+                "/p/ci.jake",
+                "@import \"shared.jake\" as util\n\ntask test: [util.setup]\n    make test\n",
+            ),
+            ("/p/shared.jake", "task setup:\n    mkdir -p build\n"),
+        ]);
+
+        assert!(workspace.errors().is_empty());
+        let names: Vec<_> = workspace
+            .recipes()
+            .iter()
+            .map(|recipe| recipe.name.as_str())
+            .collect();
+        assert_eq!(names, ["ci.test", "ci.util.setup"]);
+        assert_eq!(
+            workspace.recipe("ci.test").unwrap().dependencies,
+            ["ci.util.setup"]
+        );
+
+        let shared = workspace
+            .module_for_path(Path::new("/p/shared.jake"))
+            .unwrap();
+        assert_eq!(
+            workspace.module(shared).namespace.as_deref(),
+            Some("ci.util")
+        );
+        let repeat = workspace
+            .imports()
+            .iter()
+            .find(|edge| edge.is_repeat)
+            .unwrap();
+        assert_eq!(repeat.to, Some(shared));
+        assert_eq!(repeat.namespace.as_deref(), Some("shared"));
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let workspace = workspace(&[
+            (
+                "/p/Jakefile",
+                "@import \"a.jake\"\n@import \"missing.jake\" as gone\n",
+            ),
+            (
+                "/p/a.jake",
+                "@import \"./Jakefile\"\n\ntask a:\n    echo a\n",
+            ),
+        ]);
+
+        assert_eq!(workspace.recipes().len(), 1);
+        let errors = workspace.errors();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].kind,
+            ImportErrorKind::CircularImport(vec![
+                PathBuf::from("/p/Jakefile"),
+                PathBuf::from("/p/a.jake"),
+                PathBuf::from("/p/Jakefile"),
+            ])
+        );
+        assert_eq!(errors[0].importer, ModuleId(1));
+        assert_eq!(
+            errors[1].kind,
+            ImportErrorKind::FileNotFound(PathBuf::from("/p/missing.jake"))
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "imported file not found: /p/missing.jake"
+        );
+    }
+
+    #[test]
+    fn test_module_cache() {
+        let root = "@import \"docker.jake\" as docker\n";
+        let docker = "task push:\n    docker push\n";
+        let mut fs = MemoryFileSystem::new();
+        fs.insert("/p/docker.jake", docker);
+
+        let mut cache = ModuleCache::new();
+        let workspace = Workspace::with_cache(&fs, "/p/Jakefile", root.to_string(), &mut cache);
+        assert!(workspace.recipe("docker.push").is_some());
+
+        // Plant an index under the same contents to see that it is reused.
+        let mut parser = new_parser();
+        let other = "task tag:\n    docker tag\n";
+        let tree = parser.parse(other, None).unwrap();
+        let index = JakefileIndex::build(&tree, other);
+        cache.insert("/p/docker.jake", docker.to_string(), tree, index);
+        let workspace = Workspace::with_cache(&fs, "/p/Jakefile", root.to_string(), &mut cache);
+        assert!(workspace.recipe("docker.tag").is_some());
+
+        // Changed files are parsed again.
+        fs.insert("/p/docker.jake", "task build:\n    docker build\n");
+        let workspace = Workspace::with_cache(&fs, "/p/Jakefile", root.to_string(), &mut cache);
+        assert!(workspace.recipe("docker.build").is_some());
+        assert!(workspace.recipe("docker.tag").is_none());
+    }
+}
@@ -145,12 +145,15 @@ module.exports = grammar({
         ),
         seq(
           choice("@before", "@after"),
-          field("target", $.identifier),
+          field("target", $.hook_target),
           field("command", $.hook_command),
           $._newline,
         ),
       ),
 
+    // Recipe a @before/@after hook runs around: build, docker.push
+    hook_target: (_) => /[a-zA-Z_][a-zA-Z0-9_.-]*/,
+
     // Command text for hooks (everything until newline)
     hook_command: ($) => repeat1(choice($.text, $.interpolation, /[^\n]+/)),
 
@@ -215,6 +218,7 @@ module.exports = grammar({
           $.function_call,
           $.external_command,
           $.identifier,
+          $.path,
           $.string,
           $.shell_variable,
           $.numeric_error,
@@ -242,7 +246,9 @@ module.exports = grammar({
 
     // sequence      : expression ',' sequence
     //               | expression ','?
-    sequence: ($) => comma_sep1($.expression),
+    //
+    // Arguments are interpolated before the call: eq({{env}}, "production")
+    sequence: ($) => comma_sep1(choice($.expression, $.interpolation)),
 
     attribute: ($) =>
       seq(
@@ -264,7 +270,8 @@ module.exports = grammar({
       ),
 
     // Jake recipe with optional metadata directives
-    // recipe : metadata* (task|file)? NAME parameters? ':' dependencies? body?
+    // recipe : metadata* 'task'? NAME parameters? ':' dependencies? body?
+    //        | metadata* 'file' OUTPUT ':' file_dependencies? body?
     recipe: ($) =>
       seq(
         repeat($.recipe_attribute),
@@ -273,13 +280,14 @@ module.exports = grammar({
         optional($.recipe_body),
       ),
 
-    // Recipe metadata directives (@group, @desc, @alias, @needs, @quiet, @only, etc.)
+    // Recipe metadata directives (@group, @desc, @alias, @needs, @quiet, @only, @timeout, etc.)
     recipe_attribute: ($) =>
       choice(
         seq("@group", field("name", choice($.identifier, $.string)), $._newline),
         seq(choice("@desc", "@description"), field("text", $.string), $._newline),
         seq("@alias", repeat1(field("name", $.identifier)), $._newline),
         seq("@quiet", $._newline),
+        seq("@timeout", field("duration", $.duration), $._newline),
         seq(choice("@only", "@only-os", "@platform"), repeat1(field("platform", $.identifier)), $._newline),
         seq("@needs", repeat1(choice(
           seq($.identifier, "->", $.identifier),  // cmd -> install_task
@@ -288,15 +296,44 @@ module.exports = grammar({
         )), $._newline),
       ),
 
+    // Timeout value: 30s, 5m, 2h
+    duration: (_) => /\d+[smh]/,
+
     recipe_header: ($) =>
-      seq(
-        optional(field("type", choice("task", "file"))),
-        field("name", $.identifier),
-        optional($.parameters),
-        ":",
-        optional($.dependencies),
+      choice(
+        seq(
+          optional(field("type", "task")),
+          field("name", $.identifier),
+          optional($.parameters),
+          ":",
+          optional($.dependencies),
+        ),
+        // file dist/bundle.js: src/*.js src/utils.js
+        seq(
+          field("type", "file"),
+          field("output", $.output),
+          ":",
+          optional($.file_dependencies),
+        ),
+      ),
+
+    // Output path of a file recipe, which doubles as its name
+    output: (_) => /[a-zA-Z0-9_.~\/*?-]+/,
+
+    // File recipe inputs: src/a.c src/*.h, or bracketed [src/a.c, src/b.c]
+    file_dependencies: ($) =>
+      choice(
+        seq(
+          "[",
+          optional(seq(comma_sep1($.file_dependency), optional(","))),
+          "]",
+        ),
+        seq($.file_dependency, repeat(seq(optional(","), $.file_dependency))),
       ),
 
+    // Input path or glob pattern of a file recipe
+    file_dependency: (_) => /[a-zA-Z0-9_.~\/*?-]+/,
+
     parameters: ($) =>
       seq(repeat($.parameter), choice($.parameter, $.variadic_parameter)),
 
@@ -331,7 +368,8 @@ module.exports = grammar({
     dependency: ($) => field("name", $.dependency_name),
 
     // Dependency name can include namespace prefix (ns:recipe or ns.recipe)
-    dependency_name: (_) => /[a-zA-Z_][a-zA-Z0-9_:-]*/,
+    // or be the output path of a file recipe (dist/app.js)
+    dependency_name: (_) => /[a-zA-Z0-9_.\/][a-zA-Z0-9_.\/:-]*/,
 
     // body : INDENT (directive | command)+ DEDENT
     recipe_body: ($) =>
@@ -360,6 +398,7 @@ module.exports = grammar({
         $.confirm_directive,
         $.ignore_directive,
         $.shell_directive,
+        $.launch_directive,
         $.body_needs_directive,
         $.body_require_directive,
         $.body_export_directive,
@@ -413,6 +452,13 @@ module.exports = grammar({
     shell_directive: ($) =>
       seq("@shell", field("shell", $.identifier)),
 
+    // @launch https://example.com or @launch {{file}}
+    launch_directive: ($) =>
+      seq(
+        "@launch",
+        field("target", repeat1(choice($.text, $.interpolation, /[^\n]+/))),
+      ),
+
     // @needs cmd or @needs cmd "hint" or @needs cmd -> task (inside recipe body)
     body_needs_directive: ($) =>
       seq("@needs", repeat1(choice(
@@ -460,6 +506,9 @@ module.exports = grammar({
         ")",
       ),
 
+    // Bare name with a dot, such as Cargo.toml or .env, taken as written
+    path: (_) => /([a-zA-Z_][a-zA-Z0-9_-]*)?\.[a-zA-Z0-9_.-]*/,
+
     // Glob pattern (contains * or **)
     glob_pattern: (_) => /[a-zA-Z0-9_.*\/\-]+\*[a-zA-Z0-9_.*\/\-]*/,
 
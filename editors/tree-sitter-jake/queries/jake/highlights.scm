@@ -11,6 +11,12 @@
 (recipe_header
   name: (identifier) @function)
 
+; File recipe outputs and inputs
+(recipe_header
+  output: (output) @string.special)
+
+(file_dependency) @string.special
+
 ; Recipe type keywords
 (recipe_header
   type: _ @keyword)
@@ -81,9 +87,11 @@
 
 ; Numbers
 (number) @constant.numeric
+(duration) @constant.numeric
 
-; Glob patterns
+; Glob patterns and bare paths
 (glob_pattern) @string.special
+(path) @string.special
 
 ; Shebang
 (shebang) @keyword.directive
@@ -104,6 +112,7 @@
 (confirm_directive) @keyword.directive
 (ignore_directive) @keyword.directive
 (shell_directive) @keyword.directive
+(launch_directive) @keyword.directive
 (body_needs_directive) @keyword.directive
 (body_require_directive) @keyword.directive
 (body_export_directive) @keyword.directive
@@ -162,6 +162,8 @@ static bool handle_eof(TSLexer *lexer, Scanner *state,
 bool tree_sitter_jake_external_scanner_scan(void *payload, TSLexer *lexer,
                                             const bool *valid_symbols) {
   Scanner *scanner = (Scanner *)(payload);
+  // Newline tokens end just past the `\n`, so a scan at column 0 starts a line.
+  const bool line_start = lexer->get_column(lexer) == 0;
 
   if (lexer->eof(lexer)) {
     return handle_eof(lexer, scanner, valid_symbols);
@@ -229,11 +231,19 @@ bool tree_sitter_jake_external_scanner_scan(void *payload, TSLexer *lexer,
   }
 
   if (valid_symbols[TEXT]) {
-    if (lexer->get_column(lexer) == scanner->prev_indent &&
+    // Leave directives and command prefixes to the grammar at the start of a
+    // line. Lines indented past the body, inside @if and @each, can hold
+    // directives too, but a leading `-` there continues a command's flags.
+    uint32_t column = lexer->get_column(lexer);
+    if (column == scanner->prev_indent &&
         (lexer->lookahead == '\n' || lexer->lookahead == '@' ||
          lexer->lookahead == '-')) {
       return false;
     }
+    if (line_start && column > scanner->prev_indent &&
+        lexer->lookahead == '@') {
+      return false;
+    }
 
     bool advanced_once = false;
 
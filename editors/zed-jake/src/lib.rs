@@ -1,8 +1,13 @@
 use zed_extension_api::{
-    self as zed, SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput,
+    self as zed,
+    serde_json::{self, Value},
+    settings::LspSettings,
+    LanguageServerId, SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput,
     SlashCommandOutputSection, Worktree,
 };
 
+const SERVER_BINARY: &str = "jake-language-server";
+
 struct JakeExtension;
 
 impl zed::Extension for JakeExtension {
@@ -10,6 +15,60 @@ impl zed::Extension for JakeExtension {
         JakeExtension
     }
 
+    fn language_server_command(
+        &mut self,
+        language_server_id: &LanguageServerId,
+        worktree: &Worktree,
+    ) -> zed::Result<zed::Command> {
+        // `lsp.jake-language-server.binary` in the Zed settings wins over
+        // the binary on the worktree's PATH.
+        let binary = LspSettings::for_worktree(language_server_id.as_ref(), worktree)
+            .ok()
+            .and_then(|settings| settings.binary);
+        let command = binary
+            .as_ref()
+            .and_then(|binary| binary.path.clone())
+            .or_else(|| worktree.which(SERVER_BINARY))
+            .ok_or_else(|| {
+                format!(
+                    "{SERVER_BINARY} not found on PATH; install it or set \
+                     lsp.{SERVER_BINARY}.binary.path in your settings"
+                )
+            })?;
+        let args = binary
+            .and_then(|binary| binary.arguments)
+            .unwrap_or_default();
+
+        Ok(zed::Command {
+            command,
+            args,
+            env: worktree.shell_env(),
+        })
+    }
+
+    fn language_server_initialization_options(
+        &mut self,
+        language_server_id: &LanguageServerId,
+        worktree: &Worktree,
+    ) -> zed::Result<Option<Value>> {
+        // Passed through as is, e.g. `jakePath` and `lint` severities.
+        let mut options = LspSettings::for_worktree(language_server_id.as_ref(), worktree)
+            .ok()
+            .and_then(|settings| settings.initialization_options)
+            .unwrap_or_else(|| Value::Object(serde_json::Map::new()));
+
+        // Run recipes with the jake the worktree's shell would find, which
+        // may not be on the PATH Zed was started with.
+        if let Some(options) = options.as_object_mut() {
+            if !options.contains_key("jakePath") {
+                if let Some(jake) = worktree.which("jake") {
+                    options.insert("jakePath".to_string(), Value::String(jake));
+                }
+            }
+        }
+        Ok(Some(options))
+    }
+
     fn complete_slash_command_argument(
         &self,
         command: SlashCommand,